@@ -1,15 +1,91 @@
+#[cfg(feature = "bindgen")]
 use std::env;
+#[cfg(feature = "bindgen")]
+use std::fs;
 
 use std::path::PathBuf;
 
+#[cfg(not(feature = "vendored"))]
+const LIBLTC_DIR: &str = "/usr/local/lib";
+const LIBLTC_VERSION: &str = "1.3.2";
+#[cfg(feature = "bindgen")]
+const COMMITTED_BINDINGS: &str = "src/raw/bindings.rs";
+// Set to make a difference to the committed bindings an error instead of a warning
+#[cfg(feature = "bindgen")]
+const CHECK_BINDINGS_ENV: &str = "LIBLTC_CHECK_BINDINGS";
+// Where the vendored feature expects the libltc sources
+#[cfg(feature = "vendored")]
+const VENDORED_DIR: &str = "libltc/src";
+
 fn main() {
-    println!("cargo:rustc-link-search=native=/usr/local/lib");
+    println!("cargo:rustc-check-cfg=cfg(libltc)");
+
+    let include_paths = link_libltc();
+    if include_paths.is_some() {
+        println!("cargo:rustc-cfg=libltc");
+    }
+
+    #[cfg(feature = "bindgen")]
+    generate_bindings(include_paths.unwrap_or_default());
+}
+
+/// Links a system libltc, returns the paths to search for ltc.h or None if it isn't linked
+#[cfg(not(feature = "vendored"))]
+fn link_libltc() -> Option<Vec<PathBuf>> {
+    if let Ok(lib) = pkg_config::Config::new()
+        .atleast_version(LIBLTC_VERSION)
+        .probe("ltc")
+    {
+        return Some(lib.include_paths);
+    }
+
+    // With pure-rust nothing calls into libltc, it is only linked (if present) for the
+    // comparison tests
+    let found = ["libltc.so", "libltc.a"]
+        .iter()
+        .any(|lib| std::path::Path::new(LIBLTC_DIR).join(lib).exists());
+    if cfg!(feature = "pure-rust") && !found {
+        return None;
+    }
+    println!("cargo:rustc-link-search=native={LIBLTC_DIR}");
     println!("cargo:rustc-link-lib=ltc"); // Links to libltc.so
+    Some(vec![])
+}
+
+/// Builds the libltc sources and links them statically
+#[cfg(feature = "vendored")]
+fn link_libltc() -> Option<Vec<PathBuf>> {
+    let src = PathBuf::from(VENDORED_DIR);
+    if !src.join("ltc.h").exists() {
+        panic!(
+            "The vendored feature needs the libltc {LIBLTC_VERSION} sources in {VENDORED_DIR}, \
+             run scripts/vendor-libltc.sh"
+        );
+    }
+    println!("cargo:rerun-if-changed={VENDORED_DIR}");
 
+    cc::Build::new()
+        .files(["ltc.c", "decoder.c", "encoder.c", "timecode.c"].map(|file| src.join(file)))
+        .include(&src)
+        .warnings(false)
+        // Also emits the link lines for libltc.a
+        .compile("ltc");
+    Some(vec![src])
+}
+
+#[cfg(feature = "bindgen")]
+fn generate_bindings(include_paths: Vec<PathBuf>) {
     let bindings = bindgen::Builder::default()
         // The input header we would like to generate
         // bindings for.
         .header("wrapper.h")
+        // core::ffi types, the crate can be no_std
+        .use_core()
+        .clang_args(
+            include_paths
+                .iter()
+                .map(|path| format!("-I{}", path.display())),
+        )
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -24,4 +100,18 @@ fn main() {
     bindings
         .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
+
+    // A newer ltc.h than the committed bindings, see the README to update them
+    println!("cargo:rerun-if-env-changed={CHECK_BINDINGS_ENV}");
+    let generated = fs::read_to_string(out_path.join("bindings.rs")).unwrap_or_default();
+    if fs::read_to_string(COMMITTED_BINDINGS).unwrap_or_default() != generated {
+        let message = format!(
+            "The generated bindings differ from {COMMITTED_BINDINGS}, see {}",
+            out_path.join("bindings.rs").display()
+        );
+        if env::var_os(CHECK_BINDINGS_ENV).is_some() {
+            panic!("{message}");
+        }
+        println!("cargo:warning={message}");
+    }
 }
@@ -73,7 +73,7 @@ fn main() {
 
     let st = SMPTETimecode::new(timezone, 8, 12, 31, 23, 59, 59, 0);
 
-    println!("{}", &st.timezone());
+    println!("{}", st.timezone());
     let flags = *LtcBgFlags::default().set(LtcBgFlagsKind::LTC_USE_DATE);
 
     // Initialize the LTC Encoder
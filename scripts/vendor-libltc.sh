@@ -0,0 +1,16 @@
+#!/bin/sh
+# Copies the libltc sources and license into libltc/ for the vendored feature
+set -eu
+
+VERSION=1.3.2
+URL="https://github.com/x42/libltc/releases/download/v$VERSION/libltc-$VERSION.tar.gz"
+
+cd "$(dirname "$0")/.."
+tmp=$(mktemp -d)
+trap 'rm -rf "$tmp"' EXIT
+
+curl -sSfL "$URL" | tar xz -C "$tmp"
+rm -rf libltc
+mkdir -p libltc/src
+cp "$tmp/libltc-$VERSION"/src/*.c "$tmp/libltc-$VERSION"/src/*.h libltc/src/
+cp "$tmp/libltc-$VERSION/COPYING" libltc/
@@ -0,0 +1,304 @@
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use super::consts::LtcBgFlags;
+use super::encoder::LTCEncoder;
+use super::frame::LTCFrameExt;
+use super::{LTCTVStandard, SMPTETimecode, Timezone};
+use crate::error::NetError;
+
+/// UDP port Art-Net nodes listen on
+pub const ARTNET_PORT: u16 = 6454;
+
+const ARTNET_ID: &[u8; 8] = b"Art-Net\0";
+const OP_TIMECODE: u16 = 0x9700;
+const PROTOCOL_VERSION: u16 = 14;
+const TIMECODE_PACKET_LEN: usize = 19;
+
+/// The Type field of ArtTimeCode
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum ArtTimecodeType {
+    /// 24 fps
+    Film = 0,
+    /// 25 fps
+    #[default]
+    Ebu = 1,
+    /// 29.97 drop frame
+    Df = 2,
+    /// 30 fps
+    Smpte = 3,
+}
+
+impl ArtTimecodeType {
+    pub fn from_standard(standard: LTCTVStandard, drop_frame: bool) -> Self {
+        match standard {
+            LTCTVStandard::LTCTV_625_50 => ArtTimecodeType::Ebu,
+            LTCTVStandard::LTCTV_FILM_24 => ArtTimecodeType::Film,
+            _ if drop_frame => ArtTimecodeType::Df,
+            _ => ArtTimecodeType::Smpte,
+        }
+    }
+
+    pub fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => Some(ArtTimecodeType::Film),
+            1 => Some(ArtTimecodeType::Ebu),
+            2 => Some(ArtTimecodeType::Df),
+            3 => Some(ArtTimecodeType::Smpte),
+            _ => None,
+        }
+    }
+
+    pub fn standard(&self) -> LTCTVStandard {
+        match self {
+            ArtTimecodeType::Film => LTCTVStandard::LTCTV_FILM_24,
+            ArtTimecodeType::Ebu => LTCTVStandard::LTCTV_625_50,
+            _ => LTCTVStandard::LTCTV_525_60,
+        }
+    }
+}
+
+/// An ArtTimeCode packet
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ArtTimecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frames: u8,
+    pub kind: ArtTimecodeType,
+    /// 0 for the master timecode stream
+    pub stream_id: u8,
+}
+
+impl ArtTimecode {
+    pub fn from_timecode(timecode: &SMPTETimecode, kind: ArtTimecodeType) -> Self {
+        ArtTimecode {
+            hours: timecode.hours(),
+            minutes: timecode.minutes(),
+            seconds: timecode.seconds(),
+            frames: timecode.frame(),
+            kind,
+            stream_id: 0,
+        }
+    }
+
+    pub fn to_timecode(&self) -> SMPTETimecode {
+        SMPTETimecode::new(
+            Timezone::default(),
+            0,
+            0,
+            0,
+            self.hours,
+            self.minutes,
+            self.seconds,
+            self.frames,
+        )
+    }
+
+    pub fn to_bytes(&self) -> [u8; TIMECODE_PACKET_LEN] {
+        let mut packet = [0; TIMECODE_PACKET_LEN];
+        packet[..8].copy_from_slice(ARTNET_ID);
+        packet[8..10].copy_from_slice(&OP_TIMECODE.to_le_bytes());
+        packet[10..12].copy_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+        // packet[12] is a filler
+        packet[13] = self.stream_id;
+        packet[14] = self.frames;
+        packet[15] = self.seconds;
+        packet[16] = self.minutes;
+        packet[17] = self.hours;
+        packet[18] = self.kind as u8;
+        packet
+    }
+
+    pub fn from_bytes(packet: &[u8]) -> Result<Self, NetError> {
+        if packet.len() < 10 || &packet[..8] != ARTNET_ID {
+            return Err(NetError::InvalidPacket("not an Art-Net packet"));
+        }
+        if u16::from_le_bytes([packet[8], packet[9]]) != OP_TIMECODE {
+            return Err(NetError::InvalidPacket("not an ArtTimeCode packet"));
+        }
+        if packet.len() < TIMECODE_PACKET_LEN {
+            return Err(NetError::InvalidPacket("ArtTimeCode packet too short"));
+        }
+        let kind = ArtTimecodeType::from_code(packet[18])
+            .ok_or(NetError::InvalidPacket("unknown timecode type"))?;
+        let (frames, seconds, minutes, hours) = (packet[14], packet[15], packet[16], packet[17]);
+        if hours > 23 || minutes > 59 || seconds > 59 || frames > 29 {
+            return Err(NetError::InvalidPacket("timecode out of range"));
+        }
+        Ok(ArtTimecode {
+            hours,
+            minutes,
+            seconds,
+            frames,
+            kind,
+            stream_id: packet[13],
+        })
+    }
+}
+
+// A socket on an ephemeral port of the target's address family, allowed to broadcast
+pub(crate) fn sender_socket(
+    target: impl ToSocketAddrs,
+) -> Result<(UdpSocket, SocketAddr), NetError> {
+    let target = target
+        .to_socket_addrs()?
+        .next()
+        .ok_or(NetError::InvalidPacket("no target address"))?;
+    let bind: SocketAddr = if target.is_ipv4() {
+        ([0, 0, 0, 0], 0).into()
+    } else {
+        ([0u16; 8], 0).into()
+    };
+    let socket = UdpSocket::bind(bind)?;
+    socket.set_broadcast(true)?;
+    Ok((socket, target))
+}
+
+/// Sends an ArtTimeCode packet for every frame of a decode or generator session
+#[derive(Debug)]
+pub struct ArtNetSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    kind: ArtTimecodeType,
+    stream_id: u8,
+}
+
+impl ArtNetSender {
+    /// Sends to `target`, e.g. `("2.255.255.255", ARTNET_PORT)` or a single node
+    pub fn new(target: impl ToSocketAddrs, kind: ArtTimecodeType) -> Result<Self, NetError> {
+        let (socket, target) = sender_socket(target)?;
+        Ok(ArtNetSender {
+            socket,
+            target,
+            kind,
+            stream_id: 0,
+        })
+    }
+
+    pub fn set_stream_id(&mut self, stream_id: u8) {
+        self.stream_id = stream_id;
+    }
+
+    pub fn send(&self, packet: &ArtTimecode) -> Result<(), NetError> {
+        self.socket.send_to(&packet.to_bytes(), self.target)?;
+        Ok(())
+    }
+
+    pub fn send_timecode(&self, timecode: &SMPTETimecode) -> Result<(), NetError> {
+        self.send(&ArtTimecode {
+            stream_id: self.stream_id,
+            ..ArtTimecode::from_timecode(timecode, self.kind)
+        })
+    }
+
+    /// A frame read from `LTCDecoder`
+    pub fn send_frame(&self, frame: &LTCFrameExt) -> Result<(), NetError> {
+        self.send_timecode(&frame.ltc().to_timecode(LtcBgFlags::default()))
+    }
+
+    /// The frame the encoder is about to encode
+    pub fn send_encoder(&self, encoder: &LTCEncoder) -> Result<(), NetError> {
+        self.send_timecode(&encoder.get_timecode())
+    }
+}
+
+/// Receives ArtTimeCode packets, other Art-Net traffic is skipped
+#[derive(Debug)]
+pub struct ArtNetReceiver {
+    socket: UdpSocket,
+}
+
+impl ArtNetReceiver {
+    /// Listens on `addr`, usually `("0.0.0.0", ARTNET_PORT)`
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, NetError> {
+        Ok(ArtNetReceiver {
+            socket: UdpSocket::bind(addr)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    /// The underlying socket, e.g. to set a read timeout
+    pub fn socket(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    /// Blocks until a timecode packet arrives
+    pub fn recv(&self) -> Result<(ArtTimecode, SocketAddr), NetError> {
+        let mut buf = [0; 1024];
+        loop {
+            let (len, from) = self.socket.recv_from(&mut buf)?;
+            match ArtTimecode::from_bytes(&buf[..len]) {
+                Ok(packet) => return Ok((packet, from)),
+                Err(NetError::InvalidPacket(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decoder::{LTCDecoder, LTCDecoderConfig};
+    use crate::api::encoder::LTCEncoderConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn test_packet() {
+        let timecode = SMPTETimecode::new(Timezone::default(), 0, 0, 0, 10, 20, 30, 12);
+        let packet = ArtTimecode::from_timecode(&timecode, ArtTimecodeType::Ebu);
+        let bytes = packet.to_bytes();
+        assert_eq!(&bytes[..12], b"Art-Net\0\x00\x97\x00\x0e");
+        assert_eq!(&bytes[14..], &[12, 30, 20, 10, 1]);
+        assert_eq!(ArtTimecode::from_bytes(&bytes).unwrap(), packet);
+
+        let mut other = bytes;
+        other[9] = 0x50;
+        assert!(ArtTimecode::from_bytes(&other).is_err());
+        assert!(ArtTimecode::from_bytes(&bytes[..18]).is_err());
+        assert_eq!(
+            ArtTimecodeType::from_standard(LTCTVStandard::LTCTV_525_60, true),
+            ArtTimecodeType::Df
+        );
+    }
+
+    #[test]
+    fn test_loopback() {
+        let receiver = ArtNetReceiver::bind("127.0.0.1:0").unwrap();
+        receiver
+            .socket()
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let sender =
+            ArtNetSender::new(receiver.local_addr().unwrap(), ArtTimecodeType::Ebu).unwrap();
+
+        // A generator session: one packet per encoded frame, decoded on the way
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        let mut pos = 0;
+        let mut decoded = 0;
+        for _ in 0..5 {
+            sender.send_encoder(&encoder).unwrap();
+            encoder.encode_frame();
+            let (buf, len) = encoder.get_buf_ref(true);
+            decoder.write(buf, pos);
+            pos += len as i64;
+            encoder.inc_timecode().unwrap();
+            while let Some(frame) = decoder.read() {
+                sender.send_frame(&frame).unwrap();
+                decoded += 1;
+            }
+        }
+
+        let mut frames = vec![];
+        for _ in 0..5 + decoded {
+            let (packet, _) = receiver.recv().unwrap();
+            assert_eq!(packet.kind, ArtTimecodeType::Ebu);
+            frames.push(packet.frames);
+        }
+        assert_eq!(&frames[..2], &[0, 1]);
+    }
+}
@@ -0,0 +1,201 @@
+use super::consts::LtcBgFlags;
+use super::encoder::LTCEncoder;
+use super::export::LoggedTimecode;
+use super::frame::LTCFrameExt;
+use super::SMPTETimecode;
+
+// Size of the bext chunk without the coding history (EBU Tech 3285 v2)
+const BEXT_SIZE: usize = 602;
+// Offset of TimeReferenceLow, TimeReferenceHigh follows
+const BEXT_TIME_REFERENCE_OFFSET: usize = 338;
+const BEXT_VERSION_OFFSET: usize = 346;
+const BEXT_VERSION: u16 = 2;
+
+const SECONDS_PER_DAY: i64 = 24 * 3600;
+
+/// The start of a Broadcast WAV file: the `bext` TimeReference (samples since midnight of the
+/// first sample) and the timecode rate from the iXML chunk.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BwfTimecode {
+    pub time_reference: u64,
+    pub fps: f64,
+    pub drop_frame: bool,
+}
+
+impl BwfTimecode {
+    /// The TimeReference of a file whose first sample is at `timecode`. The date is ignored.
+    pub fn from_timecode(
+        timecode: &SMPTETimecode,
+        fps: f64,
+        drop_frame: bool,
+        sample_rate: u32,
+    ) -> Self {
+        let frames = frames_since_midnight(timecode, fps, drop_frame);
+        BwfTimecode {
+            time_reference: (frames as f64 * sample_rate as f64 / fps).round() as u64,
+            fps,
+            drop_frame,
+        }
+    }
+
+    /// The encoder's current timecode and frame rate, i.e. the start of what it encodes next
+    pub fn from_encoder(encoder: &LTCEncoder) -> Self {
+        Self::from_timecode(
+            &encoder.get_timecode(),
+            encoder.fps(),
+            encoder.get_frame().dfbit() != 0,
+            encoder.sample_rate().round() as u32,
+        )
+    }
+
+    /// How far (in samples) the LTC in the file is ahead of the TimeReference, given a frame
+    /// decoded with the sample position in the file as `posinfo` (see `WavReader::decode`).
+    ///
+    /// 0 means the LTC and the TimeReference agree. Frames decoded in reverse return `None`.
+    pub fn ltc_offset(&self, frame: &LTCFrameExt, sample_rate: u32) -> Option<i64> {
+        if frame.reverse() {
+            return None;
+        }
+        let timecode = frame.ltc().to_timecode(LtcBgFlags::default());
+        let frames = frames_since_midnight(&timecode, self.fps, self.drop_frame);
+        let ltc_samples = (frames as f64 * sample_rate as f64 / self.fps).round() as i64;
+
+        // The timecode of the first sample according to the LTC, compared across midnight
+        let day = SECONDS_PER_DAY * sample_rate as i64;
+        let offset = (ltc_samples - frame.off_start() - self.time_reference as i64).rem_euclid(day);
+        Some(if offset > day / 2 {
+            offset - day
+        } else {
+            offset
+        })
+    }
+
+    /// TIMECODE_RATE as written in iXML, e.g. `25/1` or `30000/1001`
+    pub fn timecode_rate(&self) -> String {
+        let nominal = self.fps.round();
+        if (self.fps - nominal).abs() > 0.001 {
+            format!("{}/1001", nominal as u32 * 1000)
+        } else {
+            format!("{}/1", nominal as u32)
+        }
+    }
+
+    pub(crate) fn bext_chunk(&self) -> Vec<u8> {
+        let mut body = vec![0; BEXT_SIZE];
+        body[BEXT_TIME_REFERENCE_OFFSET..BEXT_TIME_REFERENCE_OFFSET + 8]
+            .copy_from_slice(&self.time_reference.to_le_bytes());
+        body[BEXT_VERSION_OFFSET..BEXT_VERSION_OFFSET + 2]
+            .copy_from_slice(&BEXT_VERSION.to_le_bytes());
+        body
+    }
+
+    pub(crate) fn ixml_chunk(&self, sample_rate: u32) -> Vec<u8> {
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <BWFXML>\n\
+             <IXML_VERSION>1.61</IXML_VERSION>\n\
+             <SPEED>\n\
+             <TIMECODE_RATE>{}</TIMECODE_RATE>\n\
+             <TIMECODE_FLAG>{}</TIMECODE_FLAG>\n\
+             <TIMESTAMP_SAMPLE_RATE>{}</TIMESTAMP_SAMPLE_RATE>\n\
+             <TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI>{}</TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI>\n\
+             <TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_LO>{}</TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_LO>\n\
+             </SPEED>\n\
+             </BWFXML>\n",
+            self.timecode_rate(),
+            if self.drop_frame { "DF" } else { "NDF" },
+            sample_rate,
+            self.time_reference >> 32,
+            self.time_reference & 0xffff_ffff,
+        )
+        .into_bytes()
+    }
+}
+
+/// TimeReference from the body of a `bext` chunk
+pub(crate) fn parse_bext(body: &[u8]) -> Option<u64> {
+    let bytes = body.get(BEXT_TIME_REFERENCE_OFFSET..BEXT_TIME_REFERENCE_OFFSET + 8)?;
+    Some(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Frame rate and drop frame flag from the body of an iXML chunk
+pub(crate) fn parse_ixml_rate(ixml: &str) -> Option<(f64, bool)> {
+    let rate = xml_value(ixml, "TIMECODE_RATE")?;
+    let fps = match rate.split_once('/') {
+        Some((num, den)) => num.trim().parse::<f64>().ok()? / den.trim().parse::<f64>().ok()?,
+        None => rate.parse().ok()?,
+    };
+    if !fps.is_finite() || fps <= 0.0 {
+        return None;
+    }
+    let drop_frame = xml_value(ixml, "TIMECODE_FLAG") == Some("DF");
+    Some((fps, drop_frame))
+}
+
+// The text of the first `<tag>` element. iXML is flat enough to do without an XML parser.
+fn xml_value<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{tag}>");
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    Some(xml[start..end].trim())
+}
+
+fn frames_since_midnight(timecode: &SMPTETimecode, fps: f64, drop_frame: bool) -> i64 {
+    LoggedTimecode {
+        drop_frame,
+        ..timecode.into()
+    }
+    .frame_number(fps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::Timezone;
+
+    fn timecode(hours: u8, minutes: u8, seconds: u8, frame: u8) -> SMPTETimecode {
+        SMPTETimecode::new(Timezone::default(), 0, 0, 0, hours, minutes, seconds, frame)
+    }
+
+    #[test]
+    fn test_time_reference() {
+        let bwf = BwfTimecode::from_timecode(&timecode(10, 0, 0, 0), 25.0, false, 48_000);
+        assert_eq!(bwf.time_reference, 10 * 3600 * 48_000);
+        assert_eq!(bwf.timecode_rate(), "25/1");
+
+        let bwf = BwfTimecode::from_timecode(&timecode(0, 0, 1, 12), 25.0, false, 48_000);
+        assert_eq!(bwf.time_reference, 48_000 + 12 * 1920);
+
+        // 01:00:00;00 is frame 107892 in 29.97 drop frame
+        let bwf = BwfTimecode::from_timecode(&timecode(1, 0, 0, 0), 30000.0 / 1001.0, true, 48_000);
+        assert_eq!(bwf.time_reference, 172_799_827);
+        assert_eq!(bwf.timecode_rate(), "30000/1001");
+        assert_eq!(
+            frames_since_midnight(&timecode(0, 10, 0, 0), 29.97, true),
+            17_982
+        );
+        assert_eq!(
+            frames_since_midnight(&timecode(0, 1, 0, 2), 29.97, true),
+            1_800
+        );
+    }
+
+    #[test]
+    fn test_ixml() {
+        let bwf = BwfTimecode {
+            time_reference: 0x1_0000_0002,
+            fps: 30000.0 / 1001.0,
+            drop_frame: true,
+        };
+        let ixml = String::from_utf8(bwf.ixml_chunk(48_000)).unwrap();
+        assert!(ixml.contains("<TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_HI>1<"));
+        assert!(ixml.contains("<TIMESTAMP_SAMPLES_SINCE_MIDNIGHT_LO>2<"));
+        assert_eq!(parse_ixml_rate(&ixml), Some((bwf.fps, true)));
+        assert_eq!(
+            parse_ixml_rate("<TIMECODE_RATE> 25 </TIMECODE_RATE>"),
+            Some((25.0, false))
+        );
+        assert_eq!(parse_ixml_rate("<TIMECODE_RATE>25/0</TIMECODE_RATE>"), None);
+        assert_eq!(parse_bext(&bwf.bext_chunk()), Some(bwf.time_reference));
+    }
+}
@@ -26,6 +26,7 @@ pub enum LtcBgFlagsKind {
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LtcBgFlags(u32);
 
 impl LtcBgFlags {
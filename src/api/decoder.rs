@@ -1,16 +1,73 @@
+use alloc::boxed::Box;
+use core::cell::Cell;
+use core::fmt;
+
 use super::frame::LTCFrameExt;
+#[cfg(feature = "pure-rust")]
+use super::native;
+use super::polarity::{self, Polarity, SampleHistory};
 use crate::api::consts::SampleType;
 use crate::error::LTCDecoderError;
 
+#[cfg(not(feature = "pure-rust"))]
 use crate::raw;
 #[derive(Debug)]
 pub struct LTCDecoder {
+    #[cfg(not(feature = "pure-rust"))]
     inner_unsafe_ptr: *mut raw::LTCDecoder,
-    _config: LTCDecoderConfig,
+    #[cfg(feature = "pure-rust")]
+    inner: native::decoder::Decoder,
+    config: LTCDecoderConfig,
+    history: Option<SampleHistory>,
+    stats: Cell<DecoderStats>,
+    on_overflow: Option<OverflowCallback>,
+    drop_oldest: bool,
+}
+
+/// Counters kept by `LTCDecoder` since it was created or `reset_stats` was called
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct DecoderStats {
+    /// Frames the decoder produced
+    pub frames_decoded: u64,
+    /// Frames returned by `read` and `read_into`
+    pub frames_read: u64,
+    /// Frames lost because the queue was full
+    pub frames_dropped: u64,
+    /// Frames read whose parity bit doesn't match
+    pub parity_errors: u64,
+    volume_sum: f64,
+    volume_count: u64,
 }
 
+impl DecoderStats {
+    /// Average `LTCFrameExt::volume` in dBFS of the frames read, silent frames excluded
+    pub fn average_volume(&self) -> Option<f64> {
+        (self.volume_count > 0).then(|| self.volume_sum / self.volume_count as f64)
+    }
+
+    fn count(&mut self, frame: &LTCFrameExt) {
+        if !frame.ltc().parity_ok() {
+            self.parity_errors += 1;
+        }
+        if frame.volume().is_finite() {
+            self.volume_sum += frame.volume();
+            self.volume_count += 1;
+        }
+    }
+}
+
+struct OverflowCallback(Box<dyn FnMut(u64) + Send>);
+
+impl fmt::Debug for OverflowCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OverflowCallback")
+    }
+}
+
+#[cfg(not(feature = "pure-rust"))]
 unsafe impl Send for LTCDecoder {}
 
+#[cfg(not(feature = "pure-rust"))]
 impl Drop for LTCDecoder {
     fn drop(&mut self) {
         let r = unsafe { raw::ltc_decoder_free(self.inner_unsafe_ptr) };
@@ -20,9 +77,10 @@ impl Drop for LTCDecoder {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LTCDecoderConfig {
-    /// apv	audio-frames per video frame. This is just used for initial settings, the speed is tracked dynamically. setting this in the right ballpark is needed to properly decode the first LTC frame in a sequence.
+    /// apv audio-frames per video frame. This is just used for initial settings, the speed is tracked dynamically. setting this in the right ballpark is needed to properly decode the first LTC frame in a sequence.
     pub initial_apv: i32,
     /// length of the internal queue to store decoded frames to SMPTEDecoderWrite.
     pub queue_size: i32,
@@ -38,6 +96,21 @@ impl Default for LTCDecoderConfig {
 }
 
 impl LTCDecoder {
+    #[cfg(feature = "pure-rust")]
+    pub fn try_new(config: &LTCDecoderConfig) -> Result<Self, LTCDecoderError> {
+        let decoder = native::decoder::Decoder::new(config.initial_apv, config.queue_size)
+            .ok_or(LTCDecoderError::CreateError)?;
+        Ok(LTCDecoder {
+            inner: decoder,
+            config: *config,
+            history: None,
+            stats: Cell::default(),
+            on_overflow: None,
+            drop_oldest: false,
+        })
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn try_new(config: &LTCDecoderConfig) -> Result<Self, LTCDecoderError> {
         // Safety: the C function does not modify memory, it only allocates memory. Drop is
         // implemented for LTCDecoder
@@ -47,111 +120,455 @@ impl LTCDecoder {
         } else {
             Ok(LTCDecoder {
                 inner_unsafe_ptr: decoder,
-                _config: *config,
+                config: *config,
+                history: None,
+                stats: Cell::default(),
+                on_overflow: None,
+                drop_oldest: false,
             })
         }
     }
 
     pub fn write(&mut self, buf: &[SampleType], posinfo: i64) {
-        // SAFETY: We own self. buf is only read.
-        unsafe {
-            // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
-            // signature, we assume that ltc_decoder_write is will only read from it
-            let mut_ptr_buf = buf.as_ptr() as *mut SampleType;
-            raw::ltc_decoder_write(
-                self.inner_unsafe_ptr,
-                mut_ptr_buf,
-                buf.len() as libc::size_t,
-                posinfo,
-            );
+        self.write_counted(buf, posinfo, |decoder, buf, posinfo| {
+            #[cfg(feature = "pure-rust")]
+            decoder.inner.write(buf, posinfo);
+            #[cfg(not(feature = "pure-rust"))]
+            // SAFETY: We own self. buf is only read.
+            unsafe {
+                // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
+                // signature, we assume that ltc_decoder_write is will only read from it
+                let mut_ptr_buf = buf.as_ptr() as *mut SampleType;
+                raw::ltc_decoder_write(decoder.inner_unsafe_ptr, mut_ptr_buf, buf.len(), posinfo);
+            }
+        });
+        if let Some(history) = self.history.as_mut() {
+            history.extend(buf.iter().copied(), posinfo);
         }
     }
 
     pub fn write_double(&mut self, buf: &[f64], posinfo: i64) {
-        // SAFETY: We own self. buf is only read.
-        unsafe {
-            // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
-            // signature, we assume that ltc_decoder_write is will only read from it
-            let mut_ptr_buf = buf.as_ptr() as *mut f64;
-            raw::ltc_decoder_write_double(
-                self.inner_unsafe_ptr,
-                mut_ptr_buf,
-                buf.len() as libc::size_t,
-                posinfo,
-            );
+        self.write_counted(buf, posinfo, |decoder, buf, posinfo| {
+            #[cfg(feature = "pure-rust")]
+            decoder.inner.write_double(buf, posinfo);
+            #[cfg(not(feature = "pure-rust"))]
+            // SAFETY: We own self. buf is only read.
+            unsafe {
+                // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
+                // signature, we assume that ltc_decoder_write is will only read from it
+                let mut_ptr_buf = buf.as_ptr() as *mut f64;
+                raw::ltc_decoder_write_double(
+                    decoder.inner_unsafe_ptr,
+                    mut_ptr_buf,
+                    buf.len(),
+                    posinfo,
+                );
+            }
+        });
+        if let Some(history) = self.history.as_mut() {
+            history.extend(buf.iter().map(|&x| polarity::f64_to_sample(x)), posinfo);
         }
     }
 
     pub fn write_float(&mut self, buf: &[f32], posinfo: i64) {
-        // SAFETY: We own self. buf is only read.
-        unsafe {
-            // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
-            // signature, we assume that ltc_decoder_write is will only read from it
-            let mut_ptr_buf = buf.as_ptr() as *mut f32;
-            raw::ltc_decoder_write_float(
-                self.inner_unsafe_ptr,
-                mut_ptr_buf,
-                buf.len() as libc::size_t,
+        self.write_counted(buf, posinfo, |decoder, buf, posinfo| {
+            #[cfg(feature = "pure-rust")]
+            decoder.inner.write_float(buf, posinfo);
+            #[cfg(not(feature = "pure-rust"))]
+            // SAFETY: We own self. buf is only read.
+            unsafe {
+                // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
+                // signature, we assume that ltc_decoder_write is will only read from it
+                let mut_ptr_buf = buf.as_ptr() as *mut f32;
+                raw::ltc_decoder_write_float(
+                    decoder.inner_unsafe_ptr,
+                    mut_ptr_buf,
+                    buf.len(),
+                    posinfo,
+                );
+            }
+        });
+        if let Some(history) = self.history.as_mut() {
+            history.extend(
+                buf.iter().map(|&x| polarity::f64_to_sample(x as f64)),
                 posinfo,
             );
         }
     }
 
     pub fn write_i16(&mut self, buf: &[i16], posinfo: i64) {
-        // SAFETY: We own self. buf is only read.
-        unsafe {
-            // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
-            // signature, we assume that ltc_decoder_write is will only read from it
-            let mut_ptr_buf = buf.as_ptr() as *mut i16;
-            raw::ltc_decoder_write_s16(
-                self.inner_unsafe_ptr,
-                mut_ptr_buf,
-                buf.len() as libc::size_t,
-                posinfo,
-            );
+        self.write_counted(buf, posinfo, |decoder, buf, posinfo| {
+            #[cfg(feature = "pure-rust")]
+            decoder.inner.write_i16(buf, posinfo);
+            #[cfg(not(feature = "pure-rust"))]
+            // SAFETY: We own self. buf is only read.
+            unsafe {
+                // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
+                // signature, we assume that ltc_decoder_write is will only read from it
+                let mut_ptr_buf = buf.as_ptr() as *mut i16;
+                raw::ltc_decoder_write_s16(
+                    decoder.inner_unsafe_ptr,
+                    mut_ptr_buf,
+                    buf.len(),
+                    posinfo,
+                );
+            }
+        });
+        if let Some(history) = self.history.as_mut() {
+            history.extend(buf.iter().map(|&x| polarity::i16_to_sample(x)), posinfo);
         }
     }
 
     pub fn write_u16(&mut self, buf: &[u16], posinfo: i64) {
-        // SAFETY: We own self. buf is only read.
-        unsafe {
-            // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
-            // signature, we assume that ltc_decoder_write is will only read from it
-            let mut_ptr_buf = buf.as_ptr() as *mut u16;
-            raw::ltc_decoder_write_u16(
-                self.inner_unsafe_ptr,
-                mut_ptr_buf,
-                buf.len() as libc::size_t,
-                posinfo,
-            );
+        self.write_counted(buf, posinfo, |decoder, buf, posinfo| {
+            #[cfg(feature = "pure-rust")]
+            decoder.inner.write_u16(buf, posinfo);
+            #[cfg(not(feature = "pure-rust"))]
+            // SAFETY: We own self. buf is only read.
+            unsafe {
+                // SAFETY: we can cast *const SampleType as *mut SampleType to accomodate for the C function
+                // signature, we assume that ltc_decoder_write is will only read from it
+                let mut_ptr_buf = buf.as_ptr() as *mut u16;
+                raw::ltc_decoder_write_u16(
+                    decoder.inner_unsafe_ptr,
+                    mut_ptr_buf,
+                    buf.len(),
+                    posinfo,
+                );
+            }
+        });
+        if let Some(history) = self.history.as_mut() {
+            history.extend(buf.iter().map(|&x| polarity::u16_to_sample(x)), posinfo);
         }
     }
 
     pub fn read(&self) -> Option<LTCFrameExt> {
-        // SAFE: The LTCFrameExt is allocated in a box so it outlives the function
         let mut frame = LTCFrameExt::default();
+        self.read_into(&mut frame).then_some(frame)
+    }
+
+    /// Like `read`, but overwrites `frame` instead of allocating a new one, for use in audio
+    /// callbacks. Returns false if the queue is empty, leaving `frame` as it was.
+    pub fn read_into(&self, frame: &mut LTCFrameExt) -> bool {
+        if !self.read_queue(frame) {
+            return false;
+        }
+        let mut stats = self.stats.get();
+        stats.frames_read += 1;
+        stats.count(frame);
+        self.stats.set(stats);
+        true
+    }
+
+    #[cfg(feature = "pure-rust")]
+    fn read_queue(&self, frame: &mut LTCFrameExt) -> bool {
+        match self.inner.read() {
+            Some(inner) => {
+                *frame = inner.into();
+                true
+            }
+            None => false,
+        }
+    }
 
+    #[cfg(not(feature = "pure-rust"))]
+    fn read_queue(&self, frame: &mut LTCFrameExt) -> bool {
         // SAFETY: We own frame. The function is assumed to only read from self and write to frame
-        let result = unsafe {
-            #[allow(clippy::needless_borrow)] // for clarity
-            raw::ltc_decoder_read(self.inner_unsafe_ptr, (&mut frame).inner_unsafe_ptr)
-        };
-        if result == 0 {
-            None
+        unsafe { raw::ltc_decoder_read(self.inner_unsafe_ptr, &mut frame.inner_raw) != 0 }
+    }
+
+    pub fn config(&self) -> LTCDecoderConfig {
+        self.config
+    }
+
+    /// Keeps a copy of the recent input so `detect_polarity` can look at the waveform.
+    /// Requires `posinfo` to be the sample position of the buffers written.
+    pub fn track_polarity(&mut self, enable: bool) {
+        self.history = if enable {
+            let frames = self.config.queue_size.max(0) as usize + 2;
+            Some(SampleHistory::new(
+                self.config.initial_apv.max(1) as usize * frames,
+            ))
         } else {
-            Some(frame)
-        }
+            None
+        };
+    }
+
+    pub fn is_tracking_polarity(&self) -> bool {
+        self.history.is_some()
+    }
+
+    /// Polarity of the input a frame was decoded from. `None` if polarity tracking is off or the
+    /// samples of the frame are no longer available.
+    pub fn detect_polarity(&self, frame: &LTCFrameExt) -> Option<Polarity> {
+        self.history.as_ref()?.detect_polarity(frame)
     }
 
     pub fn queue_flush(&mut self) {
+        #[cfg(feature = "pure-rust")]
+        self.inner.queue_flush();
+        #[cfg(not(feature = "pure-rust"))]
         // SAFETY: We own self
         unsafe {
             raw::ltc_decoder_queue_flush(self.inner_unsafe_ptr);
         }
     }
 
+    #[cfg(feature = "pure-rust")]
+    pub fn queue_length(&self) -> i32 {
+        self.inner.queue_length()
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn queue_length(&self) -> i32 {
         // SAFETY: The function is assumed to only read self
         unsafe { raw::ltc_decoder_queue_length(self.inner_unsafe_ptr) }
     }
+
+    pub fn stats(&self) -> DecoderStats {
+        self.stats.get()
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats.set(DecoderStats::default());
+    }
+
+    /// Called after a write with the number of frames it lost because the queue was full.
+    ///
+    /// libltc's queue is a ring, writing more frames than it holds before reading loses all
+    /// queued frames. libltc doesn't count frames, so with it the samples are written an eighth
+    /// of `initial_apv` at a time and the loss is seen in the queue length after each chunk.
+    /// That misses frames only above about 8 times the initial speed; the native decoder
+    /// counts every frame.
+    pub fn on_overflow(&mut self, callback: impl FnMut(u64) + Send + 'static) {
+        self.on_overflow = Some(OverflowCallback(Box::new(callback)));
+    }
+
+    /// Drops the oldest frame instead of losing the whole queue when it is full, counting it
+    /// in `frames_dropped`. The samples are then written in chunks of an eighth of
+    /// `initial_apv` and a slot is freed before each one, so the newest `queue_size - 2` frames
+    /// are kept at up to about 8 times the initial speed. Off by default.
+    pub fn drop_oldest_on_overflow(&mut self, enable: bool) {
+        self.drop_oldest = enable;
+    }
+
+    /// Writes `buf` and passes every frame decoded from it to `on_frame`. The samples are
+    /// written an eighth of `initial_apv` at a time and the queue emptied after each chunk,
+    /// so buffers of any size can be written without overflowing the queue.
+    pub fn write_with<S: DecoderSample>(
+        &mut self,
+        buf: &[S],
+        posinfo: i64,
+        mut on_frame: impl FnMut(&LTCFrameExt),
+    ) {
+        let mut frame = LTCFrameExt::default();
+        let mut pos = posinfo;
+        for chunk in buf.chunks(self.chunk_len()) {
+            S::write_to(self, chunk, pos);
+            pos += chunk.len() as i64;
+            while self.read_into(&mut frame) {
+                on_frame(&frame);
+            }
+        }
+    }
+
+    fn chunk_len(&self) -> usize {
+        (self.config.initial_apv / 8).max(1) as usize
+    }
+
+    // Writes through `write`, counting the frames decoded and lost. With libltc a chunk must
+    // decode fewer frames than the queue holds, or the count is off by whole queues.
+    fn write_counted<T>(&mut self, buf: &[T], posinfo: i64, write: impl Fn(&mut Self, &[T], i64)) {
+        let chunk_len = if self.drop_oldest || cfg!(not(feature = "pure-rust")) {
+            self.chunk_len()
+        } else {
+            buf.len().max(1)
+        };
+        let mut stats = self.stats.get();
+        let mut lost = 0;
+        let mut pos = posinfo;
+        for chunk in buf.chunks(chunk_len) {
+            if self.drop_oldest && self.queue_length() >= self.config.queue_size - 1 {
+                let mut frame = LTCFrameExt::default();
+                lost += self.read_queue(&mut frame) as u64;
+            }
+            let queued = self.queue_length() as u64;
+            #[cfg(feature = "pure-rust")]
+            let decoded = self.inner.frames_decoded();
+            write(self, chunk, pos);
+            pos += chunk.len() as i64;
+            let now_queued = self.queue_length() as u64;
+
+            #[cfg(feature = "pure-rust")]
+            let produced = self.inner.frames_decoded() - decoded;
+            // libltc doesn't count frames, only how far the write offset moved is known
+            #[cfg(not(feature = "pure-rust"))]
+            let produced = {
+                let queue_size = self.config.queue_size as u64;
+                (now_queued + queue_size - queued) % queue_size
+            };
+            stats.frames_decoded += produced;
+            // The queue holds (queued + produced) modulo its size after a wrap
+            lost += queued + produced - now_queued;
+        }
+        if lost == 0 {
+            self.stats.set(stats);
+            return;
+        }
+        stats.frames_dropped += lost;
+        self.stats.set(stats);
+        if let Some(OverflowCallback(callback)) = self.on_overflow.as_mut() {
+            callback(lost);
+        }
+    }
+}
+
+/// Sample formats `LTCDecoder` can be written with
+pub trait DecoderSample: Copy {
+    fn write_to(decoder: &mut LTCDecoder, buf: &[Self], posinfo: i64);
+}
+
+impl DecoderSample for SampleType {
+    fn write_to(decoder: &mut LTCDecoder, buf: &[Self], posinfo: i64) {
+        decoder.write(buf, posinfo)
+    }
+}
+
+impl DecoderSample for f64 {
+    fn write_to(decoder: &mut LTCDecoder, buf: &[Self], posinfo: i64) {
+        decoder.write_double(buf, posinfo)
+    }
+}
+
+impl DecoderSample for f32 {
+    fn write_to(decoder: &mut LTCDecoder, buf: &[Self], posinfo: i64) {
+        decoder.write_float(buf, posinfo)
+    }
+}
+
+impl DecoderSample for i16 {
+    fn write_to(decoder: &mut LTCDecoder, buf: &[Self], posinfo: i64) {
+        decoder.write_i16(buf, posinfo)
+    }
+}
+
+impl DecoderSample for u16 {
+    fn write_to(decoder: &mut LTCDecoder, buf: &[Self], posinfo: i64) {
+        decoder.write_u16(buf, posinfo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consts::LtcBgFlags;
+    use crate::api::frame::LTCFrame;
+    use crate::api::test_util::encode;
+    use alloc::sync::Arc;
+    use alloc::vec::Vec;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    fn read_frames(decoder: &LTCDecoder) -> Vec<u8> {
+        core::iter::from_fn(|| decoder.read())
+            .map(|frame| frame.ltc().to_timecode(LtcBgFlags::default()))
+            .map(|timecode| timecode.seconds() * 25 + timecode.frame())
+            .collect()
+    }
+
+    #[test]
+    fn test_overflow() {
+        let config = LTCDecoderConfig {
+            queue_size: 4,
+            ..LTCDecoderConfig::default()
+        };
+        let mut decoder = LTCDecoder::try_new(&config).unwrap();
+        let lost = Arc::new(AtomicU64::new(0));
+        let counter = lost.clone();
+        decoder.on_overflow(move |frames| {
+            counter.fetch_add(frames, Ordering::Relaxed);
+        });
+
+        // As in libltc, the ring wrapped around and only 19 modulo 4 frames are left
+        decoder.write(&encode(20), 0);
+        let stats = decoder.stats();
+        assert_eq!(stats.frames_decoded, 19);
+        assert_eq!(stats.frames_dropped, 16);
+        assert_eq!(lost.load(Ordering::Relaxed), 16);
+        assert_eq!(read_frames(&decoder), [16, 17, 18]);
+        assert_eq!(decoder.stats().frames_read, 3);
+    }
+
+    #[test]
+    fn test_drop_oldest() {
+        let config = LTCDecoderConfig {
+            queue_size: 4,
+            ..LTCDecoderConfig::default()
+        };
+        let mut decoder = LTCDecoder::try_new(&config).unwrap();
+        decoder.drop_oldest_on_overflow(true);
+        decoder.write(&encode(20), 0);
+        let stats = decoder.stats();
+        assert_eq!(stats.frames_decoded, 19);
+        assert_eq!(stats.frames_dropped, 17);
+        assert_eq!(read_frames(&decoder), [17, 18]);
+    }
+
+    #[test]
+    fn test_fast_playback() {
+        // Every 5th sample, several frames per initial_apv
+        let samples: Vec<_> = encode(100).into_iter().step_by(5).collect();
+        let config = LTCDecoderConfig {
+            queue_size: 4,
+            ..LTCDecoderConfig::default()
+        };
+        for drop_oldest in [false, true] {
+            let mut decoder = LTCDecoder::try_new(&config).unwrap();
+            decoder.drop_oldest_on_overflow(drop_oldest);
+            decoder.write(&samples, 0);
+            // The first frame is lost while the decoder adapts to the speed
+            let stats = decoder.stats();
+            assert_eq!(stats.frames_decoded, 98);
+            assert_eq!(stats.frames_dropped, 96);
+            assert_eq!(read_frames(&decoder), [97, 98]);
+        }
+
+        let mut decoder = LTCDecoder::try_new(&config).unwrap();
+        let mut frames = Vec::new();
+        decoder.write_with(&samples, 0, |frame| frames.push(*frame));
+        assert_eq!(frames.len(), 98);
+        assert_eq!(decoder.stats().frames_dropped, 0);
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        for (i, chunk) in encode(60).chunks(1000).enumerate() {
+            decoder.write(chunk, i as i64 * 1000);
+            while decoder.read().is_some() {}
+        }
+
+        let stats = decoder.stats();
+        assert_eq!(stats.frames_decoded, 59);
+        assert_eq!(stats.frames_read, 59);
+        assert_eq!(stats.frames_dropped, 0);
+        assert_eq!(stats.parity_errors, 0);
+        let volume = stats.average_volume().unwrap();
+        assert!(volume < 0.0 && volume > -6.0, "{volume}");
+
+        decoder.reset_stats();
+        assert_eq!(decoder.stats(), DecoderStats::default());
+        assert_eq!(decoder.stats().average_volume(), None);
+    }
+
+    #[test]
+    fn test_parity() {
+        let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        decoder.write(&encode(3), 0);
+        let frame = decoder.read().unwrap().ltc();
+        assert!(frame.parity_ok());
+
+        let mut bytes = frame.to_bytes();
+        bytes[1] ^= 0x10;
+        assert!(!LTCFrame::from_bytes(bytes).parity_ok());
+    }
 }
@@ -1,22 +1,47 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
 use super::consts::LtcBgFlags;
+use super::consts::LtcBgFlagsKind;
+use super::filter::{BandLimiter, EncoderFilter, LIBLTC_DEFAULT_FILTER};
 use super::frame::LTCFrame;
+use super::polarity::{samples_to_f32, samples_to_i16, Polarity};
+use super::userbits::UserBits;
 use super::LTCTVStandard;
 use super::SMPTETimecode;
 use crate::api::consts::SampleType;
 use crate::api::TimecodeWasWrapped;
 use crate::error::LTCEncoderError;
 use crate::error::TimecodeError;
+#[cfg(not(feature = "pure-rust"))]
 use crate::raw;
+#[cfg(not(feature = "pure-rust"))]
 use core::slice;
 
+#[cfg(feature = "pure-rust")]
+use super::native;
+
 #[derive(Debug)]
 pub struct LTCEncoder {
+    #[cfg(not(feature = "pure-rust"))]
     inner_unsafe_ptr: *mut raw::LTCEncoder,
+    #[cfg(feature = "pure-rust")]
+    inner: native::encoder::Encoder,
     config: LTCEncoderConfig,
+    filter: EncoderFilter,
+    band_limiter: Option<BandLimiter>,
+    polarity: Polarity,
+    // Samples restored from a snapshot, followed by everything encoded since, until they are
+    // read with flush
+    restored: Vec<SampleType>,
+    restored_flushed: Cell<bool>,
 }
 
+#[cfg(not(feature = "pure-rust"))]
 unsafe impl Send for LTCEncoder {}
 
+#[cfg(not(feature = "pure-rust"))]
 impl Drop for LTCEncoder {
     fn drop(&mut self) {
         unsafe {
@@ -25,7 +50,8 @@ impl Drop for LTCEncoder {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LTCEncoderConfig {
     pub sample_rate: f64,
     pub fps: f64,
@@ -45,6 +71,27 @@ impl Default for LTCEncoderConfig {
 }
 
 impl<'a> LTCEncoder {
+    #[cfg(feature = "pure-rust")]
+    pub fn try_new(config: &LTCEncoderConfig) -> Result<Self, LTCEncoderError> {
+        let encoder = native::encoder::Encoder::new(
+            config.sample_rate,
+            config.fps,
+            config.standard,
+            config.flags,
+        )
+        .ok_or(LTCEncoderError::CreateError)?;
+        Ok(LTCEncoder {
+            inner: encoder,
+            config: *config,
+            filter: EncoderFilter::Libltc(LIBLTC_DEFAULT_FILTER),
+            band_limiter: None,
+            polarity: Polarity::Normal,
+            restored: Vec::new(),
+            restored_flushed: Cell::new(false),
+        })
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn try_new(config: &LTCEncoderConfig) -> Result<Self, LTCEncoderError> {
         // Safety: the C function does not modify memory, it only allocates memory. Drop is implemented for LTCEncoder
         let encoder = unsafe {
@@ -61,6 +108,11 @@ impl<'a> LTCEncoder {
             Ok(LTCEncoder {
                 inner_unsafe_ptr: encoder,
                 config: *config,
+                filter: EncoderFilter::Libltc(LIBLTC_DEFAULT_FILTER),
+                band_limiter: None,
+                polarity: Polarity::Normal,
+                restored: Vec::new(),
+                restored_flushed: Cell::new(false),
             })
         }
     }
@@ -77,13 +129,21 @@ impl<'a> LTCEncoder {
         self.config.standard
     }
 
-    // The internal flags might not stay consistent with the config so we don't expose it
-    //pub fn flags(&self) -> LtcBgFlags {
-    //    self.config.flags
-    //}
+    pub fn flags(&self) -> LtcBgFlags {
+        self.config.flags
+    }
+
+    pub fn config(&self) -> LTCEncoderConfig {
+        self.config
+    }
 
     // TODO: this might be incorrect
     pub fn set_timecode(&mut self, timecode: &SMPTETimecode) {
+        #[cfg(feature = "pure-rust")]
+        // SAFETY: the pointer is owned by timecode and valid
+        self.inner
+            .set_timecode(unsafe { &*timecode.inner_unsafe_ptr });
+        #[cfg(not(feature = "pure-rust"))]
         // Safety: We own self, the function is assumed to only read the timecode and write to self
         unsafe {
             raw::ltc_encoder_set_timecode(self.inner_unsafe_ptr, timecode.inner_unsafe_ptr);
@@ -92,14 +152,18 @@ impl<'a> LTCEncoder {
 
     pub fn get_timecode(&self) -> SMPTETimecode {
         let mut timecode = SMPTETimecode::default();
-        // We own timecode, the function is assumed to only read from self and write to timecode
-        unsafe {
-            #[allow(clippy::needless_borrow)] // for clarity
-            raw::ltc_encoder_get_timecode(self.inner_unsafe_ptr, (&mut timecode).inner_unsafe_ptr);
-        }
+        self.get_timecode_inplace(&mut timecode);
         timecode
     }
 
+    #[cfg(feature = "pure-rust")]
+    pub fn get_timecode_inplace(&self, timecode: &mut SMPTETimecode) {
+        // SAFETY: the pointer is owned by timecode and valid
+        self.inner
+            .timecode(unsafe { &mut *timecode.inner_unsafe_ptr });
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn get_timecode_inplace(&self, timecode: &mut SMPTETimecode) {
         // We own timecode, the function is assumed to only read from self and write to timecode
         unsafe {
@@ -109,32 +173,63 @@ impl<'a> LTCEncoder {
     }
 
     pub fn set_user_bits(&mut self, data: u32) {
+        #[cfg(feature = "pure-rust")]
+        self.inner.set_user_bits(data);
+        #[cfg(not(feature = "pure-rust"))]
         // SAFETY: We own self
         unsafe {
-            raw::ltc_encoder_set_user_bits(self.inner_unsafe_ptr, data as libc::c_ulong);
+            raw::ltc_encoder_set_user_bits(self.inner_unsafe_ptr, data as core::ffi::c_ulong);
+        }
+    }
+
+    /// Sets the user bits together with the binary group flags announcing their format, and
+    /// recomputes the parity unless LTC_NO_PARITY is set.
+    pub fn set_typed_user_bits(&mut self, bits: &UserBits) -> Result<(), LTCEncoderError> {
+        let mut frame = self.get_frame();
+        frame.set_typed_user_bits(bits, self.config.standard)?;
+        if !self.config.flags.contains(LtcBgFlagsKind::LTC_NO_PARITY) {
+            frame.set_parity(self.config.standard);
         }
+        self.set_frame(&frame);
+        Ok(())
     }
 
     pub fn inc_timecode(&mut self) -> Result<TimecodeWasWrapped, LTCEncoderError> {
+        #[cfg(feature = "pure-rust")]
+        let wrapped = self.inner.inc_timecode();
+        #[cfg(not(feature = "pure-rust"))]
         // SAFETY: We own self
-        unsafe { raw::ltc_encoder_inc_timecode(self.inner_unsafe_ptr) }
-            .try_into()
-            .map_err(|e: TimecodeError| e.into())
+        let wrapped = unsafe { raw::ltc_encoder_inc_timecode(self.inner_unsafe_ptr) };
+        wrapped.try_into().map_err(|e: TimecodeError| e.into())
     }
 
     pub fn dec_timecode(&mut self) -> Result<TimecodeWasWrapped, LTCEncoderError> {
+        #[cfg(feature = "pure-rust")]
+        let wrapped = self.inner.dec_timecode();
+        #[cfg(not(feature = "pure-rust"))]
         // SAFETY: We own self
-        unsafe { raw::ltc_encoder_dec_timecode(self.inner_unsafe_ptr) }
-            .try_into()
-            .map_err(|e: TimecodeError| e.into())
+        let wrapped = unsafe { raw::ltc_encoder_dec_timecode(self.inner_unsafe_ptr) };
+        wrapped.try_into().map_err(|e: TimecodeError| e.into())
+    }
+
+    #[cfg(feature = "pure-rust")]
+    pub fn set_frame(&mut self, frame: &LTCFrame) {
+        self.inner.set_frame(&frame.inner_raw);
     }
 
+    #[cfg(not(feature = "pure-rust"))]
     pub fn set_frame(&mut self, frame: &LTCFrame) {
         let mut inner_raw = frame.inner_raw;
         // SAFETY: We own self, the function is assumed to only read the frame and write to self
         unsafe { raw::ltc_encoder_set_frame(self.inner_unsafe_ptr, &mut inner_raw) }
     }
 
+    #[cfg(feature = "pure-rust")]
+    pub fn get_frame(&self) -> LTCFrame {
+        self.inner.frame().into()
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn get_frame(&self) -> LTCFrame {
         let mut frame = LTCFrame::new();
         // SAFETY: We own frame. The function is assumed to only read from self and write to frame
@@ -146,20 +241,83 @@ impl<'a> LTCEncoder {
     }
 
     pub fn copy_buffer_inplace(&self, buf: &mut [SampleType]) -> i32 {
+        if let Some(restored) = self.pending_restored() {
+            buf[..restored.len()].copy_from_slice(restored);
+            self.restored_flushed.set(true);
+            return restored.len() as i32;
+        }
+        self.copy_inner_buffer(buf)
+    }
+
+    #[cfg(feature = "pure-rust")]
+    fn copy_inner_buffer(&self, buf: &mut [SampleType]) -> i32 {
+        self.inner.copy_buffer(buf) as i32
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    fn copy_inner_buffer(&self, buf: &mut [SampleType]) -> i32 {
         unsafe { raw::ltc_encoder_copy_buffer(self.inner_unsafe_ptr, buf.as_mut_ptr()) }
     }
 
     pub fn copy_buffer(&self) -> (Vec<u8>, usize) {
         let mut buf = vec![0; self.get_buffersize()];
-        let size = unsafe { raw::ltc_encoder_copy_buffer(self.inner_unsafe_ptr, buf.as_mut_ptr()) };
+        let size = self.copy_buffer_inplace(&mut buf);
         (buf, size as usize)
     }
 
-    // TODO: Possible leak? does ptr ever get deallocated - maybe when the encoder is deallocated?
+    /// Like `copy_buffer_inplace`, but centred at zero in the range [-1, 1].
+    /// `buf` must hold at least `get_buffersize()` samples.
+    pub fn copy_buffer_f32(&mut self, buf: &mut [f32]) -> usize {
+        let (samples, size) = self.get_buf_ref(true);
+        samples_to_f32(samples, &mut buf[..size]);
+        size
+    }
+
+    /// Like `copy_buffer_inplace`, but as signed 16-bit samples centred at zero.
+    /// `buf` must hold at least `get_buffersize()` samples.
+    pub fn copy_buffer_i16(&mut self, buf: &mut [i16]) -> usize {
+        let (samples, size) = self.get_buf_ref(true);
+        samples_to_i16(samples, &mut buf[..size]);
+        size
+    }
+
     pub fn get_buf_ref(&'a self, flush: bool) -> (&'a [SampleType], usize) {
+        if let Some(restored) = self.pending_restored() {
+            self.restored_flushed.set(flush);
+            return (restored, restored.len());
+        }
+        let buf = self.inner_buf(flush);
+        (buf, buf.len())
+    }
+
+    pub fn get_buf_ref_mut(&'a mut self, flush: bool) -> (&'a mut [SampleType], usize) {
+        self.clear_flushed_restored();
+        if !self.restored.is_empty() {
+            self.restored_flushed.set(flush);
+            let size = self.restored.len();
+            return (&mut self.restored, size);
+        }
+        let buf = self.inner_buf_mut(flush);
+        let size = buf.len();
+        (buf, size)
+    }
+
+    #[cfg(feature = "pure-rust")]
+    fn inner_buf(&self, flush: bool) -> &[SampleType] {
+        self.inner.buffer(flush)
+    }
+
+    #[cfg(feature = "pure-rust")]
+    fn inner_buf_mut(&mut self, flush: bool) -> &mut [SampleType] {
+        self.inner.buffer_mut(flush)
+    }
+
+    // TODO: Possible leak? does ptr ever get deallocated - maybe when the encoder is deallocated?
+    #[cfg(not(feature = "pure-rust"))]
+    fn inner_buf(&self, flush: bool) -> &[SampleType] {
         // SAFETY: The buffer (pointed at by ptr) outlives the function as it has the same
         // lifetime as self
-        let mut ptr = std::ptr::null_mut();
+        let mut ptr = core::ptr::null_mut();
         // SAFETY: Self is assumed to only be read - for the buffersize
         let size = unsafe {
             raw::ltc_encoder_get_bufferptr(
@@ -169,17 +327,15 @@ impl<'a> LTCEncoder {
             )
         };
 
-        (
-            unsafe { slice::from_raw_parts(ptr, size as usize) },
-            size as usize,
-        )
+        unsafe { slice::from_raw_parts(ptr, size as usize) }
     }
 
     // TODO: Possible leak? does ptr ever get deallocated - maybe when the encoder is deallocated?
-    pub fn get_buf_ref_mut(&'a mut self, flush: bool) -> (&'a mut [SampleType], usize) {
+    #[cfg(not(feature = "pure-rust"))]
+    fn inner_buf_mut(&mut self, flush: bool) -> &mut [SampleType] {
         // SAFETY: The buffer (pointed at by ptr) outlives the function as it has the same
         // lifetime as self
-        let mut ptr = std::ptr::null_mut();
+        let mut ptr = core::ptr::null_mut();
         // SAFETY: Self is assumed to only be read - for the buffersize
         let size = unsafe {
             raw::ltc_encoder_get_bufferptr(
@@ -188,19 +344,33 @@ impl<'a> LTCEncoder {
                 if flush { 1 } else { 0 },
             )
         };
-        (
-            unsafe { slice::from_raw_parts_mut(ptr, size as usize) },
-            size as usize,
-        )
+        unsafe { slice::from_raw_parts_mut(ptr, size as usize) }
     }
 
     pub fn buffer_flush(&mut self) {
+        self.discard_restored();
+        #[cfg(feature = "pure-rust")]
+        self.inner.buffer_flush();
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
             raw::ltc_encoder_buffer_flush(self.inner_unsafe_ptr);
         }
     }
 
+    /// Enough for one frame, or for the samples restored from a snapshot and the frames encoded
+    /// after them
     pub fn get_buffersize(&self) -> usize {
+        let restored = self.pending_restored().map_or(0, <[SampleType]>::len);
+        self.inner_buffersize().max(restored)
+    }
+
+    #[cfg(feature = "pure-rust")]
+    fn inner_buffersize(&self) -> usize {
+        self.inner.buffersize()
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    fn inner_buffersize(&self) -> usize {
         // SAFETY: The function is assumed to only read self
         unsafe { raw::ltc_encoder_get_buffersize(self.inner_unsafe_ptr) }
     }
@@ -212,10 +382,10 @@ impl<'a> LTCEncoder {
         standard: LTCTVStandard,
         flags: LtcBgFlags,
     ) -> Result<(), LTCEncoderError> {
-        let result = unsafe {
-            self.config.sample_rate = sample_rate;
-            self.config.fps = fps;
-            self.config.standard = standard;
+        #[cfg(feature = "pure-rust")]
+        let result = self.inner.reinit(sample_rate, fps, standard, flags);
+        #[cfg(not(feature = "pure-rust"))]
+        let result = match unsafe {
             raw::ltc_encoder_reinit(
                 self.inner_unsafe_ptr,
                 sample_rate,
@@ -223,8 +393,22 @@ impl<'a> LTCEncoder {
                 standard.to_raw(),
                 flags.into(),
             )
+        } {
+            0 => Ok(()),
+            _ => Err(()),
         };
-        if result == 0 {
+        if result.is_ok() {
+            self.discard_restored();
+            // libltc leaves the encoder untouched when reinit fails
+            self.config = LTCEncoderConfig {
+                sample_rate,
+                fps,
+                standard,
+                flags,
+            };
+            // libltc resets the filter on reinit
+            self.set_filter_preset(self.filter);
+            self.set_band_limit(self.band_limit());
             Ok(())
         } else {
             Err(LTCEncoderError::ReinitError)
@@ -232,47 +416,145 @@ impl<'a> LTCEncoder {
     }
 
     pub fn reset(&mut self) {
+        self.discard_restored();
+        #[cfg(feature = "pure-rust")]
+        self.inner.reset();
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
             raw::ltc_encoder_reset(self.inner_unsafe_ptr);
         }
+        if let Some(limiter) = self.band_limiter.as_mut() {
+            limiter.reset();
+        }
     }
 
     pub fn set_buffersize(&mut self, sample_rate: f64, fps: f64) -> Result<(), LTCEncoderError> {
+        #[cfg(feature = "pure-rust")]
+        let result = {
+            self.inner.set_buffersize(sample_rate, fps);
+            0
+        };
+        #[cfg(not(feature = "pure-rust"))]
         let result =
             unsafe { raw::ltc_encoder_set_buffersize(self.inner_unsafe_ptr, sample_rate, fps) };
         if result == 0 {
+            self.discard_restored();
             Ok(())
         } else {
             Err(LTCEncoderError::BufferSizeError)
         }
     }
 
+    #[cfg(feature = "pure-rust")]
+    pub fn get_volume(&self) -> f64 {
+        self.inner.volume()
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn get_volume(&self) -> f64 {
         unsafe { raw::ltc_encoder_get_volume(self.inner_unsafe_ptr) }
     }
 
     pub fn set_volume(&mut self, dbfs: f64) -> Result<(), LTCEncoderError> {
-        let result = unsafe { raw::ltc_encoder_set_volume(self.inner_unsafe_ptr, dbfs) };
-        if result == 0 {
+        #[cfg(feature = "pure-rust")]
+        let result = self.inner.set_volume(dbfs);
+        #[cfg(not(feature = "pure-rust"))]
+        let result = match unsafe { raw::ltc_encoder_set_volume(self.inner_unsafe_ptr, dbfs) } {
+            0 => Ok(()),
+            _ => Err(()),
+        };
+        if result.is_ok() {
             Ok(())
         } else {
             Err(LTCEncoderError::VolumeError)
         }
     }
 
+    #[cfg(feature = "pure-rust")]
+    pub fn get_filter(&self) -> f64 {
+        self.inner.filter()
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn get_filter(&self) -> f64 {
         unsafe { raw::ltc_encoder_get_filter(self.inner_unsafe_ptr) }
     }
 
+    /// Sets libltc's filter parameter directly, see `EncoderFilter::libltc_filter` for how it
+    /// relates to the rise time. `set_filter_preset` takes the rise time instead.
     pub fn set_filter(&mut self, rise_time: f64) {
+        self.filter = EncoderFilter::Libltc(rise_time);
+        #[cfg(feature = "pure-rust")]
+        self.inner.set_filter(rise_time);
+        #[cfg(not(feature = "pure-rust"))]
+        unsafe {
+            raw::ltc_encoder_set_filter(self.inner_unsafe_ptr, rise_time);
+        }
+    }
+
+    /// Unlike `set_filter`, the preset is kept across `reinit`, and `EncoderFilter::Standard`
+    /// follows the new standard.
+    pub fn set_filter_preset(&mut self, filter: EncoderFilter) {
+        self.filter = filter;
+        let rise_time = filter.libltc_filter(self.config.standard, self.config.sample_rate);
+        #[cfg(feature = "pure-rust")]
+        self.inner.set_filter(rise_time);
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
             raw::ltc_encoder_set_filter(self.inner_unsafe_ptr, rise_time);
         }
     }
 
+    pub fn filter_preset(&self) -> EncoderFilter {
+        self.filter
+    }
+
+    /// Low-pass filters everything encoded from now on, see `BandLimiter`. `None` disables it.
+    pub fn set_band_limit(&mut self, cutoff: Option<f64>) {
+        self.band_limiter = cutoff.map(|cutoff| BandLimiter::new(self.config.sample_rate, cutoff));
+    }
+
+    /// The cutoff passed to `set_band_limit`, kept across `reinit`. The limiter clamps it for
+    /// the sample rate, see `BandLimiter::new`.
+    pub fn band_limit(&self) -> Option<f64> {
+        self.band_limiter
+            .as_ref()
+            .map(BandLimiter::requested_cutoff)
+    }
+
+    pub(crate) fn band_limiter_history(&self) -> Option<[f64; 4]> {
+        self.band_limiter.as_ref().map(BandLimiter::history)
+    }
+
+    pub(crate) fn set_band_limiter_history(&mut self, history: [f64; 4]) {
+        if let Some(limiter) = self.band_limiter.as_mut() {
+            limiter.set_history(history);
+        }
+    }
+
+    /// Inverts everything encoded from now on
+    pub fn set_polarity(&mut self, polarity: Polarity) {
+        self.polarity = polarity;
+    }
+
+    pub fn polarity(&self) -> Polarity {
+        self.polarity
+    }
+
     pub fn encode_byte(&mut self, byte: i32, speed: f64) -> Result<(), LTCEncoderError> {
-        let result = unsafe { raw::ltc_encoder_encode_byte(self.inner_unsafe_ptr, byte, speed) };
-        if result == 0 {
+        self.clear_flushed_restored();
+        let start = self.buffered_len();
+        #[cfg(feature = "pure-rust")]
+        let result = self.inner.encode_byte(byte, speed);
+        #[cfg(not(feature = "pure-rust"))]
+        let result =
+            match unsafe { raw::ltc_encoder_encode_byte(self.inner_unsafe_ptr, byte, speed) } {
+                0 => Ok(()),
+                _ => Err(()),
+            };
+        self.apply_output_shaping(start);
+        self.carry_restored();
+        if result.is_ok() {
             Ok(())
         } else {
             Err(LTCEncoderError::EncodeError)
@@ -280,8 +562,18 @@ impl<'a> LTCEncoder {
     }
 
     pub fn end_encode(&mut self) -> Result<(), LTCEncoderError> {
-        let result = unsafe { raw::ltc_encoder_end_encode(self.inner_unsafe_ptr) };
-        if result == 0 {
+        self.clear_flushed_restored();
+        let start = self.buffered_len();
+        #[cfg(feature = "pure-rust")]
+        let result = self.inner.end_encode();
+        #[cfg(not(feature = "pure-rust"))]
+        let result = match unsafe { raw::ltc_encoder_end_encode(self.inner_unsafe_ptr) } {
+            0 => Ok(()),
+            _ => Err(()),
+        };
+        self.apply_output_shaping(start);
+        self.carry_restored();
+        if result.is_ok() {
             Ok(())
         } else {
             Err(LTCEncoderError::EncodeError)
@@ -289,15 +581,132 @@ impl<'a> LTCEncoder {
     }
 
     pub fn encode_frame(&mut self) {
+        self.clear_flushed_restored();
+        let start = self.buffered_len();
+        #[cfg(feature = "pure-rust")]
+        self.inner.encode_frame();
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
             raw::ltc_encoder_encode_frame(self.inner_unsafe_ptr);
         }
+        self.apply_output_shaping(start);
+        self.carry_restored();
     }
 
     pub fn encode_reversed_frame(&mut self) {
+        self.clear_flushed_restored();
+        let start = self.buffered_len();
+        #[cfg(feature = "pure-rust")]
+        self.inner.encode_reversed_frame();
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
             raw::ltc_encoder_encode_reversed_frame(self.inner_unsafe_ptr);
         }
+        self.apply_output_shaping(start);
+        self.carry_restored();
+    }
+
+    #[cfg(feature = "pure-rust")]
+    fn buffered_len(&self) -> usize {
+        self.inner.buffer(false).len()
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    fn buffered_len(&self) -> usize {
+        let mut ptr = core::ptr::null_mut();
+        // SAFETY: Without flush, the function only reads self
+        unsafe { raw::ltc_encoder_get_bufferptr(self.inner_unsafe_ptr, &mut ptr, 0) as usize }
+    }
+
+    /// The level of the last edge and the sub-sample delay of the next one. libltc keeps them
+    /// private, so only the pure-Rust encoder has them.
+    #[cfg(feature = "pure-rust")]
+    pub(crate) fn phase(&self) -> Option<(bool, f64)> {
+        Some(self.inner.phase())
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    pub(crate) fn phase(&self) -> Option<(bool, f64)> {
+        None
+    }
+
+    /// Does nothing with libltc
+    #[cfg(feature = "pure-rust")]
+    pub(crate) fn set_phase(&mut self, state: bool, sample_remainder: f64) {
+        self.inner.set_phase(state, sample_remainder);
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
+    pub(crate) fn set_phase(&mut self, _state: bool, _sample_remainder: f64) {}
+
+    /// Samples from a snapshot to output before anything the encoder produces, see
+    /// `LTCEncoder::restore`
+    pub(crate) fn set_restored(&mut self, samples: &[SampleType]) {
+        self.restored = samples.to_vec();
+        self.restored_flushed.set(false);
+    }
+
+    fn pending_restored(&self) -> Option<&[SampleType]> {
+        (!self.restored.is_empty() && !self.restored_flushed.get()).then_some(&self.restored)
+    }
+
+    // Restored samples read with flush through &self are only dropped on the next &mut call
+    fn clear_flushed_restored(&mut self) {
+        if self.restored_flushed.replace(false) {
+            self.restored.clear();
+        }
+    }
+
+    fn discard_restored(&mut self) {
+        self.restored.clear();
+        self.restored_flushed.set(false);
+    }
+
+    // Moves what was just encoded behind the restored samples, so both are read together
+    fn carry_restored(&mut self) {
+        if self.restored.is_empty() {
+            return;
+        }
+        #[cfg(feature = "pure-rust")]
+        self.restored.extend_from_slice(self.inner.buffer(true));
+        #[cfg(not(feature = "pure-rust"))]
+        {
+            let mut ptr = core::ptr::null_mut();
+            // SAFETY: The buffer is owned by the encoder and read before anything else uses it
+            let size =
+                unsafe { raw::ltc_encoder_get_bufferptr(self.inner_unsafe_ptr, &mut ptr, 1) };
+            self.restored
+                .extend_from_slice(unsafe { slice::from_raw_parts(ptr, size as usize) });
+        }
+    }
+
+    // Filters and inverts the samples encoded since `start`
+    fn apply_output_shaping(&mut self, start: usize) {
+        if self.band_limiter.is_none() && self.polarity == Polarity::Normal {
+            return;
+        }
+        #[cfg(feature = "pure-rust")]
+        let (buf, size) = {
+            let buf = self.inner.buffer_mut(false);
+            let size = buf.len();
+            (buf, size)
+        };
+        #[cfg(not(feature = "pure-rust"))]
+        let (buf, size) = {
+            let mut ptr = core::ptr::null_mut();
+            // SAFETY: The buffer is owned by the encoder and we hold &mut self
+            let size = unsafe {
+                raw::ltc_encoder_get_bufferptr(self.inner_unsafe_ptr, &mut ptr, 0) as usize
+            };
+            (unsafe { slice::from_raw_parts_mut(ptr, size) }, size)
+        };
+        if size > start {
+            let buf = &mut buf[start..];
+            if let Some(limiter) = self.band_limiter.as_mut() {
+                limiter.process(buf);
+            }
+            self.polarity.apply(buf);
+        }
     }
 }
 
@@ -326,6 +735,20 @@ mod tests {
         assert!(encoder.set_volume(1.0).is_err());
     }
 
+    #[test]
+    fn test_encoder_typed_user_bits() {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let bits = UserBits::Ascii(*b"SC01");
+        encoder.set_typed_user_bits(&bits).unwrap();
+        assert_eq!(
+            encoder.get_frame().get_typed_user_bits(encoder.standard()),
+            bits
+        );
+        assert!(encoder
+            .set_typed_user_bits(&UserBits::Bcd([0, 0, 0, 0, 0, 0, 0, 12]))
+            .is_err());
+    }
+
     #[test]
     fn test_encoder_reinit() {
         let encoder_config = LTCEncoderConfig {
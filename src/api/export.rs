@@ -0,0 +1,480 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Display;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+use super::consts::LtcBgFlags;
+use super::frame::LTCFrameExt;
+use super::SMPTETimecode;
+#[cfg(not(feature = "std"))]
+use crate::math::F64Ext;
+
+/// Time of day of a decoded frame, without the heap allocation of `SMPTETimecode`
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct LoggedTimecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frame: u8,
+    pub drop_frame: bool,
+}
+
+impl From<&SMPTETimecode> for LoggedTimecode {
+    fn from(timecode: &SMPTETimecode) -> Self {
+        LoggedTimecode {
+            hours: timecode.hours(),
+            minutes: timecode.minutes(),
+            seconds: timecode.seconds(),
+            frame: timecode.frame(),
+            drop_frame: false,
+        }
+    }
+}
+
+/// HH:MM:SS:FF, HH:MM:SS;FF for drop frame
+impl Display for LoggedTimecode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let separator = if self.drop_frame { ';' } else { ':' };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            self.hours, self.minutes, self.seconds, self.frame
+        )
+    }
+}
+
+impl LoggedTimecode {
+    /// Frames since 00:00:00:00, skipping the frame numbers drop frame timecode leaves out
+    pub fn frame_number(&self, fps: f64) -> i64 {
+        let nominal = fps.round() as i64;
+        let minutes = self.hours as i64 * 60 + self.minutes as i64;
+        let frames = (minutes * 60 + self.seconds as i64) * nominal + self.frame as i64;
+        if self.drop_frame {
+            // 2 frames (4 at 60fps) every minute, except every tenth minute
+            frames - nominal / 15 * (minutes - minutes / 10)
+        } else {
+            frames
+        }
+    }
+
+    /// The inverse of `frame_number`, wrapping around at midnight
+    pub fn from_frame_number(number: i64, fps: f64, drop_frame: bool) -> Self {
+        let nominal = fps.round().max(1.0) as i64;
+        let mut number = number.rem_euclid(frames_per_day(nominal, drop_frame));
+        if drop_frame {
+            let dropped = nominal / 15;
+            let per_ten_minutes = nominal * 600 - 9 * dropped;
+            let per_minute = nominal * 60 - dropped;
+            let tens = number / per_ten_minutes;
+            let rest = number % per_ten_minutes;
+            number += 9 * dropped * tens;
+            if rest >= dropped {
+                number += dropped * ((rest - dropped) / per_minute);
+            }
+        }
+        LoggedTimecode {
+            hours: (number / (nominal * 3600)) as u8,
+            minutes: (number / (nominal * 60) % 60) as u8,
+            seconds: (number / nominal % 60) as u8,
+            frame: (number % nominal) as u8,
+            drop_frame,
+        }
+    }
+}
+
+fn frames_per_day(nominal: i64, drop_frame: bool) -> i64 {
+    if drop_frame {
+        // 144 blocks of ten minutes
+        144 * (nominal * 600 - 9 * (nominal / 15))
+    } else {
+        nominal * 24 * 3600
+    }
+}
+
+/// The parts of a decoded frame that go into a timecode log
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LoggedFrame {
+    pub timecode: LoggedTimecode,
+    pub user_bits: u32,
+    pub off_start: i64,
+    pub off_end: i64,
+    pub reverse: bool,
+    pub volume: f64,
+}
+
+impl From<&LTCFrameExt> for LoggedFrame {
+    fn from(frame: &LTCFrameExt) -> Self {
+        let ltc = frame.ltc();
+        LoggedFrame {
+            timecode: LoggedTimecode {
+                drop_frame: ltc.dfbit() != 0,
+                ..(&ltc.to_timecode(LtcBgFlags::default())).into()
+            },
+            user_bits: ltc.get_user_bits(),
+            off_start: frame.off_start(),
+            off_end: frame.off_end(),
+            reverse: frame.reverse(),
+            volume: frame.volume(),
+        }
+    }
+}
+
+/// A run of frames with continuous timecode, in the order they were decoded
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TimecodeSegment {
+    /// Timecode of the first decoded frame
+    pub first: LoggedTimecode,
+    /// Timecode of the last decoded frame
+    pub last: LoggedTimecode,
+    /// Sample position of the start of the first frame
+    pub off_start: i64,
+    /// Sample position of the end of the last frame
+    pub off_end: i64,
+    pub frames: usize,
+    pub reverse: bool,
+}
+
+/// Collects decoded frames and splits them into segments wherever the timecode is
+/// discontinuous (a jump, a repeated frame or a change of direction), then exports them as
+/// CSV, JSON, a CMX3600 EDL or an Avid ALE.
+///
+/// ```ignore
+/// let mut log = TimecodeLog::new(25.0, 48_000.0);
+/// while let Some(frame) = decoder.read() {
+///     log.push(&frame);
+/// }
+/// log.write_edl(&mut File::create("take.edl")?, "TAKE 1", "A001")?;
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimecodeLog {
+    fps: f64,
+    // Only needed by the writers
+    #[cfg_attr(not(feature = "std"), allow(dead_code))]
+    sample_rate: f64,
+    frames: Vec<LoggedFrame>,
+    segments: Vec<TimecodeSegment>,
+}
+
+impl TimecodeLog {
+    pub fn new(fps: f64, sample_rate: f64) -> Self {
+        TimecodeLog {
+            fps,
+            sample_rate,
+            frames: vec![],
+            segments: vec![],
+        }
+    }
+
+    pub fn push(&mut self, frame: &LTCFrameExt) {
+        self.push_logged(frame.into());
+    }
+
+    pub fn push_logged(&mut self, frame: LoggedFrame) {
+        let continues = self.segments.last().is_some_and(|segment| {
+            // A single frame does not tell the direction yet
+            let turned = segment.frames > 1 && segment.reverse != frame.reverse;
+            self.out_point(segment) == frame.timecode && !turned
+        });
+
+        match self.segments.last_mut() {
+            Some(segment) if continues => {
+                segment.last = frame.timecode;
+                segment.off_end = frame.off_end;
+                segment.frames += 1;
+                segment.reverse = frame.reverse;
+            }
+            _ => self.segments.push(TimecodeSegment {
+                first: frame.timecode,
+                last: frame.timecode,
+                off_start: frame.off_start,
+                off_end: frame.off_end,
+                frames: 1,
+                reverse: frame.reverse,
+            }),
+        }
+        self.frames.push(frame);
+    }
+
+    pub fn frames(&self) -> &[LoggedFrame] {
+        &self.frames
+    }
+
+    pub fn segments(&self) -> &[TimecodeSegment] {
+        &self.segments
+    }
+
+    /// The timecode after the last frame of a segment (the exclusive out point)
+    pub fn out_point(&self, segment: &TimecodeSegment) -> LoggedTimecode {
+        let step = if segment.reverse { -1 } else { 1 };
+        LoggedTimecode::from_frame_number(
+            segment.last.frame_number(self.fps) + step,
+            self.fps,
+            segment.last.drop_frame,
+        )
+    }
+
+    #[cfg(feature = "std")]
+    // Position in the recording as timecode, from 00:00:00:00
+    fn record_timecode(&self, sample: i64, drop_frame: bool) -> LoggedTimecode {
+        let frames = (sample as f64 * self.fps / self.sample_rate).round() as i64;
+        LoggedTimecode::from_frame_number(frames, self.fps, drop_frame)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "off_start,off_end,timecode,user_bits,reverse,volume")?;
+        for frame in &self.frames {
+            writeln!(
+                out,
+                "{},{},{},{:08x},{},{:.1}",
+                frame.off_start,
+                frame.off_end,
+                frame.timecode,
+                frame.user_bits,
+                frame.reverse,
+                frame.volume
+            )?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    /// A JSON array with one object per frame
+    pub fn write_json(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "[")?;
+        for (i, frame) in self.frames.iter().enumerate() {
+            write!(
+                out,
+                "{}\n  {{\"off_start\":{},\"off_end\":{},\"timecode\":\"{}\",\
+                 \"user_bits\":\"{:08x}\",\"reverse\":{},\"volume\":{:.1}}}",
+                if i == 0 { "" } else { "," },
+                frame.off_start,
+                frame.off_end,
+                frame.timecode,
+                frame.user_bits,
+                frame.reverse,
+                frame.volume
+            )?;
+        }
+        writeln!(out, "\n]")
+    }
+
+    #[cfg(feature = "std")]
+    /// A CMX3600 EDL with one audio event per segment. The source timecode is the LTC, the
+    /// record timecode the position in the recording. Segments played in reverse are left out.
+    pub fn write_edl(&self, out: &mut impl Write, title: &str, reel: &str) -> io::Result<()> {
+        let drop_frame = self.segments.first().is_some_and(|s| s.first.drop_frame);
+        writeln!(out, "TITLE: {title}")?;
+        writeln!(
+            out,
+            "FCM: {}",
+            if drop_frame {
+                "DROP FRAME"
+            } else {
+                "NON-DROP FRAME"
+            }
+        )?;
+        let reel: String = reel.chars().take(8).collect();
+        for (event, segment) in self.segments.iter().filter(|s| !s.reverse).enumerate() {
+            writeln!(
+                out,
+                "\n{:03}  {reel:<8} A     C        {} {} {} {}",
+                event + 1,
+                segment.first,
+                self.out_point(segment),
+                self.record_timecode(segment.off_start, drop_frame),
+                self.record_timecode(segment.off_end + 1, drop_frame)
+            )?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    /// An Avid ALE with one clip per segment, named `<name>_<n>`. Segments played in reverse
+    /// are left out.
+    pub fn write_ale(&self, out: &mut impl Write, name: &str, tape: &str) -> io::Result<()> {
+        writeln!(out, "Heading")?;
+        writeln!(out, "FIELD_DELIM\tTABS")?;
+        writeln!(
+            out,
+            "AUDIO_FORMAT\t{}khz",
+            (self.sample_rate / 1000.0).round()
+        )?;
+        writeln!(out, "FPS\t{}", self.fps_label())?;
+        writeln!(out)?;
+        writeln!(out, "Column")?;
+        writeln!(out, "Name\tTape\tTracks\tStart\tEnd\tDuration")?;
+        writeln!(out)?;
+        writeln!(out, "Data")?;
+        for (i, segment) in self.segments.iter().filter(|s| !s.reverse).enumerate() {
+            let duration = LoggedTimecode::from_frame_number(
+                segment.frames as i64,
+                self.fps,
+                segment.first.drop_frame,
+            );
+            writeln!(
+                out,
+                "{name}_{}\t{tape}\tA1\t{}\t{}\t{duration}",
+                i + 1,
+                segment.first,
+                self.out_point(segment)
+            )?;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    // 25, 29.97, 23.976...
+    fn fps_label(&self) -> String {
+        if (self.fps - self.fps.round()).abs() < 0.001 {
+            format!("{}", self.fps.round())
+        } else {
+            format!("{:.3}", self.fps).trim_end_matches('0').to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decoder::{LTCDecoder, LTCDecoderConfig};
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+    use crate::api::Timezone;
+    use alloc::string::ToString;
+
+    fn logged(number: i64, reverse: bool) -> LoggedFrame {
+        LoggedFrame {
+            timecode: LoggedTimecode::from_frame_number(number, 25.0, false),
+            user_bits: 0,
+            off_start: number * 1920,
+            off_end: number * 1920 + 1919,
+            reverse,
+            volume: -3.0,
+        }
+    }
+
+    #[test]
+    fn test_frame_numbers() {
+        for drop_frame in [false, true] {
+            for number in [0, 1, 1799, 1800, 17_982, 107_891, 107_892, 2_589_407] {
+                let timecode = LoggedTimecode::from_frame_number(number, 29.97, drop_frame);
+                assert_eq!(timecode.frame_number(29.97), number);
+            }
+        }
+        let timecode = LoggedTimecode::from_frame_number(1800, 29.97, true);
+        assert_eq!(timecode.to_string(), "00:01:00;02");
+        // Wraps at midnight
+        let timecode = LoggedTimecode::from_frame_number(-1, 25.0, false);
+        assert_eq!(timecode.to_string(), "23:59:59:24");
+    }
+
+    #[test]
+    fn test_segments() {
+        let mut log = TimecodeLog::new(25.0, 48_000.0);
+        // 0..10, a jump to 100..105, then back from 104 in reverse
+        let numbers = (0..10).chain(100..105);
+        for number in numbers {
+            log.push_logged(logged(number, false));
+        }
+        for number in (100..104).rev() {
+            log.push_logged(logged(number, true));
+        }
+        let segments = log.segments();
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0].frames, 10);
+        assert_eq!(segments[0].last.to_string(), "00:00:00:09");
+        assert_eq!(log.out_point(&segments[0]).to_string(), "00:00:00:10");
+        assert_eq!(segments[1].first.to_string(), "00:00:04:00");
+        assert!(segments[2].reverse);
+        assert_eq!(segments[2].frames, 4);
+        assert_eq!(log.frames().len(), 19);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_exports() {
+        let mut log = TimecodeLog::new(25.0, 48_000.0);
+        for number in (0..3).chain(250..252) {
+            log.push_logged(logged(number, false));
+        }
+
+        let mut csv = vec![];
+        log.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert_eq!(csv.lines().count(), 6);
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("0,1919,00:00:00:00,00000000,false,-3.0")
+        );
+
+        let mut json = vec![];
+        log.write_json(&mut json).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert!(json.starts_with("[\n  {\"off_start\":0,"));
+        assert_eq!(json.matches("\"timecode\"").count(), 5);
+
+        let mut edl = vec![];
+        log.write_edl(&mut edl, "TEST", "A001").unwrap();
+        let edl = String::from_utf8(edl).unwrap();
+        assert!(edl.starts_with("TITLE: TEST\nFCM: NON-DROP FRAME\n"));
+        assert!(edl.contains(
+            "001  A001     A     C        00:00:00:00 00:00:00:03 00:00:00:00 00:00:00:03"
+        ));
+        assert!(edl.contains(
+            "002  A001     A     C        00:00:10:00 00:00:10:02 00:00:10:00 00:00:10:02"
+        ));
+
+        let mut ale = vec![];
+        log.write_ale(&mut ale, "take", "A001").unwrap();
+        let ale = String::from_utf8(ale).unwrap();
+        assert!(ale.contains("FPS\t25\n"));
+        assert!(ale.contains("take_2\tA001\tA1\t00:00:10:00\t00:00:10:02\t00:00:00:02\n"));
+    }
+
+    #[test]
+    fn test_log_decoded_frames() {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        let mut log = TimecodeLog::new(25.0, 48_000.0);
+        encoder.set_timecode(&SMPTETimecode::new(
+            Timezone::default(),
+            0,
+            0,
+            0,
+            1,
+            0,
+            0,
+            0,
+        ));
+        let mut pos = 0;
+        for i in 0..20 {
+            if i == 10 {
+                // Jump ahead by a second
+                let tc = encoder.get_timecode();
+                let tc = SMPTETimecode::new(
+                    Timezone::default(),
+                    0,
+                    0,
+                    0,
+                    tc.hours(),
+                    tc.minutes(),
+                    tc.seconds() + 1,
+                    tc.frame(),
+                );
+                encoder.set_timecode(&tc);
+            }
+            encoder.encode_frame();
+            let (buf, len) = encoder.get_buf_ref(true);
+            decoder.write(buf, pos);
+            pos += len as i64;
+            encoder.inc_timecode().unwrap();
+            while let Some(frame) = decoder.read() {
+                log.push(&frame);
+            }
+        }
+        let segments = log.segments();
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[1].first.to_string(), "01:00:01:10");
+    }
+}
@@ -0,0 +1,344 @@
+use super::consts::SampleType;
+use super::LTCTVStandard;
+#[cfg(not(feature = "std"))]
+use crate::math::F64Ext;
+
+/// Rise time (10% to 90%) recommended by SMPTE 12M, in microseconds
+pub const SMPTE_RISE_TIME_US: f64 = 40.0;
+/// Rise time (10% to 90%) recommended by EBU Tech 3097 for 25fps, in microseconds
+pub const EBU_RISE_TIME_US: f64 = 50.0;
+
+/// Shaping of the edges of the encoded signal, see `LTCEncoder::set_filter_preset`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum EncoderFilter {
+    /// Unfiltered square wave
+    Square,
+    /// The rise time recommended for the encoder's standard
+    Standard,
+    /// Rise time in microseconds (10% to 90%)
+    RiseTime(f64),
+    /// libltc's own filter parameter, as passed to `LTCEncoder::set_filter`. Its rise time
+    /// is shorter, see `EncoderFilter::libltc_filter`.
+    Libltc(f64),
+}
+
+/// libltc's filter parameter after creating or resetting an encoder
+pub const LIBLTC_DEFAULT_FILTER: f64 = 40.0;
+
+impl EncoderFilter {
+    /// The value to pass to libltc's `ltc_encoder_set_filter`, 0 means no filtering.
+    ///
+    /// libltc's parameter is not the rise time of its output: each edge starts half a sample
+    /// from the midpoint and approaches the new level by a constant fraction per sample,
+    /// reaching 90% after `ln(5) / e` of the parameter plus half a sample. This inverts that,
+    /// ignoring the rounding to 8 bits.
+    pub fn libltc_filter(&self, standard: LTCTVStandard, sample_rate: f64) -> f64 {
+        let rise_time = match self {
+            EncoderFilter::Square => return 0.0,
+            EncoderFilter::Libltc(filter) => return filter.max(0.0),
+            EncoderFilter::Standard => standard_rise_time(standard),
+            EncoderFilter::RiseTime(rise_time) => *rise_time,
+        };
+        if rise_time <= 0.0 {
+            return 0.0;
+        }
+        core::f64::consts::E * (rise_time + 1e6 / sample_rate) / LN_5
+    }
+}
+
+const LN_5: f64 = 1.609_437_912_434_100_3;
+
+pub fn standard_rise_time(standard: LTCTVStandard) -> f64 {
+    match standard {
+        LTCTVStandard::LTCTV_625_50 => EBU_RISE_TIME_US,
+        _ => SMPTE_RISE_TIME_US,
+    }
+}
+
+/// Default cutoff of the band limiter. The 2.4kHz LTC fundamental and the harmonics needed
+/// for a 40us rise time are well below it.
+pub const BAND_LIMIT_CUTOFF_HZ: f64 = 10_000.0;
+
+// Q of the two sections of a 4th order Butterworth lowpass
+const BUTTERWORTH_Q: [f64; 2] = [0.541_196_100_146_197, 1.306_562_964_876_376_7];
+
+#[derive(Debug, Copy, Clone, Default)]
+struct Biquad {
+    b0: f64,
+    b1: f64,
+    b2: f64,
+    a1: f64,
+    a2: f64,
+    z1: f64,
+    z2: f64,
+}
+
+impl Biquad {
+    fn lowpass(sample_rate: f64, cutoff: f64, q: f64) -> Self {
+        let w0 = 2.0 * core::f64::consts::PI * cutoff / sample_rate;
+        let alpha = w0.sin() / (2.0 * q);
+        let cos = w0.cos();
+        let a0 = 1.0 + alpha;
+        Biquad {
+            b0: (1.0 - cos) / 2.0 / a0,
+            b1: (1.0 - cos) / a0,
+            b2: (1.0 - cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+            z1: 0.0,
+            z2: 0.0,
+        }
+    }
+
+    // Transposed direct form II
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b0 * x + self.z1;
+        self.z1 = self.b1 * x - self.a1 * y + self.z2;
+        self.z2 = self.b2 * x - self.a2 * y;
+        y
+    }
+
+    fn reset(&mut self) {
+        self.z1 = 0.0;
+        self.z2 = 0.0;
+    }
+}
+
+/// 4th order Butterworth lowpass for 8-bit encoder output (centred at 128).
+///
+/// It removes the energy between the cutoff and Nyquist, which a cheap converter otherwise
+/// images, e.g. when a square wave is played at 44.1kHz. The filter keeps its state between
+/// calls so a stream can be processed buffer by buffer.
+///
+/// It runs on the samples the encoder has already generated and quantized. The harmonics of
+/// edges snapped to the sample grid are already folded below Nyquist by then, and that
+/// aliasing passes the filter like the signal does. To keep it low, encode at a higher sample
+/// rate and resample, or use the rise time filter (`EncoderFilter`), which shapes the edges
+/// while they are generated.
+#[derive(Debug, Clone)]
+pub struct BandLimiter {
+    sections: [Biquad; 2],
+    cutoff: f64,
+    requested_cutoff: f64,
+}
+
+impl BandLimiter {
+    /// The cutoff is clamped to 45% of the sample rate
+    pub fn new(sample_rate: f64, cutoff: f64) -> Self {
+        let clamped = cutoff.min(sample_rate * 0.45);
+        BandLimiter {
+            sections: BUTTERWORTH_Q.map(|q| Biquad::lowpass(sample_rate, clamped, q)),
+            cutoff: clamped,
+            requested_cutoff: cutoff,
+        }
+    }
+
+    /// The cutoff in use, after clamping
+    pub fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+
+    /// The cutoff passed to `new`
+    pub fn requested_cutoff(&self) -> f64 {
+        self.requested_cutoff
+    }
+
+    pub fn process(&mut self, buf: &mut [SampleType]) {
+        for sample in buf.iter_mut() {
+            let x = *sample as f64 - 128.0;
+            let y = self.sections.iter_mut().fold(x, |x, s| s.process(x));
+            *sample = (y + 128.0).round().clamp(0.0, 255.0) as SampleType;
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.sections.iter_mut().for_each(Biquad::reset);
+    }
+
+    /// The delay line of both sections, to continue a stream elsewhere with `set_history`
+    pub fn history(&self) -> [f64; 4] {
+        let [a, b] = self.sections;
+        [a.z1, a.z2, b.z1, b.z2]
+    }
+
+    pub fn set_history(&mut self, history: [f64; 4]) {
+        let [a, b] = &mut self.sections;
+        [a.z1, a.z2, b.z1, b.z2] = history;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consts::LtcBgFlags;
+    use crate::api::decoder::{LTCDecoder, LTCDecoderConfig};
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+    use crate::api::test_util::encode_with;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    const SAMPLE_RATE: f64 = 44_100.0;
+
+    fn encode(filter: EncoderFilter, band_limit: Option<f64>, frames: usize) -> Vec<SampleType> {
+        let config = LTCEncoderConfig {
+            sample_rate: SAMPLE_RATE,
+            fps: 30.0,
+            standard: LTCTVStandard::LTCTV_525_60,
+            flags: LtcBgFlags::default(),
+        };
+        let mut encoder = LTCEncoder::try_new(&config).unwrap();
+        encoder.set_filter_preset(filter);
+        encoder.set_band_limit(band_limit);
+        encode_with(&mut encoder, frames)
+    }
+
+    // Energy of the signal between `from` and `to` Hz, using a plain DFT
+    fn band_energy(samples: &[SampleType], from: f64, to: f64) -> f64 {
+        let n = samples.len();
+        let resolution = SAMPLE_RATE / n as f64;
+        let first = (from / resolution).ceil() as usize;
+        let last = ((to / resolution).floor() as usize).min(n / 2);
+        (first..=last)
+            .map(|k| {
+                let w = 2.0 * core::f64::consts::PI * k as f64 / n as f64;
+                let (re, im) = samples
+                    .iter()
+                    .enumerate()
+                    .fold((0.0, 0.0), |(re, im), (i, &s)| {
+                        let x = s as f64 - 128.0;
+                        (re + x * (w * i as f64).cos(), im - x * (w * i as f64).sin())
+                    });
+                re * re + im * im
+            })
+            .sum()
+    }
+
+    // 10% to 90% time of every complete edge, in microseconds
+    fn rise_times(samples: &[SampleType], sample_rate: f64) -> Vec<f64> {
+        let lo = *samples.iter().min().unwrap() as f64;
+        let hi = *samples.iter().max().unwrap() as f64;
+        let level = |fraction: f64| lo + (hi - lo) * fraction;
+        // Where the signal crosses `level` between sample i and the next one, if it does
+        let crossing = |i: usize, level: f64| {
+            let (a, b) = (samples[i] as f64, samples[i + 1] as f64);
+            ((a < level) != (b < level)).then(|| i as f64 + (level - a) / (b - a))
+        };
+
+        let mut times = vec![];
+        for edge in (0..samples.len() - 1).filter(|&i| crossing(i, level(0.5)).is_some()) {
+            let (start, end) = if samples[edge + 1] > samples[edge] {
+                (level(0.1), level(0.9))
+            } else {
+                (level(0.9), level(0.1))
+            };
+            let start = (0..=edge).rev().find_map(|i| crossing(i, start));
+            let end = (edge..samples.len() - 1).find_map(|i| crossing(i, end));
+            if let (Some(start), Some(end)) = (start, end) {
+                times.push((end - start) / sample_rate * 1e6);
+            }
+        }
+        times
+    }
+
+    #[test]
+    fn test_filter_presets() {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        assert_eq!(encoder.get_filter(), LIBLTC_DEFAULT_FILTER);
+        encoder.set_filter(25.0);
+        assert_eq!(encoder.filter_preset(), EncoderFilter::Libltc(25.0));
+
+        encoder.set_filter_preset(EncoderFilter::Square);
+        assert_eq!(encoder.get_filter(), 0.0);
+
+        encoder.set_filter_preset(EncoderFilter::Standard);
+        let ebu = EncoderFilter::RiseTime(EBU_RISE_TIME_US)
+            .libltc_filter(LTCTVStandard::LTCTV_625_50, 48_000.0);
+        assert!((encoder.get_filter() - ebu).abs() < 0.5);
+
+        // The preset follows the standard on reinit
+        encoder
+            .reinit(48_000.0, 30.0, LTCTVStandard::LTCTV_525_60, 0.into())
+            .unwrap();
+        let smpte = EncoderFilter::RiseTime(SMPTE_RISE_TIME_US)
+            .libltc_filter(LTCTVStandard::LTCTV_525_60, 48_000.0);
+        assert!((encoder.get_filter() - smpte).abs() < 0.5);
+        assert_eq!(encoder.filter_preset(), EncoderFilter::Standard);
+
+        // The band limit is clamped for the sample rate, reinit starts from the requested one
+        let config = LTCEncoderConfig {
+            sample_rate: 96_000.0,
+            ..Default::default()
+        };
+        let mut encoder = LTCEncoder::try_new(&config).unwrap();
+        encoder.set_band_limit(Some(30_000.0));
+        for sample_rate in [48_000.0, 96_000.0] {
+            encoder
+                .reinit(sample_rate, 25.0, LTCTVStandard::LTCTV_625_50, 0.into())
+                .unwrap();
+            assert_eq!(encoder.band_limit(), Some(30_000.0));
+        }
+    }
+
+    #[test]
+    fn test_rise_time() {
+        // SMPTE 12M allows 40 +/- 10 us, EBU Tech 3097 50 +/- 10 us
+        for sample_rate in [44_100.0, 48_000.0, 96_000.0, 192_000.0] {
+            for (standard, fps, nominal) in [
+                (LTCTVStandard::LTCTV_525_60, 30.0, SMPTE_RISE_TIME_US),
+                (LTCTVStandard::LTCTV_625_50, 25.0, EBU_RISE_TIME_US),
+            ] {
+                let config = LTCEncoderConfig {
+                    sample_rate,
+                    fps,
+                    standard,
+                    flags: LtcBgFlags::default(),
+                };
+                let mut encoder = LTCEncoder::try_new(&config).unwrap();
+                encoder.set_filter_preset(EncoderFilter::Standard);
+                let times = rise_times(&encode_with(&mut encoder, 4), sample_rate);
+
+                assert!(times.len() > 300);
+                for time in times {
+                    assert!(
+                        (time - nominal).abs() <= 10.0,
+                        "{time} us at {sample_rate} Hz, {standard:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_filter_spectrum() {
+        let square = encode(EncoderFilter::Square, None, 10);
+        let limited = encode(EncoderFilter::Square, Some(BAND_LIMIT_CUTOFF_HZ), 10);
+        let square_high = band_energy(&square, 18_000.0, 22_050.0);
+        let limited_high = band_energy(&limited, 18_000.0, 22_050.0);
+
+        // The band limiter removes (almost) everything close to Nyquist
+        assert!(limited_high < square_high / 30.0);
+
+        // ...while the LTC fundamental band is left alone
+        let square_low = band_energy(&square, 1_000.0, 2_500.0);
+        let limited_low = band_energy(&limited, 1_000.0, 2_500.0);
+        assert!(limited_low > square_low * 0.8);
+    }
+
+    #[test]
+    fn test_band_limited_decodes() {
+        let samples = encode(EncoderFilter::Square, Some(BAND_LIMIT_CUTOFF_HZ), 10);
+        let config = LTCDecoderConfig {
+            initial_apv: (SAMPLE_RATE / 30.0) as i32,
+            queue_size: 32,
+        };
+        let mut decoder = LTCDecoder::try_new(&config).unwrap();
+        decoder.write(&samples, 0);
+
+        let mut decoded = vec![];
+        while let Some(frame) = decoder.read() {
+            decoded.push(frame.ltc().to_timecode(LtcBgFlags::default()).frame());
+        }
+        assert!(decoded.len() >= 8);
+        assert!(decoded.windows(2).all(|w| w[1] == w[0] + 1));
+    }
+}
@@ -7,11 +7,22 @@ use crate::api::TimecodeWasWrapped;
 use crate::error::TimecodeError;
 use crate::raw;
 
+#[cfg(feature = "pure-rust")]
+use super::native::timecode as native;
+
 #[derive(Debug, Copy, Clone, Default)]
 pub struct LTCFrame {
     pub(super) inner_raw: raw::LTCFrame,
 }
 
+impl PartialEq for LTCFrame {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for LTCFrame {}
+
 impl From<raw::LTCFrame> for LTCFrame {
     fn from(inner: raw::LTCFrame) -> Self {
         LTCFrame { inner_raw: inner }
@@ -22,95 +33,107 @@ impl LTCFrame {
     pub fn dfbit(&self) -> u32 {
         self.inner_raw.dfbit()
     }
+
+    /// Marks the frame as drop frame timecode. The parity bit is not updated.
+    pub fn set_dfbit(&mut self, dfbit: bool) {
+        self.inner_raw.set_dfbit(dfbit as u32);
+    }
+
+    /// The 80 bits of the frame in transmission order, bit 0 is the LSB of the first byte
+    pub fn to_bytes(&self) -> [u8; 10] {
+        core::array::from_fn(|i| self.inner_raw._bitfield_1.get(i * 8, 8) as u8)
+    }
+
+    /// Whether the frame has an even number of 1 bits, as `set_parity` makes it
+    pub fn parity_ok(&self) -> bool {
+        self.to_bytes()
+            .iter()
+            .fold(0u8, |p, byte| p ^ byte)
+            .count_ones()
+            .is_multiple_of(2)
+    }
+
+    pub fn from_bytes(bytes: [u8; 10]) -> Self {
+        let mut inner_raw = raw::LTCFrame::default();
+        for (i, byte) in bytes.into_iter().enumerate() {
+            inner_raw._bitfield_1.set(i * 8, 8, byte as u64);
+        }
+        inner_raw.into()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, Default)]
 pub struct LTCFrameExt {
-    pub(super) inner_unsafe_ptr: *mut raw::LTCFrameExt,
+    pub(super) inner_raw: raw::LTCFrameExt,
+}
+
+impl PartialEq for LTCFrameExt {
+    fn eq(&self, other: &Self) -> bool {
+        self.ltc() == other.ltc()
+            && self.off_start() == other.off_start()
+            && self.off_end() == other.off_end()
+            && self.reverse() == other.reverse()
+            && self.biphase_tics() == other.biphase_tics()
+            && self.sample_min() == other.sample_min()
+            && self.sample_max() == other.sample_max()
+            && self.volume() == other.volume()
+    }
 }
 
 impl LTCFrameExt {
-    // SAFETY: this is safe because we own the pointer
     pub fn ltc(&self) -> LTCFrame {
-        unsafe { *self.inner_unsafe_ptr }.ltc.into()
+        self.inner_raw.ltc.into()
+    }
+    pub fn set_ltc(&mut self, ltc: LTCFrame) {
+        self.inner_raw.ltc = ltc.inner_raw;
     }
     pub fn off_start(&self) -> i64 {
-        unsafe { *self.inner_unsafe_ptr }.off_start
+        self.inner_raw.off_start
     }
-    pub fn set_off_start(&self, off_start: i64) {
-        unsafe {
-            (*self.inner_unsafe_ptr).off_start = off_start;
-        }
+    pub fn set_off_start(&mut self, off_start: i64) {
+        self.inner_raw.off_start = off_start;
     }
     pub fn off_end(&self) -> i64 {
-        unsafe { *self.inner_unsafe_ptr }.off_end
+        self.inner_raw.off_end
     }
-    pub fn set_off_end(&self, off_end: i64) {
-        unsafe {
-            (*self.inner_unsafe_ptr).off_end = off_end;
-        }
+    pub fn set_off_end(&mut self, off_end: i64) {
+        self.inner_raw.off_end = off_end;
     }
     pub fn reverse(&self) -> bool {
-        unsafe { *self.inner_unsafe_ptr }.reverse != 0
+        self.inner_raw.reverse != 0
     }
-    pub fn set_reverse(&self, reverse: bool) {
-        unsafe {
-            (*self.inner_unsafe_ptr).reverse = if reverse { 1 } else { 0 };
-        }
+    pub fn set_reverse(&mut self, reverse: bool) {
+        self.inner_raw.reverse = reverse as i32;
     }
     pub fn biphase_tics(&self) -> [f32; 80usize] {
-        unsafe { *self.inner_unsafe_ptr }.biphase_tics
+        self.inner_raw.biphase_tics
     }
-    pub fn set_biphase_tics(&self, biphase_tics: [f32; 80usize]) {
-        unsafe {
-            (*self.inner_unsafe_ptr).biphase_tics = biphase_tics;
-        }
+    pub fn set_biphase_tics(&mut self, biphase_tics: [f32; 80usize]) {
+        self.inner_raw.biphase_tics = biphase_tics;
     }
     pub fn sample_min(&self) -> SampleType {
-        unsafe { *self.inner_unsafe_ptr }.sample_min
+        self.inner_raw.sample_min
     }
-    pub fn set_sample_min(&self, sample_min: SampleType) {
-        unsafe {
-            (*self.inner_unsafe_ptr).sample_min = sample_min;
-        }
+    pub fn set_sample_min(&mut self, sample_min: SampleType) {
+        self.inner_raw.sample_min = sample_min;
     }
     pub fn sample_max(&self) -> SampleType {
-        unsafe { *self.inner_unsafe_ptr }.sample_max
+        self.inner_raw.sample_max
     }
-    pub fn set_sample_max(&self, sample_max: SampleType) {
-        unsafe {
-            (*self.inner_unsafe_ptr).sample_max = sample_max;
-        }
+    pub fn set_sample_max(&mut self, sample_max: SampleType) {
+        self.inner_raw.sample_max = sample_max;
     }
     pub fn volume(&self) -> f64 {
-        unsafe { *self.inner_unsafe_ptr }.volume
+        self.inner_raw.volume
     }
-    pub fn set_volume(&self, volume: f64) {
-        unsafe {
-            (*self.inner_unsafe_ptr).volume = volume;
-        }
+    pub fn set_volume(&mut self, volume: f64) {
+        self.inner_raw.volume = volume;
     }
 }
 
-// SAFETY: We are allocating the pointer as a Box so it outlives the function
-// Drop is implemented for LTCFrameExt
-impl Default for LTCFrameExt {
-    fn default() -> Self {
-        let inner = Box::new(raw::LTCFrameExt::default());
-        LTCFrameExt {
-            inner_unsafe_ptr: Box::into_raw(inner),
-        }
-    }
-}
-
-impl Drop for LTCFrameExt {
-    fn drop(&mut self) {
-        if !self.inner_unsafe_ptr.is_null() {
-            // SAFETY: the pointer is assumed to not be null
-            unsafe {
-                let _ = Box::from_raw(self.inner_unsafe_ptr);
-            }
-        }
+impl From<raw::LTCFrameExt> for LTCFrameExt {
+    fn from(inner_raw: raw::LTCFrameExt) -> Self {
+        LTCFrameExt { inner_raw }
     }
 }
 
@@ -119,7 +142,10 @@ impl LTCFrame {
         // SAFETY: The pointer will outlive the function because it is allocated in a Box
         let mut inner_raw = raw::LTCFrame::default();
 
+        #[cfg(feature = "pure-rust")]
+        native::frame_reset(&mut inner_raw);
         // SAFETY: frame is created above and is not null
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
             #[allow(clippy::needless_borrow)] // for clarity
             raw::ltc_frame_reset(&mut inner_raw);
@@ -130,19 +156,25 @@ impl LTCFrame {
 
     pub fn to_timecode(&self, flags: consts::LtcBgFlags) -> SMPTETimecode {
         let mut timecode = SMPTETimecode::default();
-        let mut inner_raw = self.inner_raw;
+        self.to_timecode_inplace(&mut timecode, flags);
+        timecode
+    }
 
+    /// Like `to_timecode`, without allocating
+    pub fn to_timecode_inplace(&self, timecode: &mut SMPTETimecode, flags: consts::LtcBgFlags) {
+        #[cfg(feature = "pure-rust")]
+        // SAFETY: We own timecode
+        native::frame_to_time(
+            unsafe { &mut *timecode.inner_unsafe_ptr },
+            &self.inner_raw,
+            flags,
+        );
         // SAFETY: We own timecode. The function is assumed to only read the frame.
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
-            #[allow(clippy::needless_borrow)] // for clarity
-            raw::ltc_frame_to_time(
-                (&mut timecode).inner_unsafe_ptr,
-                &mut inner_raw,
-                flags.into(),
-            );
+            let mut inner_raw = self.inner_raw;
+            raw::ltc_frame_to_time(timecode.inner_unsafe_ptr, &mut inner_raw, flags.into());
         }
-
-        timecode
     }
 
     pub fn from_timecode(
@@ -151,18 +183,7 @@ impl LTCFrame {
         flags: consts::LtcBgFlags,
     ) -> Self {
         let mut frame = Self::new();
-
-        // SAFETY: We own frame. The function is assumed to only read the timecode.
-        unsafe {
-            #[allow(clippy::needless_borrow)] // for clarity
-            raw::ltc_time_to_frame(
-                &mut frame.inner_raw,
-                timecode.inner_unsafe_ptr,
-                standard.to_raw(),
-                flags.into(),
-            );
-        }
-
+        frame.from_timecode_inplace(timecode, standard, flags);
         frame
     }
 
@@ -172,7 +193,16 @@ impl LTCFrame {
         standard: LTCTVStandard,
         flags: consts::LtcBgFlags,
     ) {
+        #[cfg(feature = "pure-rust")]
+        // SAFETY: The timecode pointer is valid for the lifetime of timecode
+        native::time_to_frame(
+            &mut self.inner_raw,
+            unsafe { &*timecode.inner_unsafe_ptr },
+            standard,
+            flags,
+        );
         // SAFETY: We own frame. The function is assumed to only read the timecode.
+        #[cfg(not(feature = "pure-rust"))]
         unsafe {
             #[allow(clippy::needless_borrow)] // for clarity
             raw::ltc_time_to_frame(
@@ -190,7 +220,11 @@ impl LTCFrame {
         standard: LTCTVStandard,
         flags: LtcBgFlags,
     ) -> Result<TimecodeWasWrapped, TimecodeError> {
+        #[cfg(feature = "pure-rust")]
+        let timecode_was_wrapped =
+            native::frame_increment(&mut self.inner_raw, fps, standard, flags);
         // SAFETY: We own self
+        #[cfg(not(feature = "pure-rust"))]
         let timecode_was_wrapped = unsafe {
             raw::ltc_frame_increment(&mut self.inner_raw, fps, standard.to_raw(), flags.into())
         };
@@ -207,7 +241,11 @@ impl LTCFrame {
         standard: LTCTVStandard,
         flags: LtcBgFlags,
     ) -> Result<TimecodeWasWrapped, TimecodeError> {
+        #[cfg(feature = "pure-rust")]
+        let timecode_was_wrapped =
+            native::frame_decrement(&mut self.inner_raw, fps, standard, flags);
         // SAFETY: We own self
+        #[cfg(not(feature = "pure-rust"))]
         let timecode_was_wrapped = unsafe {
             raw::ltc_frame_decrement(&mut self.inner_raw, fps, standard.to_raw(), flags.into())
         };
@@ -219,26 +257,91 @@ impl LTCFrame {
     }
 
     pub fn set_parity(&mut self, standard: LTCTVStandard) {
+        #[cfg(feature = "pure-rust")]
+        native::frame_set_parity(&mut self.inner_raw, standard);
+        #[cfg(not(feature = "pure-rust"))]
         // SAFETY: We own self
         unsafe {
             raw::ltc_frame_set_parity(&mut self.inner_raw, standard.to_raw());
         }
     }
 
+    #[cfg(feature = "pure-rust")]
+    pub fn parse_bcg_flags(&self, standard: LTCTVStandard) -> LtcBgFlags {
+        native::frame_parse_bcg_flags(&self.inner_raw, standard).into()
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn parse_bcg_flags(&self, standard: LTCTVStandard) -> LtcBgFlags {
         let mut inner_raw = self.inner_raw;
         // SAFETY: The function is assumed to only read self (the frame)
         unsafe { raw::ltc_frame_parse_bcg_flags(&mut inner_raw, standard.to_raw()) }.into()
     }
 
+    #[cfg(feature = "pure-rust")]
+    pub fn get_user_bits(&self) -> u32 {
+        native::frame_get_user_bits(&self.inner_raw)
+    }
+
+    #[cfg(not(feature = "pure-rust"))]
     pub fn get_user_bits(&self) -> u32 {
         let mut inner_raw = self.inner_raw;
         // SAFETY: The function is assumed to only read self (the frame)
         unsafe { raw::ltc_frame_get_user_bits(&mut inner_raw) as u32 }
     }
+
+    // Same layout as ltc_encoder_set_user_bits: the lowest nibble goes to user1
+    pub fn set_user_bits(&mut self, data: u32) {
+        self.inner_raw.set_user1(data & 0xf);
+        self.inner_raw.set_user2((data >> 4) & 0xf);
+        self.inner_raw.set_user3((data >> 8) & 0xf);
+        self.inner_raw.set_user4((data >> 12) & 0xf);
+        self.inner_raw.set_user5((data >> 16) & 0xf);
+        self.inner_raw.set_user6((data >> 20) & 0xf);
+        self.inner_raw.set_user7((data >> 24) & 0xf);
+        self.inner_raw.set_user8((data >> 28) & 0xf);
+    }
 }
 
+#[cfg(feature = "pure-rust")]
+pub fn calc_frame_alignment(samples_per_frame: f64, standard: LTCTVStandard) -> i64 {
+    native::frame_alignment(samples_per_frame, standard)
+}
+
+#[cfg(not(feature = "pure-rust"))]
 pub fn calc_frame_alignment(samples_per_frame: f64, standard: LTCTVStandard) -> i64 {
     // SAFETY: The function is assumed to be pure
     unsafe { raw::ltc_frame_alignment(samples_per_frame, standard.to_raw()) }
 }
+
+// Sends frames to another thread
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use crate::api::decoder::{LTCDecoder, LTCDecoderConfig};
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+
+    #[test]
+    fn test_frame_ext_value() {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        for i in 0..3 {
+            encoder.encode_frame();
+            let (buf, len) = encoder.get_buf_ref(true);
+            decoder.write(buf, i * len as i64);
+            encoder.inc_timecode().unwrap();
+        }
+
+        let frames: Vec<LTCFrameExt> = core::iter::from_fn(|| decoder.read()).collect();
+        let sent = std::thread::spawn(move || frames).join().unwrap();
+        assert!(sent.len() >= 2);
+        assert_ne!(sent[0], sent[1]);
+
+        let mut copy = sent[0];
+        assert_eq!(copy, sent[0]);
+        copy.set_off_start(copy.off_start() + 1);
+        copy.set_reverse(!copy.reverse());
+        assert_ne!(copy, sent[0]);
+        assert_eq!(sent[0].ltc().to_timecode(LtcBgFlags::default()).frame(), 0);
+    }
+}
@@ -1,14 +1,44 @@
+#[cfg(feature = "net")]
+pub mod artnet;
+#[cfg(feature = "wav")]
+pub mod bwf;
 pub mod consts;
 pub mod decoder;
 pub mod encoder;
+pub mod export;
+pub mod filter;
 pub mod frame;
+pub mod mtc;
+#[cfg(feature = "pure-rust")]
+mod native;
+#[cfg(feature = "net")]
+pub mod osc;
+pub mod payload;
+pub mod polarity;
+#[cfg(feature = "serde")]
+mod serialize;
+pub mod snapshot;
+pub mod split;
+#[cfg(feature = "async")]
+pub mod stream;
+#[cfg(test)]
+mod test_util;
+pub mod userbits;
+#[cfg(feature = "wav")]
+pub mod wav;
 
-use std::fmt::Display;
+use alloc::boxed::Box;
+use core::ffi::c_char;
+use core::fmt::Display;
 
 use crate::error;
 use crate::error::TimecodeError;
 use crate::raw;
-pub use error::{LTCDecoderError, LTCEncoderError};
+#[cfg(feature = "net")]
+pub use error::NetError;
+#[cfg(feature = "wav")]
+pub use error::WavError;
+pub use error::{LTCDecoderError, LTCEncoderError, PayloadError, SnapshotError, UserBitsError};
 
 #[derive(Debug)]
 pub struct SMPTETimecode {
@@ -53,7 +83,7 @@ impl TryInto<TimecodeWasWrapped> for i32 {
 }
 
 impl Display for SMPTETimecode {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let timezone = self.timezone();
         let years = self.years();
         let months = self.months();
@@ -159,14 +189,16 @@ impl SMPTETimecode {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
-pub struct Timezone([i8; 6]);
+/// A NUL terminated timezone string as in `raw::SMPTETimecode`, e.g. "+0100". `c_char` is
+/// signed or unsigned depending on the target, the conversions accept both.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Timezone([c_char; 6]);
 
 impl Display for Timezone {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let u8_timezone = self.0.iter().map(|x| *x as u8).collect::<Vec<u8>>();
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let u8_timezone = self.to_bytes();
 
-        let str = std::str::from_utf8(u8_timezone.as_slice()).unwrap();
+        let str = core::str::from_utf8(&u8_timezone).unwrap();
         write!(f, "{str}")
     }
 }
@@ -179,41 +211,131 @@ impl Default for Timezone {
 }
 impl From<&[i8; 6]> for Timezone {
     fn from(timezone: &[i8; 6]) -> Self {
-        Timezone(timezone.to_owned())
+        Timezone(timezone.map(|x| x as c_char))
     }
 }
 
 impl From<&[u8; 6]> for Timezone {
     fn from(timezone: &[u8; 6]) -> Self {
-        Timezone(timezone.map(|x| x as i8))
+        Timezone(timezone.map(|x| x as c_char))
     }
 }
 
 impl From<[i8; 6]> for Timezone {
     fn from(timezone: [i8; 6]) -> Self {
-        Timezone(timezone)
+        Timezone(timezone.map(|x| x as c_char))
     }
 }
 
 impl From<[u8; 6]> for Timezone {
     fn from(timezone: [u8; 6]) -> Self {
-        Timezone(timezone.map(|x| x as i8))
+        Timezone(timezone.map(|x| x as c_char))
     }
 }
 
 impl Timezone {
-    pub fn new(timezone: [i8; 6]) -> Self {
+    pub fn new(timezone: [c_char; 6]) -> Self {
         Timezone(timezone)
     }
 
-    pub fn to_raw(&self) -> [i8; 6] {
+    pub fn to_raw(&self) -> [c_char; 6] {
         self.0
     }
+
+    pub fn to_bytes(&self) -> [u8; 6] {
+        self.0.map(|x| x as u8)
+    }
+
+    /// The SMPTE 309M code of the timezone, `None` if libltc doesn't know it
+    pub(crate) fn smpte_code(&self) -> Option<u8> {
+        // Compared as a C string
+        let len = self.0.iter().position(|&b| b == 0).unwrap_or(6);
+        let timezone = &self.0[..len];
+        SMPTE_TIMEZONES
+            .iter()
+            .find(|(_, tz)| {
+                let tz_len = tz.iter().position(|&b| b == 0).unwrap_or(5);
+                tz[..tz_len]
+                    .iter()
+                    .map(|&b| b as c_char)
+                    .eq(timezone.iter().copied())
+            })
+            .map(|(code, _)| *code)
+    }
 }
 
+// SMPTE 309M timezone codes, the first match wins in both directions
+pub(crate) const SMPTE_TIMEZONES: [(u8, &[u8; 5]); 64] = [
+    (0x00, b"+0000"),
+    (0x01, b"-0100"),
+    (0x02, b"-0200"),
+    (0x03, b"-0300"),
+    (0x04, b"-0400"),
+    (0x05, b"-0500"),
+    (0x06, b"-0600"),
+    (0x07, b"-0700"),
+    (0x08, b"-0800"),
+    (0x09, b"-0900"),
+    (0x0a, b"-0030"),
+    (0x0b, b"-0130"),
+    (0x0c, b"-0230"),
+    (0x0d, b"-0330"),
+    (0x0e, b"-0430"),
+    (0x0f, b"-0530"),
+    (0x10, b"-1000"),
+    (0x11, b"-1100"),
+    (0x12, b"-1200"),
+    (0x13, b"+1300"),
+    (0x14, b"+1200"),
+    (0x15, b"+1100"),
+    (0x16, b"+1000"),
+    (0x17, b"+0900"),
+    (0x18, b"+0800"),
+    (0x19, b"+0700"),
+    (0x1a, b"-0630"),
+    (0x1b, b"-0730"),
+    (0x1c, b"-0830"),
+    (0x1d, b"-0930"),
+    (0x1e, b"-1030"),
+    (0x1f, b"-1130"),
+    (0x20, b"+0600"),
+    (0x21, b"+0500"),
+    (0x22, b"+0400"),
+    (0x23, b"+0300"),
+    (0x24, b"+0200"),
+    (0x25, b"+0100"),
+    (0x26, b"UDF\0\0"),
+    (0x27, b"UDF\0\0"),
+    (0x28, b"TP-03"),
+    (0x29, b"TP-02"),
+    (0x2a, b"+1130"),
+    (0x2b, b"+1030"),
+    (0x2c, b"+0930"),
+    (0x2d, b"+0830"),
+    (0x2e, b"+0730"),
+    (0x2f, b"+0630"),
+    (0x30, b"TP-01"),
+    (0x31, b"TP-00"),
+    (0x32, b"+1245"),
+    (0x33, b"UDF\0\0"),
+    (0x34, b"UDF\0\0"),
+    (0x35, b"UDF\0\0"),
+    (0x36, b"UDF\0\0"),
+    (0x37, b"UDF\0\0"),
+    (0x38, b"+XXXX"),
+    (0x39, b"UDF\0\0"),
+    (0x3a, b"+0530"),
+    (0x3b, b"+0430"),
+    (0x3c, b"+0330"),
+    (0x3d, b"+0230"),
+    (0x3e, b"+0130"),
+    (0x3f, b"+0030"),
+];
+
 #[allow(non_camel_case_types)]
 #[repr(u32)]
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LTCTVStandard {
     #[default]
     LTCTV_525_60 = 0, // 30fps
@@ -234,6 +356,7 @@ impl From<LTCTVStandard> for raw::LTC_TV_STANDARD {
 }
 
 impl LTCTVStandard {
+    #[cfg_attr(feature = "pure-rust", allow(dead_code))]
     pub(crate) fn to_raw(self) -> raw::LTC_TV_STANDARD {
         self.into()
     }
@@ -0,0 +1,538 @@
+use super::consts::LtcBgFlags;
+use super::export::LoggedTimecode;
+use super::frame::{LTCFrame, LTCFrameExt};
+use super::{LTCTVStandard, SMPTETimecode, Timezone};
+#[cfg(not(feature = "std"))]
+use crate::math::F64Ext;
+
+/// MIDI status byte of a quarter-frame message
+pub const MTC_QUARTER_FRAME: u8 = 0xf1;
+
+// F0 7F <device: all> 01 01 hh mm ss ff F7
+const FULL_FRAME_HEADER: [u8; 5] = [0xf0, 0x7f, 0x7f, 0x01, 0x01];
+const SYSEX_END: u8 = 0xf7;
+
+/// The frame rates MTC can express, with their rate codes
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum MtcRate {
+    Fps24 = 0,
+    #[default]
+    Fps25 = 1,
+    /// 29.97 drop frame
+    Fps30Drop = 2,
+    /// 30 or 29.97 non-drop
+    Fps30 = 3,
+}
+
+impl MtcRate {
+    pub fn from_code(code: u8) -> Self {
+        match code & 0x3 {
+            0 => MtcRate::Fps24,
+            1 => MtcRate::Fps25,
+            2 => MtcRate::Fps30Drop,
+            _ => MtcRate::Fps30,
+        }
+    }
+
+    pub fn from_standard(standard: LTCTVStandard, drop_frame: bool) -> Self {
+        match standard {
+            LTCTVStandard::LTCTV_625_50 => MtcRate::Fps25,
+            LTCTVStandard::LTCTV_FILM_24 => MtcRate::Fps24,
+            _ if drop_frame => MtcRate::Fps30Drop,
+            _ => MtcRate::Fps30,
+        }
+    }
+
+    /// `None` for rates MTC has no code for, e.g. 50 or 60 fps. 23.976 maps to 24.
+    pub fn from_fps(fps: f64, drop_frame: bool) -> Option<Self> {
+        match fps.round() as i32 {
+            24 => Some(MtcRate::Fps24),
+            25 => Some(MtcRate::Fps25),
+            30 if drop_frame => Some(MtcRate::Fps30Drop),
+            30 => Some(MtcRate::Fps30),
+            _ => None,
+        }
+    }
+
+    pub fn code(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn fps(&self) -> f64 {
+        match self {
+            MtcRate::Fps24 => 24.0,
+            MtcRate::Fps25 => 25.0,
+            MtcRate::Fps30Drop => 30000.0 / 1001.0,
+            MtcRate::Fps30 => 30.0,
+        }
+    }
+
+    pub fn standard(&self) -> LTCTVStandard {
+        match self {
+            MtcRate::Fps24 => LTCTVStandard::LTCTV_FILM_24,
+            MtcRate::Fps25 => LTCTVStandard::LTCTV_625_50,
+            _ => LTCTVStandard::LTCTV_525_60,
+        }
+    }
+}
+
+/// A time of day as carried by MTC
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MtcTimecode {
+    pub hours: u8,
+    pub minutes: u8,
+    pub seconds: u8,
+    pub frame: u8,
+    pub rate: MtcRate,
+}
+
+impl MtcTimecode {
+    /// The date and timezone are dropped, MTC has no room for them
+    pub fn from_timecode(timecode: &SMPTETimecode, rate: MtcRate) -> Self {
+        MtcTimecode {
+            hours: timecode.hours(),
+            minutes: timecode.minutes(),
+            seconds: timecode.seconds(),
+            frame: timecode.frame(),
+            rate,
+        }
+    }
+
+    pub fn to_timecode(&self) -> SMPTETimecode {
+        SMPTETimecode::new(
+            Timezone::default(),
+            0,
+            0,
+            0,
+            self.hours,
+            self.minutes,
+            self.seconds,
+            self.frame,
+        )
+    }
+
+    pub fn from_frame(frame: &LTCFrame, rate: MtcRate) -> Self {
+        Self::from_timecode(&frame.to_timecode(LtcBgFlags::default()), rate)
+    }
+
+    /// An LTC frame at this time, with the drop frame bit and parity for the rate
+    pub fn to_frame(&self, flags: LtcBgFlags) -> LTCFrame {
+        let standard = self.rate.standard();
+        let mut frame = LTCFrame::from_timecode(&self.to_timecode(), standard, flags);
+        frame.set_dfbit(self.rate == MtcRate::Fps30Drop);
+        frame.set_parity(standard);
+        frame
+    }
+
+    /// The timecode `frames` frames later (earlier when negative), wrapping at midnight
+    pub fn offset(&self, frames: i64) -> Self {
+        let logged = LoggedTimecode::from(*self);
+        let fps = self.rate.fps();
+        LoggedTimecode::from_frame_number(logged.frame_number(fps) + frames, fps, logged.drop_frame)
+            .with_rate(self.rate)
+    }
+
+    /// The 8 quarter-frame messages that transmit this time, piece 0 first
+    pub fn quarter_frames(&self) -> [MtcQuarterFrame; 8] {
+        let values = [
+            self.frame & 0xf,
+            (self.frame >> 4) & 0x1,
+            self.seconds & 0xf,
+            (self.seconds >> 4) & 0x3,
+            self.minutes & 0xf,
+            (self.minutes >> 4) & 0x3,
+            self.hours & 0xf,
+            ((self.hours >> 4) & 0x1) | (self.rate.code() << 1),
+        ];
+        core::array::from_fn(|piece| MtcQuarterFrame {
+            piece: piece as u8,
+            value: values[piece],
+        })
+    }
+
+    /// Full-frame SysEx, sent when locating rather than playing
+    pub fn full_frame(&self) -> [u8; 10] {
+        let mut message = [0; 10];
+        message[..5].copy_from_slice(&FULL_FRAME_HEADER);
+        message[5] = (self.rate.code() << 5) | (self.hours & 0x1f);
+        message[6] = self.minutes & 0x3f;
+        message[7] = self.seconds & 0x3f;
+        message[8] = self.frame & 0x1f;
+        message[9] = SYSEX_END;
+        message
+    }
+
+    pub fn from_full_frame(message: &[u8]) -> Option<Self> {
+        let [header @ .., hours, minutes, seconds, frame, SYSEX_END] = message else {
+            return None;
+        };
+        if header != FULL_FRAME_HEADER {
+            return None;
+        }
+        Some(MtcTimecode {
+            hours: hours & 0x1f,
+            minutes: minutes & 0x3f,
+            seconds: seconds & 0x3f,
+            frame: frame & 0x1f,
+            rate: MtcRate::from_code(hours >> 5),
+        })
+    }
+}
+
+impl From<MtcTimecode> for LoggedTimecode {
+    fn from(timecode: MtcTimecode) -> Self {
+        LoggedTimecode {
+            hours: timecode.hours,
+            minutes: timecode.minutes,
+            seconds: timecode.seconds,
+            frame: timecode.frame,
+            drop_frame: timecode.rate == MtcRate::Fps30Drop,
+        }
+    }
+}
+
+impl LoggedTimecode {
+    fn with_rate(self, rate: MtcRate) -> MtcTimecode {
+        MtcTimecode {
+            hours: self.hours,
+            minutes: self.minutes,
+            seconds: self.seconds,
+            frame: self.frame,
+            rate,
+        }
+    }
+}
+
+/// One MTC quarter-frame message: a piece number (0-7) and its 4 bit value
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MtcQuarterFrame {
+    pub piece: u8,
+    pub value: u8,
+}
+
+impl MtcQuarterFrame {
+    pub fn to_bytes(&self) -> [u8; 2] {
+        [
+            MTC_QUARTER_FRAME,
+            ((self.piece & 0x7) << 4) | (self.value & 0xf),
+        ]
+    }
+
+    pub fn from_bytes(message: &[u8]) -> Option<Self> {
+        match message {
+            [MTC_QUARTER_FRAME, data] if data & 0x80 == 0 => Some(MtcQuarterFrame {
+                piece: data >> 4,
+                value: data & 0xf,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// A quarter-frame message and the sample position it is due at
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimedQuarterFrame {
+    pub position: i64,
+    pub message: MtcQuarterFrame,
+}
+
+/// A time of day recovered from MTC and the sample position the frame starts at
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimedMtcTimecode {
+    pub position: i64,
+    pub timecode: MtcTimecode,
+}
+
+/// Turns decoded LTC frames into quarter-frame messages, four per frame, spread evenly over
+/// the samples each frame occupies.
+///
+/// A sequence of 8 messages spans two frames and carries the time of the frame it started in.
+/// Frames played in reverse send the pieces in descending order. A discontinuity in the
+/// timecode restarts the sequence.
+#[derive(Debug)]
+pub struct LtcToMtc {
+    rate: MtcRate,
+    // Time and direction of a sequence whose first half has been sent
+    pending: Option<(MtcTimecode, bool)>,
+}
+
+impl LtcToMtc {
+    pub fn new(rate: MtcRate) -> Self {
+        LtcToMtc {
+            rate,
+            pending: None,
+        }
+    }
+
+    pub fn push(&mut self, frame: &LTCFrameExt) -> [TimedQuarterFrame; 4] {
+        let timecode = MtcTimecode::from_frame(&frame.ltc(), self.rate);
+        self.push_timecode(
+            timecode,
+            frame.off_start(),
+            frame.off_end(),
+            frame.reverse(),
+        )
+    }
+
+    /// Like `push`, for a frame at `off_start..=off_end` that did not come from the decoder
+    pub fn push_timecode(
+        &mut self,
+        timecode: MtcTimecode,
+        off_start: i64,
+        off_end: i64,
+        reverse: bool,
+    ) -> [TimedQuarterFrame; 4] {
+        let step = if reverse { -1 } else { 1 };
+        let (start, second_half) = match self.pending.take() {
+            Some((start, was_reverse))
+                if was_reverse == reverse && start.offset(step) == timecode =>
+            {
+                (start, true)
+            }
+            _ => {
+                self.pending = Some((timecode, reverse));
+                (timecode, false)
+            }
+        };
+
+        let pieces = start.quarter_frames();
+        let len = off_end - off_start + 1;
+        core::array::from_fn(|i| {
+            // Forward 0-3 then 4-7, reverse 7-4 then 3-0
+            let index = if second_half { i + 4 } else { i };
+            let piece = if reverse { 7 - index } else { index };
+            TimedQuarterFrame {
+                position: off_start + len * i as i64 / 4,
+                message: pieces[piece],
+            }
+        })
+    }
+
+    /// Forgets the sequence in progress, e.g. after a locate
+    pub fn reset(&mut self) {
+        self.pending = None;
+    }
+}
+
+/// Recovers timecode from a stream of MTC messages with sample positions, one frame at a time.
+///
+/// Once a complete sequence of quarter frames has been received, a frame is reported at every
+/// frame boundary (pieces 0 and 4, or 7 and 3 in reverse). Full-frame messages are reported
+/// immediately. Out of order pieces drop the lock until the next complete sequence.
+#[derive(Debug, Default)]
+pub struct MtcToLtc {
+    values: [u8; 8],
+    last_piece: Option<u8>,
+    reverse: bool,
+    // Pieces received in order since the last gap
+    run: usize,
+    // The frame that starts at the next boundary
+    next: Option<MtcTimecode>,
+}
+
+impl MtcToLtc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds one MIDI message, a quarter frame (2 bytes) or full-frame SysEx (10 bytes)
+    pub fn push(&mut self, position: i64, message: &[u8]) -> Option<TimedMtcTimecode> {
+        if let Some(timecode) = MtcTimecode::from_full_frame(message) {
+            self.reset();
+            return Some(TimedMtcTimecode { position, timecode });
+        }
+        self.push_quarter_frame(position, MtcQuarterFrame::from_bytes(message)?)
+    }
+
+    pub fn push_quarter_frame(
+        &mut self,
+        position: i64,
+        message: MtcQuarterFrame,
+    ) -> Option<TimedMtcTimecode> {
+        let piece = message.piece & 0x7;
+        match self.last_piece {
+            Some(last) if piece == (last + 1) % 8 && (!self.reverse || self.run < 2) => {
+                self.reverse = false;
+                self.run += 1;
+            }
+            Some(last) if piece == (last + 7) % 8 && (self.reverse || self.run < 2) => {
+                self.reverse = true;
+                self.run += 1;
+            }
+            _ => {
+                self.run = 1;
+                self.next = None;
+            }
+        }
+        self.last_piece = Some(piece);
+        self.values[piece as usize] = message.value & 0xf;
+
+        let (boundaries, last) = if self.reverse {
+            ([7, 3], 0)
+        } else {
+            ([0, 4], 7)
+        };
+        let step = if self.reverse { -1 } else { 1 };
+
+        let mut out = None;
+        if boundaries.contains(&piece) {
+            if let Some(next) = self.next {
+                out = Some(TimedMtcTimecode {
+                    position,
+                    timecode: next,
+                });
+                self.next = Some(next.offset(step));
+            }
+        }
+        if piece == last && self.run >= 8 {
+            // The sequence started two frames before the frame that begins now
+            self.next = Some(self.assemble().offset(2 * step));
+        }
+        out
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn assemble(&self) -> MtcTimecode {
+        let v = &self.values;
+        MtcTimecode {
+            frame: v[0] | ((v[1] & 0x1) << 4),
+            seconds: v[2] | ((v[3] & 0x3) << 4),
+            minutes: v[4] | ((v[5] & 0x3) << 4),
+            hours: v[6] | ((v[7] & 0x1) << 4),
+            rate: MtcRate::from_code(v[7] >> 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decoder::{LTCDecoder, LTCDecoderConfig};
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+    use alloc::vec;
+
+    fn tc(hours: u8, minutes: u8, seconds: u8, frame: u8, rate: MtcRate) -> MtcTimecode {
+        MtcTimecode {
+            hours,
+            minutes,
+            seconds,
+            frame,
+            rate,
+        }
+    }
+
+    #[test]
+    fn test_messages() {
+        let timecode = tc(23, 59, 58, 29, MtcRate::Fps30Drop);
+        let pieces = timecode.quarter_frames();
+        assert_eq!(pieces[0].to_bytes(), [0xf1, 0x0d]);
+        assert_eq!(pieces[7].to_bytes(), [0xf1, 0x75]);
+        assert_eq!(MtcQuarterFrame::from_bytes(&[0xf1, 0x75]), Some(pieces[7]));
+
+        let sysex = timecode.full_frame();
+        assert_eq!(
+            sysex,
+            [0xf0, 0x7f, 0x7f, 0x01, 0x01, 0x57, 59, 58, 29, 0xf7]
+        );
+        assert_eq!(MtcTimecode::from_full_frame(&sysex), Some(timecode));
+        assert_eq!(MtcTimecode::from_full_frame(&sysex[..9]), None);
+
+        assert_eq!(
+            MtcRate::from_standard(LTCTVStandard::LTCTV_525_60, true),
+            MtcRate::Fps30Drop
+        );
+        assert_eq!(MtcRate::from_fps(23.976, false), Some(MtcRate::Fps24));
+        assert_eq!(MtcRate::from_fps(50.0, false), None);
+    }
+
+    #[test]
+    fn test_timecode_conversions() {
+        let timecode = tc(1, 2, 3, 4, MtcRate::Fps25);
+        let frame = timecode.to_frame(LtcBgFlags::default());
+        assert_eq!(MtcTimecode::from_frame(&frame, MtcRate::Fps25), timecode);
+        assert_eq!(frame.dfbit(), 0);
+
+        let timecode = tc(0, 0, 59, 29, MtcRate::Fps30Drop);
+        assert_eq!(timecode.offset(1), tc(0, 1, 0, 2, MtcRate::Fps30Drop));
+        assert_eq!(timecode.to_frame(LtcBgFlags::default()).dfbit(), 1);
+    }
+
+    #[test]
+    fn test_bridge_roundtrip() {
+        let rate = MtcRate::Fps25;
+        let mut to_mtc = LtcToMtc::new(rate);
+        let mut to_ltc = MtcToLtc::new();
+        let start = tc(10, 0, 0, 0, rate);
+
+        let mut recovered = vec![];
+        for i in 0..12 {
+            let timecode = start.offset(i);
+            let off_start = i * 1920;
+            for message in to_mtc.push_timecode(timecode, off_start, off_start + 1919, false) {
+                recovered.extend(to_ltc.push(message.position, &message.message.to_bytes()));
+            }
+        }
+        // Locked after the first sequence (2 frames), then one frame per frame
+        assert_eq!(recovered.len(), 10);
+        for timed in &recovered {
+            let i = timed.position / 1920;
+            assert_eq!(timed.position % 1920, 0);
+            assert_eq!(timed.timecode, start.offset(i));
+        }
+
+        // ...and in reverse
+        let mut recovered = vec![];
+        for i in 0..12 {
+            let timecode = start.offset(-i);
+            let off_start = i * 1920;
+            for message in to_mtc.push_timecode(timecode, off_start, off_start + 1919, true) {
+                recovered.extend(to_ltc.push(message.position, &message.message.to_bytes()));
+            }
+        }
+        assert!(recovered.len() >= 9);
+        for timed in &recovered {
+            assert_eq!(timed.timecode, start.offset(-(timed.position / 1920)));
+        }
+    }
+
+    #[test]
+    fn test_bridge_discontinuity() {
+        let rate = MtcRate::Fps25;
+        let mut to_mtc = LtcToMtc::new(rate);
+        let first = to_mtc.push_timecode(tc(0, 0, 0, 0, rate), 0, 1919, false);
+        let jumped = to_mtc.push_timecode(tc(1, 0, 0, 0, rate), 1920, 3839, false);
+        assert_eq!(first[0].message.piece, 0);
+        // The sequence restarts with the new time
+        assert_eq!(jumped[0].message.piece, 0);
+        assert_eq!(jumped[3].position, 1920 + 1440);
+    }
+
+    #[test]
+    fn test_decoded_ltc_to_mtc() {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        let mut to_mtc = LtcToMtc::new(MtcRate::Fps25);
+        let mut to_ltc = MtcToLtc::new();
+        let mut pos = 0;
+        let mut recovered = vec![];
+        for _ in 0..10 {
+            encoder.encode_frame();
+            let (buf, len) = encoder.get_buf_ref(true);
+            decoder.write(buf, pos);
+            pos += len as i64;
+            encoder.inc_timecode().unwrap();
+            while let Some(frame) = decoder.read() {
+                for message in to_mtc.push(&frame) {
+                    recovered.extend(to_ltc.push(message.position, &message.message.to_bytes()));
+                }
+            }
+        }
+        assert!(recovered.len() >= 5);
+        assert!(recovered
+            .windows(2)
+            .all(|w| w[1].timecode == w[0].timecode.offset(1)));
+    }
+}
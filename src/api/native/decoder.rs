@@ -0,0 +1,450 @@
+//! Port of libltc's `decoder.c`: biphase-mark edge detection with adaptive period
+//! tracking, and a sync-word search for frames played forwards and backwards.
+//!
+//! The arithmetic, including the integer truncations, follows the C code so both
+//! backends produce the same frames and offsets.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use crate::api::consts::SampleType;
+use crate::api::frame::LTCFrame;
+use crate::api::polarity;
+#[cfg(not(feature = "std"))]
+use crate::math::F64Ext;
+use crate::raw;
+
+const BIT_COUNT: usize = raw::LTC_FRAME_BIT_COUNT as usize;
+const SAMPLE_CENTER: i32 = 128;
+const SYNC_WORD_FORWARD: u16 = 0x3ffd;
+const SYNC_WORD_REVERSE: u16 = 0xbffc;
+// Size of the stack buffer the non-u8 write functions convert through
+const CONVERSION_BUF_SIZE: usize = 1024;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Decoder {
+    queue: Vec<raw::LTCFrameExt>,
+    // `LTCDecoder::read` takes `&self`
+    queue_read_off: Cell<usize>,
+    queue_write_off: usize,
+    // Frames pushed since creation, libltc has no such counter
+    frames_decoded: u64,
+
+    biphase_state: u8,
+    biphase_prev: u8,
+    snd_to_biphase_state: u8,
+    // Samples in the current period
+    snd_to_biphase_cnt: i32,
+    // Above this many samples a state change is a full bit period
+    snd_to_biphase_lmt: i32,
+    snd_to_biphase_period: f64,
+
+    snd_to_biphase_min: SampleType,
+    snd_to_biphase_max: SampleType,
+
+    decoder_sync_word: u16,
+    ltc_frame: [u8; BIT_COUNT / 8],
+    bit_cnt: usize,
+
+    frame_start_off: i64,
+    frame_start_prev: i64,
+
+    biphase_tics: [f32; BIT_COUNT],
+    biphase_tic: usize,
+}
+
+impl Decoder {
+    /// `None` for an empty queue
+    pub(crate) fn new(apv: i32, queue_len: i32) -> Option<Self> {
+        if queue_len <= 0 {
+            return None;
+        }
+        let snd_to_biphase_period = (apv / BIT_COUNT as i32) as f64;
+        Some(Decoder {
+            queue: vec![raw::LTCFrameExt::default(); queue_len as usize],
+            queue_read_off: Cell::new(0),
+            queue_write_off: 0,
+            frames_decoded: 0,
+            biphase_state: 1,
+            biphase_prev: 0,
+            snd_to_biphase_state: 0,
+            snd_to_biphase_cnt: 0,
+            snd_to_biphase_lmt: (snd_to_biphase_period * 3.0 / 4.0) as i32,
+            snd_to_biphase_period,
+            snd_to_biphase_min: SAMPLE_CENTER as SampleType,
+            snd_to_biphase_max: SAMPLE_CENTER as SampleType,
+            decoder_sync_word: 0,
+            ltc_frame: [0; BIT_COUNT / 8],
+            bit_cnt: 0,
+            frame_start_off: 0,
+            frame_start_prev: -1,
+            biphase_tics: [0.0; BIT_COUNT],
+            biphase_tic: 0,
+        })
+    }
+
+    pub(crate) fn write(&mut self, buf: &[SampleType], posinfo: i64) {
+        for (i, &sample) in buf.iter().enumerate() {
+            self.decode_sample(sample, i as i64, posinfo);
+        }
+    }
+
+    // The C library converts in chunks and passes the chunk start as position, which
+    // shows in the offsets of the first frame. Do the same.
+    fn write_converted<T: Copy>(
+        &mut self,
+        buf: &[T],
+        posinfo: i64,
+        convert: impl Fn(T) -> SampleType,
+    ) {
+        let mut tmp = [0; CONVERSION_BUF_SIZE];
+        for (n, chunk) in buf.chunks(CONVERSION_BUF_SIZE).enumerate() {
+            for (out, &sample) in tmp.iter_mut().zip(chunk) {
+                *out = convert(sample);
+            }
+            self.write(
+                &tmp[..chunk.len()],
+                posinfo + (n * CONVERSION_BUF_SIZE) as i64,
+            );
+        }
+    }
+
+    pub(crate) fn write_double(&mut self, buf: &[f64], posinfo: i64) {
+        self.write_converted(buf, posinfo, polarity::f64_to_sample);
+    }
+
+    pub(crate) fn write_float(&mut self, buf: &[f32], posinfo: i64) {
+        self.write_converted(buf, posinfo, |x| polarity::f64_to_sample(x as f64));
+    }
+
+    pub(crate) fn write_i16(&mut self, buf: &[i16], posinfo: i64) {
+        self.write_converted(buf, posinfo, polarity::i16_to_sample);
+    }
+
+    pub(crate) fn write_u16(&mut self, buf: &[u16], posinfo: i64) {
+        self.write_converted(buf, posinfo, polarity::u16_to_sample);
+    }
+
+    pub(crate) fn read(&self) -> Option<raw::LTCFrameExt> {
+        let read_off = self.queue_read_off.get();
+        if read_off == self.queue_write_off {
+            return None;
+        }
+        self.queue_read_off.set((read_off + 1) % self.queue.len());
+        Some(self.queue[read_off])
+    }
+
+    pub(crate) fn queue_flush(&mut self) {
+        self.queue_read_off.set(self.queue_write_off);
+    }
+
+    pub(crate) fn frames_decoded(&self) -> u64 {
+        self.frames_decoded
+    }
+
+    pub(crate) fn queue_length(&self) -> i32 {
+        let len = self.queue.len();
+        ((self.queue_write_off + len - self.queue_read_off.get()) % len) as i32
+    }
+
+    fn decode_sample(&mut self, sample: SampleType, offset: i64, posinfo: i64) {
+        // Track the minimum and maximum, decaying towards the center
+        let min = SAMPLE_CENTER - ((SAMPLE_CENTER - self.snd_to_biphase_min as i32) * 15) / 16;
+        let max = SAMPLE_CENTER + ((self.snd_to_biphase_max as i32 - SAMPLE_CENTER) * 15) / 16;
+        self.snd_to_biphase_min = (min as SampleType).min(sample);
+        self.snd_to_biphase_max = (max as SampleType).max(sample);
+
+        // Thresholds for the hi/lo state
+        let min_threshold =
+            SAMPLE_CENTER - ((SAMPLE_CENTER - self.snd_to_biphase_min as i32) * 8) / 16;
+        let max_threshold =
+            SAMPLE_CENTER + ((self.snd_to_biphase_max as i32 - SAMPLE_CENTER) * 8) / 16;
+
+        let sample = sample as i32;
+        let edge = if self.snd_to_biphase_state != 0 {
+            sample > max_threshold
+        } else {
+            sample < min_threshold
+        };
+        if edge {
+            if self.snd_to_biphase_cnt > self.snd_to_biphase_lmt {
+                // A single state change within a bit period decodes to a 0
+                self.biphase_decode(offset, posinfo);
+                self.biphase_decode(offset, posinfo);
+            } else {
+                // Half a period, together with the next or previous change this is a 1
+                self.snd_to_biphase_cnt *= 2;
+                self.biphase_decode(offset, posinfo);
+            }
+
+            if self.snd_to_biphase_cnt as f64 > self.snd_to_biphase_period * 4.0 {
+                // Long silence in between, reset the parser and don't track the phase
+                self.bit_cnt = 0;
+            } else {
+                // Track speed variations, the count is the length of the current period
+                self.snd_to_biphase_period =
+                    (self.snd_to_biphase_period * 3.0 + self.snd_to_biphase_cnt as f64) / 4.0;
+                self.snd_to_biphase_lmt = (self.snd_to_biphase_period * 3.0 / 4.0) as i32;
+            }
+
+            self.snd_to_biphase_cnt = 0;
+            self.snd_to_biphase_state = (self.snd_to_biphase_state == 0) as u8;
+        }
+        self.snd_to_biphase_cnt += 1;
+    }
+
+    fn biphase_decode(&mut self, offset: i64, mut pos: i64) {
+        self.biphase_tics[self.biphase_tic] = self.snd_to_biphase_period as f32;
+        self.biphase_tic = (self.biphase_tic + 1) % BIT_COUNT;
+        if self.snd_to_biphase_cnt as f64 <= 2.0 * self.snd_to_biphase_period {
+            pos =
+                (pos as f64 - (self.snd_to_biphase_period - self.snd_to_biphase_cnt as f64)) as i64;
+        }
+
+        if self.snd_to_biphase_state == self.biphase_prev {
+            self.biphase_state = 1;
+            self.parse_bit(false, offset, pos);
+        } else {
+            self.biphase_state = 1 - self.biphase_state;
+            if self.biphase_state == 1 {
+                self.parse_bit(true, offset, pos);
+            }
+        }
+        self.biphase_prev = self.snd_to_biphase_state;
+    }
+
+    fn parse_bit(&mut self, bit: bool, offset: i64, posinfo: i64) {
+        if self.bit_cnt == 0 {
+            self.ltc_frame = [0; BIT_COUNT / 8];
+            self.frame_start_off = if self.frame_start_prev < 0 {
+                (posinfo as f64 - self.snd_to_biphase_period) as i64
+            } else {
+                self.frame_start_prev
+            };
+        }
+        self.frame_start_prev = offset + posinfo;
+
+        if self.bit_cnt >= BIT_COUNT {
+            // Drop the oldest bit
+            for k in 0..self.ltc_frame.len() {
+                let next = self.ltc_frame.get(k + 1).map_or(0, |b| b & 0x01);
+                self.ltc_frame[k] = (self.ltc_frame[k] >> 1) | (next << 7);
+            }
+            self.frame_start_off =
+                (self.frame_start_off as f64 + self.snd_to_biphase_period.ceil()) as i64;
+            self.bit_cnt -= 1;
+        }
+
+        self.decoder_sync_word <<= 1;
+        if bit {
+            self.decoder_sync_word |= 0x0001;
+            if self.bit_cnt < BIT_COUNT {
+                self.ltc_frame[self.bit_cnt >> 3] |= 1 << (self.bit_cnt & 0x7);
+            }
+        }
+        self.bit_cnt += 1;
+
+        let end = posinfo + offset - 1;
+        if self.decoder_sync_word == SYNC_WORD_FORWARD {
+            if self.bit_cnt == BIT_COUNT {
+                self.push_frame(self.frame_start_off, end, 0);
+            }
+            self.bit_cnt = 0;
+        }
+
+        if self.decoder_sync_word == SYNC_WORD_REVERSE {
+            if self.bit_cnt == BIT_COUNT {
+                for byte in self.ltc_frame.iter_mut() {
+                    *byte = byte.reverse_bits();
+                }
+                // The sync word stays in place
+                self.ltc_frame[..BIT_COUNT / 8 - 2].reverse();
+
+                // The sync word detected belongs to the next frame, correct the offsets
+                let correction = 16.0 * self.snd_to_biphase_period;
+                self.push_frame(
+                    (self.frame_start_off as f64 - correction) as i64,
+                    (end as f64 - correction) as i64,
+                    (BIT_COUNT as f64 * self.snd_to_biphase_period) as i32,
+                );
+            }
+            self.bit_cnt = 0;
+        }
+    }
+
+    fn push_frame(&mut self, off_start: i64, off_end: i64, reverse: i32) {
+        let frame = &mut self.queue[self.queue_write_off];
+        frame.ltc = LTCFrame::from_bytes(self.ltc_frame).inner_raw;
+        for (bc, tic) in frame.biphase_tics.iter_mut().enumerate() {
+            *tic = self.biphase_tics[(self.biphase_tic + bc) % BIT_COUNT];
+        }
+        frame.off_start = off_start;
+        frame.off_end = off_end;
+        frame.reverse = reverse;
+        frame.sample_min = self.snd_to_biphase_min;
+        frame.sample_max = self.snd_to_biphase_max;
+        frame.volume = if self.snd_to_biphase_max <= self.snd_to_biphase_min {
+            f64::NEG_INFINITY
+        } else {
+            20.0 * ((self.snd_to_biphase_max - self.snd_to_biphase_min) as f64 / 255.0).log10()
+        };
+
+        self.queue_write_off = (self.queue_write_off + 1) % self.queue.len();
+        self.frames_decoded += 1;
+    }
+}
+
+#[cfg(all(test, libltc))]
+mod tests {
+    use super::*;
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+    use crate::api::LTCTVStandard;
+
+    // The C decoder fed the same input, to compare against
+    struct CDecoder(*mut raw::LTCDecoder);
+
+    impl CDecoder {
+        fn new(apv: i32, queue_len: i32) -> Self {
+            CDecoder(unsafe { raw::ltc_decoder_create(apv, queue_len) })
+        }
+
+        fn read(&mut self) -> Option<raw::LTCFrameExt> {
+            let mut frame = raw::LTCFrameExt::default();
+            (unsafe { raw::ltc_decoder_read(self.0, &mut frame) } != 0).then_some(frame)
+        }
+    }
+
+    impl Drop for CDecoder {
+        fn drop(&mut self) {
+            unsafe { raw::ltc_decoder_free(self.0) };
+        }
+    }
+
+    fn assert_same_frames(native: &Decoder, c: &mut CDecoder) -> usize {
+        let mut count = 0;
+        loop {
+            let (a, b) = (native.read(), c.read());
+            let (Some(a), Some(b)) = (a, b) else {
+                assert!(a.is_none() && b.is_none(), "frame count differs");
+                return count;
+            };
+            assert_eq!(
+                LTCFrame::from(a.ltc).to_bytes(),
+                LTCFrame::from(b.ltc).to_bytes()
+            );
+            assert_eq!(
+                (a.off_start, a.off_end, a.reverse),
+                (b.off_start, b.off_end, b.reverse)
+            );
+            assert_eq!(a.biphase_tics, b.biphase_tics);
+            assert_eq!(
+                (a.sample_min, a.sample_max, a.volume.to_bits()),
+                (b.sample_min, b.sample_max, b.volume.to_bits())
+            );
+            count += 1;
+        }
+    }
+
+    // A few seconds of LTC: forwards, a silent gap, backwards and at varying speed
+    fn signal(fps: f64, standard: LTCTVStandard) -> Vec<SampleType> {
+        let config = LTCEncoderConfig {
+            sample_rate: 44_100.0,
+            fps,
+            standard,
+            ..Default::default()
+        };
+        let mut encoder = LTCEncoder::try_new(&config).unwrap();
+        encoder.set_volume(-12.0).unwrap();
+        let mut samples = vec![];
+        for _ in 0..30 {
+            encoder.encode_frame();
+            samples.extend_from_slice(encoder.get_buf_ref(true).0);
+            encoder.inc_timecode().unwrap();
+        }
+        samples.resize(samples.len() + 5000, 128);
+        for _ in 0..30 {
+            encoder.dec_timecode().unwrap();
+            encoder.encode_reversed_frame();
+            samples.extend_from_slice(encoder.get_buf_ref(true).0);
+        }
+        for (n, speed) in [1.0, 1.1, 1.25, 0.9, 0.8]
+            .iter()
+            .cycle()
+            .take(20)
+            .enumerate()
+        {
+            for byte in 0..10 {
+                encoder.encode_byte(byte, *speed).unwrap();
+            }
+            samples.extend_from_slice(encoder.get_buf_ref(true).0);
+            if n % 2 == 0 {
+                encoder.inc_timecode().unwrap();
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn test_matches_c_decoder() {
+        for (fps, standard) in [
+            (25.0, LTCTVStandard::LTCTV_625_50),
+            (30000.0 / 1001.0, LTCTVStandard::LTCTV_525_60),
+            (24.0, LTCTVStandard::LTCTV_FILM_24),
+        ] {
+            let samples = signal(fps, standard);
+            let apv = (44_100.0 / fps) as i32;
+            let mut native = Decoder::new(apv, 256).unwrap();
+            let mut c = CDecoder::new(apv, 256);
+            // Odd buffer sizes and a non-zero start position
+            let mut pos = 1000;
+            for chunk in samples.chunks(777) {
+                native.write(chunk, pos);
+                unsafe {
+                    raw::ltc_decoder_write(c.0, chunk.as_ptr() as *mut _, chunk.len(), pos);
+                }
+                pos += chunk.len() as i64;
+                assert_eq!(native.queue_length(), unsafe {
+                    raw::ltc_decoder_queue_length(c.0)
+                });
+            }
+            assert!(assert_same_frames(&native, &mut c) > 60);
+        }
+    }
+
+    #[test]
+    fn test_matches_c_decoder_converted() {
+        let samples = signal(25.0, LTCTVStandard::LTCTV_625_50);
+        let floats: Vec<f32> = samples
+            .iter()
+            .map(|&s| (s as f32 - 128.0) / 127.0)
+            .collect();
+        let shorts: Vec<i16> = samples.iter().map(|&s| (s as i16 - 128) << 8).collect();
+
+        let mut native = Decoder::new(1764, 256).unwrap();
+        let mut c = CDecoder::new(1764, 256);
+        native.write_float(&floats, 0);
+        unsafe { raw::ltc_decoder_write_float(c.0, floats.as_ptr() as *mut _, floats.len(), 0) };
+        assert!(assert_same_frames(&native, &mut c) > 60);
+
+        let mut native = Decoder::new(1764, 256).unwrap();
+        let mut c = CDecoder::new(1764, 256);
+        native.write_i16(&shorts, 0);
+        unsafe { raw::ltc_decoder_write_s16(c.0, shorts.as_ptr() as *mut _, shorts.len(), 0) };
+        assert!(assert_same_frames(&native, &mut c) > 60);
+    }
+
+    #[test]
+    fn test_queue_overflow() {
+        let samples = signal(25.0, LTCTVStandard::LTCTV_625_50);
+        let mut native = Decoder::new(1764, 8).unwrap();
+        let mut c = CDecoder::new(1764, 8);
+        native.write(&samples, 0);
+        unsafe { raw::ltc_decoder_write(c.0, samples.as_ptr() as *mut _, samples.len(), 0) };
+        assert_eq!(native.queue_length(), unsafe {
+            raw::ltc_decoder_queue_length(c.0)
+        });
+        assert_same_frames(&native, &mut c);
+        assert!(Decoder::new(1764, 0).is_none());
+    }
+}
@@ -0,0 +1,443 @@
+//! Port of libltc's `encoder.c` and the encoder part of `ltc.c`: biphase-mark modulation
+//! with the rise-time low-pass filter and variable speed.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::Cell;
+
+use super::timecode;
+use crate::api::consts::{LtcBgFlags, LtcBgFlagsKind, SampleType};
+use crate::api::LTCTVStandard;
+#[cfg(not(feature = "std"))]
+use crate::math::F64Ext;
+use crate::raw;
+
+const SAMPLE_CENTER: i32 = 128;
+// libltc's default rise time in us
+const DEFAULT_RISE_TIME: f64 = 40.0;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Encoder {
+    f: raw::LTCFrame,
+
+    sample_rate: f64,
+    fps: f64,
+    standard: LTCTVStandard,
+    flags: LtcBgFlags,
+
+    samples_per_clock: f64,
+    samples_per_clock_2: f64,
+    sample_remainder: f64,
+    filter_const: f64,
+
+    state: bool,
+    enc_lo: SampleType,
+    enc_hi: SampleType,
+
+    buf: Vec<SampleType>,
+    // `LTCEncoder::get_buf_ref` flushes through `&self`
+    offset: Cell<usize>,
+}
+
+fn buffer_size(sample_rate: f64, fps: f64) -> usize {
+    1 + (sample_rate / fps).ceil() as usize
+}
+
+impl Encoder {
+    pub(crate) fn new(
+        sample_rate: f64,
+        fps: f64,
+        standard: LTCTVStandard,
+        flags: LtcBgFlags,
+    ) -> Option<Self> {
+        if sample_rate < 1.0 {
+            return None;
+        }
+        let mut f = raw::LTCFrame::default();
+        timecode::frame_reset(&mut f);
+        let mut encoder = Encoder {
+            f,
+            sample_rate,
+            fps,
+            standard,
+            flags,
+            samples_per_clock: 0.0,
+            samples_per_clock_2: 0.0,
+            sample_remainder: 0.0,
+            filter_const: 0.0,
+            state: false,
+            // -3 dBFS
+            enc_lo: 38,
+            enc_hi: 218,
+            buf: vec![0; buffer_size(sample_rate, fps)],
+            offset: Cell::new(0),
+        };
+        encoder.reinit(sample_rate, fps, standard, flags).ok()?;
+        Some(encoder)
+    }
+
+    pub(crate) fn reinit(
+        &mut self,
+        sample_rate: f64,
+        fps: f64,
+        standard: LTCTVStandard,
+        flags: LtcBgFlags,
+    ) -> Result<(), ()> {
+        if sample_rate < 1.0 || buffer_size(sample_rate, fps) > self.buf.len() {
+            return Err(());
+        }
+
+        self.state = false;
+        self.offset.set(0);
+        self.sample_rate = sample_rate;
+        self.set_filter(DEFAULT_RISE_TIME);
+        self.fps = fps;
+        self.flags = flags;
+        self.standard = standard;
+        self.samples_per_clock = sample_rate / (fps * 80.0);
+        self.samples_per_clock_2 = self.samples_per_clock / 2.0;
+        self.sample_remainder = 0.5;
+
+        if !flags.contains(LtcBgFlagsKind::LTC_BGF_DONT_TOUCH) {
+            self.f.set_col_frame(0);
+            self.f
+                .set_binary_group_flag_bit1(flags.contains(LtcBgFlagsKind::LTC_TC_CLOCK) as u32);
+            if standard == LTCTVStandard::LTCTV_625_50 {
+                // BGF0 and BGF2 at 25 fps
+                self.f.set_biphase_mark_phase_correction(0);
+                self.f.set_binary_group_flag_bit0(0);
+            } else {
+                self.f.set_binary_group_flag_bit0(0);
+                self.f.set_binary_group_flag_bit2(0);
+            }
+        }
+        if !flags.contains(LtcBgFlagsKind::LTC_NO_PARITY) {
+            timecode::frame_set_parity(&mut self.f, standard);
+        }
+
+        self.f
+            .set_dfbit(((fps * 100.0).round_ties_even() as i32 == 2997) as u32);
+        Ok(())
+    }
+
+    pub(crate) fn reset(&mut self) {
+        self.offset.set(0);
+    }
+
+    pub(crate) fn set_buffersize(&mut self, sample_rate: f64, fps: f64) {
+        self.offset.set(0);
+        self.buf = vec![0; buffer_size(sample_rate, fps)];
+    }
+
+    pub(crate) fn buffersize(&self) -> usize {
+        self.buf.len()
+    }
+
+    pub(crate) fn buffer_flush(&mut self) {
+        self.offset.set(0);
+    }
+
+    /// The samples encoded since the last flush
+    pub(crate) fn buffer(&self, flush: bool) -> &[SampleType] {
+        let len = self.offset.get();
+        if flush {
+            self.offset.set(0);
+        }
+        &self.buf[..len]
+    }
+
+    pub(crate) fn buffer_mut(&mut self, flush: bool) -> &mut [SampleType] {
+        let len = self.offset.get();
+        if flush {
+            self.offset.set(0);
+        }
+        &mut self.buf[..len]
+    }
+
+    pub(crate) fn copy_buffer(&self, out: &mut [SampleType]) -> usize {
+        let buf = self.buffer(true);
+        out[..buf.len()].copy_from_slice(buf);
+        buf.len()
+    }
+
+    pub(crate) fn volume(&self) -> f64 {
+        20.0 * ((self.enc_hi as i32 - SAMPLE_CENTER) as f64 / 127.0).log10()
+    }
+
+    pub(crate) fn set_volume(&mut self, dbfs: f64) -> Result<(), ()> {
+        if dbfs > 0.0 {
+            return Err(());
+        }
+        let pp = (127.0 * 10f64.powf(dbfs / 20.0)).round_ties_even();
+        if !(1.0..=127.0).contains(&pp) {
+            return Err(());
+        }
+        self.enc_lo = (SAMPLE_CENTER - pp as i32) as SampleType;
+        self.enc_hi = (SAMPLE_CENTER + pp as i32) as SampleType;
+        Ok(())
+    }
+
+    pub(crate) fn filter(&self) -> f64 {
+        if self.filter_const <= 0.0 {
+            return 0.0;
+        }
+        -2_000_000.0 * 1f64.exp() / (self.sample_rate * (1.0 - self.filter_const).ln())
+    }
+
+    pub(crate) fn set_filter(&mut self, rise_time: f64) {
+        // The rise time is from 10% to 90%, each transition starts at 50%
+        self.filter_const = if rise_time <= 0.0 {
+            0.0
+        } else {
+            1.0 - (-1.0 / (self.sample_rate * rise_time / 2_000_000.0 / 1f64.exp())).exp()
+        };
+    }
+
+    // The level of the last edge and the fraction of a sample the next one is delayed by
+    pub(crate) fn phase(&self) -> (bool, f64) {
+        (self.state, self.sample_remainder)
+    }
+
+    pub(crate) fn set_phase(&mut self, state: bool, sample_remainder: f64) {
+        self.state = state;
+        self.sample_remainder = sample_remainder;
+    }
+
+    pub(crate) fn frame(&self) -> raw::LTCFrame {
+        self.f
+    }
+
+    pub(crate) fn set_frame(&mut self, frame: &raw::LTCFrame) {
+        self.f = *frame;
+    }
+
+    pub(crate) fn timecode(&self, stime: &mut raw::SMPTETimecode) {
+        timecode::frame_to_time(stime, &self.f, self.flags);
+    }
+
+    pub(crate) fn set_timecode(&mut self, stime: &raw::SMPTETimecode) {
+        timecode::time_to_frame(&mut self.f, stime, self.standard, self.flags);
+    }
+
+    pub(crate) fn set_user_bits(&mut self, data: u32) {
+        self.f.set_user1(data & 0xf);
+        self.f.set_user2((data >> 4) & 0xf);
+        self.f.set_user3((data >> 8) & 0xf);
+        self.f.set_user4((data >> 12) & 0xf);
+        self.f.set_user5((data >> 16) & 0xf);
+        self.f.set_user6((data >> 20) & 0xf);
+        self.f.set_user7((data >> 24) & 0xf);
+        self.f.set_user8((data >> 28) & 0xf);
+    }
+
+    pub(crate) fn inc_timecode(&mut self) -> i32 {
+        let fps = self.fps.round_ties_even() as i32;
+        timecode::frame_increment(&mut self.f, fps, self.standard, self.flags)
+    }
+
+    pub(crate) fn dec_timecode(&mut self) -> i32 {
+        let fps = self.fps.round_ties_even() as i32;
+        timecode::frame_decrement(&mut self.f, fps, self.standard, self.flags)
+    }
+
+    // Appends `n` samples of a transition towards the current state, `Err` if they don't fit
+    fn add_values(&mut self, n: usize) -> Result<(), ()> {
+        let offset = self.offset.get();
+        if offset + n >= self.buf.len() {
+            return Err(());
+        }
+        let target = if self.state { self.enc_hi } else { self.enc_lo };
+        let wave = &mut self.buf[offset..offset + n];
+        let tcf = self.filter_const;
+        if tcf > 0.0 {
+            // Symmetric rise from the center, truncated to a sample on every step like in C
+            let mut val = SAMPLE_CENTER as SampleType;
+            for i in 0..(n + 1) >> 1 {
+                val = (val as f64 + tcf * (target as i32 - val as i32) as f64) as SampleType;
+                wave[i] = val;
+                wave[n - i - 1] = val;
+            }
+        } else {
+            wave.fill(target);
+        }
+        self.offset.set(offset + n);
+        Ok(())
+    }
+
+    // The next transition after `samples`, carrying the fraction over
+    fn transition(&mut self, samples: f64) -> Result<(), ()> {
+        let n = (samples + self.sample_remainder) as i32;
+        self.sample_remainder = samples + self.sample_remainder - n as f64;
+        self.state = !self.state;
+        self.add_values(n.max(0) as usize)
+    }
+
+    pub(crate) fn encode_byte(&mut self, byte: i32, speed: f64) -> Result<(), ()> {
+        if !(0..=9).contains(&byte) || speed == 0.0 {
+            return Err(());
+        }
+
+        let c = self.f._bitfield_1.get(byte as usize * 8, 8) as u8;
+        let spc = self.samples_per_clock * speed.abs();
+        let sph = self.samples_per_clock_2 * speed.abs();
+        let mut result = Ok(());
+        for bit in 0..8 {
+            // Played backwards, the bits go out MSB first
+            let b = if speed < 0.0 { 0x80 >> bit } else { 1 << bit };
+            if c & b == 0 {
+                result = result.and(self.transition(spc));
+            } else {
+                result = result.and(self.transition(sph));
+                result = result.and(self.transition(sph));
+            }
+        }
+        result
+    }
+
+    pub(crate) fn end_encode(&mut self) -> Result<(), ()> {
+        self.transition(self.samples_per_clock_2)
+    }
+
+    pub(crate) fn encode_frame(&mut self) {
+        for byte in 0..10 {
+            let _ = self.encode_byte(byte, 1.0);
+        }
+    }
+
+    pub(crate) fn encode_reversed_frame(&mut self) {
+        for byte in (0..10).rev() {
+            let _ = self.encode_byte(byte, -1.0);
+        }
+    }
+}
+
+#[cfg(all(test, libltc))]
+mod tests {
+    use super::*;
+    use crate::api::frame::LTCFrame;
+
+    // The C encoder with the same calls applied, to compare against
+    struct CEncoder(*mut raw::LTCEncoder);
+
+    impl CEncoder {
+        fn new(sample_rate: f64, fps: f64, standard: LTCTVStandard, flags: LtcBgFlags) -> Self {
+            CEncoder(unsafe {
+                raw::ltc_encoder_create(sample_rate, fps, standard.to_raw(), flags.into())
+            })
+        }
+
+        fn buffer(&self) -> Vec<SampleType> {
+            let mut ptr = core::ptr::null_mut();
+            let len = unsafe { raw::ltc_encoder_get_bufferptr(self.0, &mut ptr, 1) };
+            unsafe { core::slice::from_raw_parts(ptr, len as usize) }.to_vec()
+        }
+
+        fn frame(&self) -> raw::LTCFrame {
+            let mut frame = raw::LTCFrame::default();
+            unsafe { raw::ltc_encoder_get_frame(self.0, &mut frame) };
+            frame
+        }
+    }
+
+    impl Drop for CEncoder {
+        fn drop(&mut self) {
+            unsafe { raw::ltc_encoder_free(self.0) };
+        }
+    }
+
+    fn assert_close(native: &[SampleType], c: &[SampleType], tolerance: i32) {
+        assert_eq!(native.len(), c.len());
+        for (i, (&a, &b)) in native.iter().zip(c).enumerate() {
+            assert!(
+                (a as i32 - b as i32).abs() <= tolerance,
+                "sample {i}: {a} != {b}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches_c_encoder() {
+        let configs = [
+            (48_000.0, 25.0, LTCTVStandard::LTCTV_625_50),
+            (44_100.0, 30000.0 / 1001.0, LTCTVStandard::LTCTV_525_60),
+            (48_000.0, 24.0, LTCTVStandard::LTCTV_FILM_24),
+            (96_000.0, 30.0, LTCTVStandard::LTCTV_1125_60),
+        ];
+        for (sample_rate, fps, standard) in configs {
+            for flags in [0u32, 1, 2, 3, 4, 8] {
+                let flags = LtcBgFlags::from(flags);
+                // Without the filter the output is bit-exact, with it within one step
+                for (rise_time, tolerance) in [(0.0, 0), (40.0, 1), (25.0, 1)] {
+                    let mut native = Encoder::new(sample_rate, fps, standard, flags).unwrap();
+                    let c = CEncoder::new(sample_rate, fps, standard, flags);
+                    assert_eq!(
+                        LTCFrame::from(native.frame()).to_bytes(),
+                        LTCFrame::from(c.frame()).to_bytes()
+                    );
+                    native.set_filter(rise_time);
+                    native.set_volume(-6.0).unwrap();
+                    native.set_user_bits(0x1234_5678);
+                    unsafe {
+                        raw::ltc_encoder_set_filter(c.0, rise_time);
+                        raw::ltc_encoder_set_volume(c.0, -6.0);
+                        raw::ltc_encoder_set_user_bits(c.0, 0x1234_5678);
+                    }
+                    assert!(
+                        (native.volume() - unsafe { raw::ltc_encoder_get_volume(c.0) }).abs()
+                            < 1e-9
+                    );
+
+                    for n in 0..12 {
+                        match n % 4 {
+                            0 | 1 => {
+                                native.encode_frame();
+                                unsafe { raw::ltc_encoder_encode_frame(c.0) };
+                            }
+                            2 => {
+                                native.encode_reversed_frame();
+                                unsafe { raw::ltc_encoder_encode_reversed_frame(c.0) };
+                            }
+                            _ => {
+                                for byte in 0..10 {
+                                    let speed = [1.5, -0.75, 0.5][byte as usize % 3];
+                                    let a = native.encode_byte(byte, speed);
+                                    let b =
+                                        unsafe { raw::ltc_encoder_encode_byte(c.0, byte, speed) };
+                                    assert_eq!(a.is_ok(), b == 0);
+                                }
+                            }
+                        }
+                        assert_close(native.buffer(true), &c.buffer(), tolerance);
+                        assert_eq!(native.inc_timecode(), unsafe {
+                            raw::ltc_encoder_inc_timecode(c.0)
+                        });
+                        assert_eq!(
+                            LTCFrame::from(native.frame()).to_bytes(),
+                            LTCFrame::from(c.frame()).to_bytes()
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_buffer_overflow_matches_c() {
+        let flags = LtcBgFlags::default();
+        let mut native = Encoder::new(48_000.0, 25.0, LTCTVStandard::LTCTV_625_50, flags).unwrap();
+        let c = CEncoder::new(48_000.0, 25.0, LTCTVStandard::LTCTV_625_50, flags);
+        for byte in 0..10 {
+            for _ in 0..2 {
+                let a = native.encode_byte(byte, 1.0);
+                let b = unsafe { raw::ltc_encoder_encode_byte(c.0, byte, 1.0) };
+                assert_eq!(a.is_ok(), b == 0);
+            }
+        }
+        assert_eq!(native.buffer(true), &c.buffer()[..]);
+        assert!(native.encode_byte(10, 1.0).is_err());
+        assert!(native.encode_byte(0, 0.0).is_err());
+        assert_eq!(native.buffersize(), unsafe {
+            raw::ltc_encoder_get_buffersize(c.0)
+        });
+    }
+}
@@ -0,0 +1,451 @@
+//! Golden vectors for the native decoder and encoder, in `golden/`.
+//!
+//! The ignored `capture` test rewrites every file with C libltc 1.3.2: the decoder input with
+//! its encoder and the expected outputs with its encoder and decoder. It needs libltc and std.
+//! The `golden` CI job runs it and fails if any file changes, the other tests here check the
+//! native port against the files without libltc.
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use core::ffi::c_char;
+
+use super::decoder::Decoder;
+use super::encoder::Encoder;
+use crate::api::consts::{LtcBgFlags, SampleType};
+use crate::api::frame::LTCFrame;
+use crate::api::LTCTVStandard;
+use crate::raw;
+
+// Chunk size the input is written with, frames are read after each chunk
+const DECODER_CHUNK: usize = 1024;
+const DECODER_APV: i32 = 1920;
+const DECODER_QUEUE: i32 = 32;
+
+// Unsigned 8 bit, 48 kHz, 25 fps: a frame for each speed from 10:00:00:00 with user bits at
+// -12 dBFS, between silence. Frames with a negative speed are encoded in reverse, after
+// stepping the timecode back.
+const DECODER_SILENCE: usize = 1000;
+const DECODER_SPEEDS: [f64; 16] = [
+    1.0, 1.0, 1.0, 1.0, 0.9, 1.1, 1.25, 0.8, 0.8, -1.0, -1.0, -1.2, -0.85, -0.85, 1.0, 1.0,
+];
+const DECODER_USER_BITS: u32 = 0x1234_5678;
+const DECODER_INPUT: &[u8] = include_bytes!("golden/decoder_input.u8");
+const DECODER_FRAMES: &str = include_str!("golden/decoder_frames.txt");
+
+struct EncoderCase {
+    // `capture` writes the expected samples to `golden/encoder_<name>.u8`
+    name: &'static str,
+    expected: &'static [u8],
+    sample_rate: f64,
+    fps: f64,
+    standard: LTCTVStandard,
+    flags: u32,
+    rise_time: f64,
+    volume: f64,
+    // Date, timezone and time of the first frame
+    date: [u8; 3],
+    timezone: &'static [u8; 6],
+    time: [u8; 4],
+    drop_frame: bool,
+    user_bits: u32,
+    frames: usize,
+    reverse: bool,
+    // Encoded a byte at a time with this speed, the factor on the samples per bit, unless 1
+    speed: f64,
+}
+
+const ENCODER_CASES: [EncoderCase; 4] = [
+    EncoderCase {
+        name: "625_50",
+        expected: include_bytes!("golden/encoder_625_50.u8"),
+        sample_rate: 48_000.0,
+        fps: 25.0,
+        standard: LTCTVStandard::LTCTV_625_50,
+        flags: 0,
+        rise_time: 40.0,
+        volume: -3.0,
+        date: [0; 3],
+        timezone: b"+0000\0",
+        time: [1, 2, 3, 4],
+        drop_frame: false,
+        user_bits: 0x8765_4321,
+        frames: 2,
+        reverse: false,
+        speed: 1.0,
+    },
+    // Across a minute with dropped frames, with the date in the user bits
+    EncoderCase {
+        name: "525_60_df",
+        expected: include_bytes!("golden/encoder_525_60_df.u8"),
+        sample_rate: 44_100.0,
+        fps: 30_000.0 / 1001.0,
+        standard: LTCTVStandard::LTCTV_525_60,
+        flags: 1,
+        rise_time: 25.0,
+        volume: -12.0,
+        date: [26, 10, 19],
+        timezone: b"+0100\0",
+        time: [0, 0, 59, 28],
+        drop_frame: true,
+        user_bits: 0,
+        frames: 3,
+        reverse: false,
+        speed: 1.0,
+    },
+    EncoderCase {
+        name: "1125_60_reverse",
+        expected: include_bytes!("golden/encoder_1125_60_reverse.u8"),
+        sample_rate: 48_000.0,
+        fps: 30.0,
+        standard: LTCTVStandard::LTCTV_1125_60,
+        flags: 0,
+        rise_time: 0.0,
+        volume: -3.0,
+        date: [0; 3],
+        timezone: b"+0000\0",
+        time: [0, 0, 10, 0],
+        drop_frame: false,
+        user_bits: 0,
+        frames: 2,
+        reverse: true,
+        speed: 1.0,
+    },
+    EncoderCase {
+        name: "film_24_speed",
+        expected: include_bytes!("golden/encoder_film_24_speed.u8"),
+        sample_rate: 48_000.0,
+        fps: 24.0,
+        standard: LTCTVStandard::LTCTV_FILM_24,
+        flags: 0,
+        rise_time: 40.0,
+        volume: -6.0,
+        date: [0; 3],
+        timezone: b"+0000\0",
+        time: [23, 59, 59, 23],
+        drop_frame: false,
+        user_bits: 0x0102_0304,
+        frames: 2,
+        reverse: false,
+        speed: 0.5,
+    },
+];
+
+impl EncoderCase {
+    fn timecode(&self) -> raw::SMPTETimecode {
+        let [years, months, days] = self.date;
+        let [hours, mins, secs, frame] = self.time;
+        raw::SMPTETimecode {
+            timezone: self.timezone.map(|b| b as c_char),
+            years,
+            months,
+            days,
+            hours,
+            mins,
+            secs,
+            frame,
+        }
+    }
+}
+
+fn native_encode(case: &EncoderCase) -> Vec<SampleType> {
+    let flags = LtcBgFlags::from(case.flags);
+    let mut encoder = Encoder::new(case.sample_rate, case.fps, case.standard, flags).unwrap();
+    encoder.set_filter(case.rise_time);
+    encoder.set_volume(case.volume).unwrap();
+    encoder.set_timecode(&case.timecode());
+    if case.drop_frame {
+        let mut frame = encoder.frame();
+        frame.set_dfbit(1);
+        encoder.set_frame(&frame);
+    }
+    encoder.set_user_bits(case.user_bits);
+
+    let mut samples = Vec::new();
+    for _ in 0..case.frames {
+        if case.reverse {
+            encoder.dec_timecode();
+            encoder.encode_reversed_frame();
+        } else if case.speed != 1.0 {
+            for byte in 0..10 {
+                encoder.encode_byte(byte, case.speed).unwrap();
+            }
+        } else {
+            encoder.encode_frame();
+        }
+        samples.extend_from_slice(encoder.buffer(true));
+        if !case.reverse {
+            encoder.inc_timecode();
+        }
+    }
+    samples
+}
+
+fn decoder_input_timecode() -> raw::SMPTETimecode {
+    raw::SMPTETimecode {
+        timezone: b"+0000\0".map(|b| b as c_char),
+        hours: 10,
+        ..raw::SMPTETimecode::default()
+    }
+}
+
+fn native_decoder_input() -> Vec<SampleType> {
+    let mut encoder = Encoder::new(
+        48_000.0,
+        25.0,
+        LTCTVStandard::LTCTV_625_50,
+        LtcBgFlags::default(),
+    )
+    .unwrap();
+    encoder.set_volume(-12.0).unwrap();
+    encoder.set_timecode(&decoder_input_timecode());
+    encoder.set_user_bits(DECODER_USER_BITS);
+
+    let mut samples = vec![128; DECODER_SILENCE];
+    for speed in DECODER_SPEEDS {
+        // A slow frame doesn't fit the buffer, so it is read after every byte
+        if speed < 0.0 {
+            encoder.dec_timecode();
+            for byte in (0..10).rev() {
+                encoder.encode_byte(byte, speed).unwrap();
+                samples.extend_from_slice(encoder.buffer(true));
+            }
+        } else {
+            for byte in 0..10 {
+                encoder.encode_byte(byte, speed).unwrap();
+                samples.extend_from_slice(encoder.buffer(true));
+            }
+            encoder.inc_timecode();
+        }
+    }
+    samples.resize(samples.len() + DECODER_SILENCE, 128);
+    samples
+}
+
+fn native_decode(input: &[u8]) -> Vec<raw::LTCFrameExt> {
+    let mut decoder = Decoder::new(DECODER_APV, DECODER_QUEUE).unwrap();
+    let mut frames = Vec::new();
+    for (n, chunk) in input.chunks(DECODER_CHUNK).enumerate() {
+        decoder.write(chunk, (n * DECODER_CHUNK) as i64);
+        frames.extend(core::iter::from_fn(|| decoder.read()));
+    }
+    frames
+}
+
+// The format `capture::format_frame` writes
+fn parse_frame(line: &str) -> raw::LTCFrameExt {
+    let mut fields = line.split(' ');
+    let mut next = || fields.next().unwrap();
+    let hex = next();
+    let bytes: [u8; 10] =
+        core::array::from_fn(|i| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap());
+    let mut frame = raw::LTCFrameExt {
+        ltc: LTCFrame::from_bytes(bytes).inner_raw,
+        off_start: next().parse().unwrap(),
+        off_end: next().parse().unwrap(),
+        reverse: next().parse().unwrap(),
+        sample_min: next().parse().unwrap(),
+        sample_max: next().parse().unwrap(),
+        volume: next().parse().unwrap(),
+        ..raw::LTCFrameExt::default()
+    };
+    for tic in &mut frame.biphase_tics {
+        *tic = next().parse().unwrap();
+    }
+    frame
+}
+
+#[test]
+fn test_decoder_golden() {
+    let expected: Vec<_> = DECODER_FRAMES.lines().map(parse_frame).collect();
+    let frames = native_decode(DECODER_INPUT);
+    assert_eq!(frames.len(), expected.len());
+    for (i, (frame, expected)) in frames.iter().zip(&expected).enumerate() {
+        let ltc = LTCFrame::from(frame.ltc);
+        let expected_ltc = LTCFrame::from(expected.ltc);
+        assert_eq!(ltc.to_bytes(), expected_ltc.to_bytes(), "frame {i}");
+        assert_eq!(
+            (frame.off_start, frame.off_end, frame.reverse),
+            (expected.off_start, expected.off_end, expected.reverse),
+            "frame {i}"
+        );
+        assert_eq!(
+            (frame.sample_min, frame.sample_max),
+            (expected.sample_min, expected.sample_max),
+            "frame {i}"
+        );
+        assert!((frame.volume - expected.volume).abs() < 1e-9, "frame {i}");
+        for (bit, (a, b)) in frame
+            .biphase_tics
+            .iter()
+            .zip(expected.biphase_tics)
+            .enumerate()
+        {
+            assert!((a - b).abs() < 1e-3, "frame {i} bit {bit}: {a} != {b}");
+        }
+    }
+}
+
+#[test]
+fn test_decoder_input() {
+    let samples = native_decoder_input();
+    assert_eq!(samples.len(), DECODER_INPUT.len());
+    for (i, (&a, &b)) in samples.iter().zip(DECODER_INPUT).enumerate() {
+        assert!((a as i32 - b as i32).abs() <= 1, "sample {i}: {a} != {b}");
+    }
+}
+
+#[test]
+fn test_encoder_golden() {
+    for case in &ENCODER_CASES {
+        let samples = native_encode(case);
+        assert_eq!(samples.len(), case.expected.len(), "{}", case.name);
+        // libm and the C math library may round the filter differently
+        for (i, (&a, &b)) in samples.iter().zip(case.expected).enumerate() {
+            assert!(
+                (a as i32 - b as i32).abs() <= 1,
+                "{} sample {i}: {a} != {b}",
+                case.name
+            );
+        }
+    }
+}
+
+#[cfg(all(libltc, feature = "std"))]
+mod capture {
+    use super::*;
+    use alloc::string::String;
+    use core::fmt::Write;
+    use std::fs;
+
+    const DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/api/native/golden");
+
+    // One frame per line: the 10 frame bytes in hex, off_start, off_end, reverse, sample_min,
+    // sample_max, volume and the 80 biphase tics
+    fn format_frame(frame: &raw::LTCFrameExt) -> String {
+        let mut line = String::new();
+        let bytes = LTCFrame::from(frame.ltc).to_bytes();
+        for byte in bytes {
+            write!(line, "{byte:02x}").unwrap();
+        }
+        write!(
+            line,
+            " {} {} {} {} {} {:?}",
+            frame.off_start,
+            frame.off_end,
+            frame.reverse,
+            frame.sample_min,
+            frame.sample_max,
+            frame.volume
+        )
+        .unwrap();
+        for tic in frame.biphase_tics {
+            write!(line, " {tic:?}").unwrap();
+        }
+        line
+    }
+
+    fn c_decode(input: &[u8]) -> Vec<raw::LTCFrameExt> {
+        let decoder = unsafe { raw::ltc_decoder_create(DECODER_APV, DECODER_QUEUE) };
+        let mut frames = Vec::new();
+        for (n, chunk) in input.chunks(DECODER_CHUNK).enumerate() {
+            unsafe {
+                raw::ltc_decoder_write(
+                    decoder,
+                    chunk.as_ptr() as *mut _,
+                    chunk.len(),
+                    (n * DECODER_CHUNK) as i64,
+                )
+            };
+            let mut frame = raw::LTCFrameExt::default();
+            while unsafe { raw::ltc_decoder_read(decoder, &mut frame) } != 0 {
+                frames.push(frame);
+            }
+        }
+        unsafe { raw::ltc_decoder_free(decoder) };
+        frames
+    }
+
+    fn c_decoder_input() -> Vec<SampleType> {
+        let mut samples = vec![128; DECODER_SILENCE];
+        unsafe {
+            let e =
+                raw::ltc_encoder_create(48_000.0, 25.0, LTCTVStandard::LTCTV_625_50.to_raw(), 0);
+            assert_eq!(raw::ltc_encoder_set_volume(e, -12.0), 0);
+            raw::ltc_encoder_set_timecode(e, &mut decoder_input_timecode());
+            raw::ltc_encoder_set_user_bits(e, DECODER_USER_BITS as _);
+            let mut encode_byte = |byte, speed| {
+                assert_eq!(raw::ltc_encoder_encode_byte(e, byte, speed), 0);
+                let mut ptr = core::ptr::null_mut();
+                let len = raw::ltc_encoder_get_bufferptr(e, &mut ptr, 1);
+                samples.extend_from_slice(core::slice::from_raw_parts(ptr, len as usize));
+            };
+            for speed in DECODER_SPEEDS {
+                if speed < 0.0 {
+                    raw::ltc_encoder_dec_timecode(e);
+                    (0..10).rev().for_each(|byte| encode_byte(byte, speed));
+                } else {
+                    (0..10).for_each(|byte| encode_byte(byte, speed));
+                    raw::ltc_encoder_inc_timecode(e);
+                }
+            }
+            raw::ltc_encoder_free(e);
+        }
+        samples.resize(samples.len() + DECODER_SILENCE, 128);
+        samples
+    }
+
+    fn c_encode(case: &EncoderCase) -> Vec<SampleType> {
+        let flags = case.flags as i32;
+        let e = unsafe {
+            raw::ltc_encoder_create(case.sample_rate, case.fps, case.standard.to_raw(), flags)
+        };
+        let mut samples = Vec::new();
+        unsafe {
+            raw::ltc_encoder_set_filter(e, case.rise_time);
+            assert_eq!(raw::ltc_encoder_set_volume(e, case.volume), 0);
+            raw::ltc_encoder_set_timecode(e, &mut case.timecode());
+            if case.drop_frame {
+                let mut frame = raw::LTCFrame::default();
+                raw::ltc_encoder_get_frame(e, &mut frame);
+                frame.set_dfbit(1);
+                raw::ltc_encoder_set_frame(e, &mut frame);
+            }
+            raw::ltc_encoder_set_user_bits(e, case.user_bits as _);
+
+            for _ in 0..case.frames {
+                if case.reverse {
+                    raw::ltc_encoder_dec_timecode(e);
+                    raw::ltc_encoder_encode_reversed_frame(e);
+                } else if case.speed != 1.0 {
+                    for byte in 0..10 {
+                        assert_eq!(raw::ltc_encoder_encode_byte(e, byte, case.speed), 0);
+                    }
+                } else {
+                    raw::ltc_encoder_encode_frame(e);
+                }
+                let mut ptr = core::ptr::null_mut();
+                let len = raw::ltc_encoder_get_bufferptr(e, &mut ptr, 1);
+                samples.extend_from_slice(core::slice::from_raw_parts(ptr, len as usize));
+                if !case.reverse {
+                    raw::ltc_encoder_inc_timecode(e);
+                }
+            }
+            raw::ltc_encoder_free(e);
+        }
+        samples
+    }
+
+    /// Rewrites the decoder input and the expected outputs with what libltc produces
+    #[test]
+    #[ignore]
+    fn capture() {
+        for case in &ENCODER_CASES {
+            fs::write(format!("{DIR}/encoder_{}.u8", case.name), c_encode(case)).unwrap();
+        }
+        let input = c_decoder_input();
+        fs::write(format!("{DIR}/decoder_input.u8"), &input).unwrap();
+        let lines: Vec<String> = c_decode(&input).iter().map(format_frame).collect();
+        fs::write(format!("{DIR}/decoder_frames.txt"), lines.join("\n") + "\n").unwrap();
+    }
+}
@@ -0,0 +1,7 @@
+//! Rust implementations of the libltc internals, selected by the `pure-rust` feature
+
+pub(crate) mod decoder;
+pub(crate) mod encoder;
+#[cfg(test)]
+mod golden;
+pub(crate) mod timecode;
@@ -0,0 +1,498 @@
+//! Port of libltc's `timecode.c`: conversion between frames and timecodes, frame
+//! arithmetic, parity and the SMPTE 309M timezone codes.
+//!
+//! The frame fields are bitfields, so values are truncated to the field width on write
+//! just like the C assignments.
+
+use core::ffi::c_char;
+
+use crate::api::consts::{LtcBgFlags, LtcBgFlagsKind};
+use crate::api::{LTCTVStandard, Timezone, SMPTE_TIMEZONES};
+#[cfg(not(feature = "std"))]
+use crate::math::F64Ext;
+use crate::raw;
+
+const SYNC_WORD: u32 = 0xbffc;
+const NO_TIMEZONE: [u8; 6] = *b"+0000\0";
+
+fn timezone_to_raw(timezone: &[u8; 5]) -> [c_char; 6] {
+    let mut raw = [0; 6];
+    for (out, &b) in raw.iter_mut().zip(timezone) {
+        *out = b as c_char;
+    }
+    raw
+}
+
+pub(crate) fn frame_reset(frame: &mut raw::LTCFrame) {
+    *frame = raw::LTCFrame::default();
+    frame.set_sync_word(SYNC_WORD);
+}
+
+fn date(frame: &raw::LTCFrame) -> (u32, u32, u32) {
+    (
+        frame.user5() + frame.user6() * 10,
+        frame.user3() + frame.user4() * 10,
+        frame.user1() + frame.user2() * 10,
+    )
+}
+
+fn set_date(frame: &mut raw::LTCFrame, years: u32, months: u32, days: u32) {
+    frame.set_user6(years / 10);
+    frame.set_user5(years % 10);
+    frame.set_user4(months / 10);
+    frame.set_user3(months % 10);
+    frame.set_user2(days / 10);
+    frame.set_user1(days % 10);
+}
+
+fn days_in_month(years: u32, months: u32) -> u32 {
+    const DAYS: [u32; 12] = [31, 28, 31, 30, 31, 30, 31, 31, 30, 31, 30, 31];
+    // The year is 0..99, so every fourth is a leap year
+    if months == 2 && years.is_multiple_of(4) {
+        29
+    } else {
+        DAYS[months as usize - 1]
+    }
+}
+
+pub(crate) fn frame_to_time(
+    stime: &mut raw::SMPTETimecode,
+    frame: &raw::LTCFrame,
+    flags: LtcBgFlags,
+) {
+    if flags.contains(LtcBgFlagsKind::LTC_USE_DATE) {
+        let code = (frame.user7() + (frame.user8() << 4)) as u8;
+        stime.timezone = SMPTE_TIMEZONES
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map_or(timezone_to_raw(b"+0000"), |(_, tz)| timezone_to_raw(tz));
+        let (years, months, days) = date(frame);
+        stime.years = years as u8;
+        stime.months = months as u8;
+        stime.days = days as u8;
+    } else {
+        stime.timezone = NO_TIMEZONE.map(|b| b as c_char);
+        stime.years = 0;
+        stime.months = 0;
+        stime.days = 0;
+    }
+
+    stime.hours = (frame.hours_units() + frame.hours_tens() * 10) as u8;
+    stime.mins = (frame.mins_units() + frame.mins_tens() * 10) as u8;
+    stime.secs = (frame.secs_units() + frame.secs_tens() * 10) as u8;
+    stime.frame = (frame.frame_units() + frame.frame_tens() * 10) as u8;
+}
+
+pub(crate) fn time_to_frame(
+    frame: &mut raw::LTCFrame,
+    stime: &raw::SMPTETimecode,
+    standard: LTCTVStandard,
+    flags: LtcBgFlags,
+) {
+    if flags.contains(LtcBgFlagsKind::LTC_USE_DATE) {
+        let code = Timezone::new(stime.timezone)
+            .smpte_code()
+            .map_or(0, u32::from);
+        frame.set_user7(code & 0x0f);
+        frame.set_user8((code & 0xf0) >> 4);
+
+        // As in C, the units are computed from the tens stored in the field
+        frame.set_user6(stime.years as u32 / 10);
+        frame.set_user5((stime.years as u32).wrapping_sub(frame.user6() * 10));
+        frame.set_user4(stime.months as u32 / 10);
+        frame.set_user3((stime.months as u32).wrapping_sub(frame.user4() * 10));
+        frame.set_user2(stime.days as u32 / 10);
+        frame.set_user1((stime.days as u32).wrapping_sub(frame.user2() * 10));
+    }
+
+    frame.set_hours_tens(stime.hours as u32 / 10);
+    frame.set_hours_units((stime.hours as u32).wrapping_sub(frame.hours_tens() * 10));
+    frame.set_mins_tens(stime.mins as u32 / 10);
+    frame.set_mins_units((stime.mins as u32).wrapping_sub(frame.mins_tens() * 10));
+    frame.set_secs_tens(stime.secs as u32 / 10);
+    frame.set_secs_units((stime.secs as u32).wrapping_sub(frame.secs_tens() * 10));
+    frame.set_frame_tens(stime.frame as u32 / 10);
+    frame.set_frame_units((stime.frame as u32).wrapping_sub(frame.frame_tens() * 10));
+
+    // Prevent illegal drop frame timecodes
+    if is_dropped(frame, 0) {
+        frame.set_frame_units(frame.frame_units() + 2);
+    }
+
+    if !flags.contains(LtcBgFlagsKind::LTC_NO_PARITY) {
+        frame_set_parity(frame, standard);
+    }
+}
+
+// Drop frame timecode skips frames 0 and 1 at the start of every minute but every tenth
+fn is_dropped(frame: &raw::LTCFrame, frame_units: u32) -> bool {
+    frame.dfbit() != 0
+        && frame.mins_units() != 0
+        && frame.secs_units() == 0
+        && frame.secs_tens() == 0
+        && frame.frame_units() == frame_units
+        && frame.frame_tens() == 0
+}
+
+pub(crate) fn frame_increment(
+    frame: &mut raw::LTCFrame,
+    fps: i32,
+    standard: LTCTVStandard,
+    flags: LtcBgFlags,
+) -> i32 {
+    let mut rv = 0;
+
+    frame.set_frame_units(frame.frame_units() + 1);
+    if frame.frame_units() == 10 {
+        frame.set_frame_units(0);
+        frame.set_frame_tens(frame.frame_tens() + 1);
+    }
+    if fps as u32 == frame.frame_units() + frame.frame_tens() * 10 {
+        frame.set_frame_units(0);
+        frame.set_frame_tens(0);
+        frame.set_secs_units(frame.secs_units() + 1);
+        if frame.secs_units() == 10 {
+            frame.set_secs_units(0);
+            frame.set_secs_tens(frame.secs_tens() + 1);
+            if frame.secs_tens() == 6 {
+                frame.set_secs_tens(0);
+                frame.set_mins_units(frame.mins_units() + 1);
+                if frame.mins_units() == 10 {
+                    frame.set_mins_units(0);
+                    frame.set_mins_tens(frame.mins_tens() + 1);
+                    if frame.mins_tens() == 6 {
+                        frame.set_mins_tens(0);
+                        frame.set_hours_units(frame.hours_units() + 1);
+                        if frame.hours_units() == 10 {
+                            frame.set_hours_units(0);
+                            frame.set_hours_tens(frame.hours_tens() + 1);
+                        }
+                        if frame.hours_units() == 4 && frame.hours_tens() == 2 {
+                            // 24h wrap around
+                            rv = 1;
+                            frame.set_hours_tens(0);
+                            frame.set_hours_units(0);
+
+                            if flags.contains(LtcBgFlagsKind::LTC_USE_DATE) {
+                                let (mut years, mut months, mut days) = date(frame);
+                                if (1..13).contains(&months) {
+                                    days += 1;
+                                    if days > days_in_month(years, months) {
+                                        days = 1;
+                                        months += 1;
+                                        if months > 12 {
+                                            months = 1;
+                                            years = (years + 1) % 100;
+                                        }
+                                    }
+                                    set_date(frame, years, months, days);
+                                } else {
+                                    rv = -1;
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if is_dropped(frame, 0) {
+        frame.set_frame_units(frame.frame_units() + 2);
+    }
+
+    if !flags.contains(LtcBgFlagsKind::LTC_NO_PARITY) {
+        frame_set_parity(frame, standard);
+    }
+
+    rv
+}
+
+// One frame back, without drop frame handling or parity
+fn frame_step_back(frame: &mut raw::LTCFrame, fps: u32, flags: LtcBgFlags) -> i32 {
+    let mut rv = 0;
+
+    let frames = match frame.frame_units() + frame.frame_tens() * 10 {
+        0 => fps - 1,
+        frames => frames - 1,
+    };
+    frame.set_frame_units(frames % 10);
+    frame.set_frame_tens(frames / 10);
+    if frames != fps - 1 {
+        return rv;
+    }
+
+    let secs = match frame.secs_units() + frame.secs_tens() * 10 {
+        0 => 59,
+        secs => secs - 1,
+    };
+    frame.set_secs_units(secs % 10);
+    frame.set_secs_tens(secs / 10);
+    if secs != 59 {
+        return rv;
+    }
+
+    let mins = match frame.mins_units() + frame.mins_tens() * 10 {
+        0 => 59,
+        mins => mins - 1,
+    };
+    frame.set_mins_units(mins % 10);
+    frame.set_mins_tens(mins / 10);
+    if mins != 59 {
+        return rv;
+    }
+
+    let hours = match frame.hours_units() + frame.hours_tens() * 10 {
+        0 => 23,
+        hours => hours - 1,
+    };
+    frame.set_hours_units(hours % 10);
+    frame.set_hours_tens(hours / 10);
+    if hours != 23 {
+        return rv;
+    }
+
+    // 24h wrap around
+    rv = 1;
+    if flags.contains(LtcBgFlagsKind::LTC_USE_DATE) {
+        let (mut years, mut months, mut days) = date(frame);
+        if (1..13).contains(&months) {
+            if days > 1 {
+                days -= 1;
+            } else {
+                months = 1 + (months + 10) % 12;
+                days = days_in_month(years, months);
+                if months == 12 {
+                    years = (years + 99) % 100;
+                }
+            }
+            set_date(frame, years, months, days);
+        } else {
+            rv = -1;
+        }
+    }
+    rv
+}
+
+pub(crate) fn frame_decrement(
+    frame: &mut raw::LTCFrame,
+    fps: i32,
+    standard: LTCTVStandard,
+    flags: LtcBgFlags,
+) -> i32 {
+    let fps = fps.max(1) as u32;
+    let rv = frame_step_back(frame, fps, flags);
+
+    // Frames 1 and 0 are dropped, continue at the end of the previous minute. This never
+    // crosses the hour, as minute 0 keeps its frames.
+    if is_dropped(frame, 1) {
+        frame_step_back(frame, fps, flags);
+        frame_step_back(frame, fps, flags);
+    }
+
+    if !flags.contains(LtcBgFlagsKind::LTC_NO_PARITY) {
+        frame_set_parity(frame, standard);
+    }
+
+    rv
+}
+
+pub(crate) fn frame_set_parity(frame: &mut raw::LTCFrame, standard: LTCTVStandard) {
+    let pal = standard == LTCTVStandard::LTCTV_625_50;
+    if pal {
+        frame.set_binary_group_flag_bit2(0);
+    } else {
+        frame.set_biphase_mark_phase_correction(0);
+    }
+
+    let p = (0..raw::LTC_FRAME_BIT_COUNT as usize / 8)
+        .fold(0u8, |p, i| p ^ frame._bitfield_1.get(i * 8, 8) as u8);
+    let parity = p.count_ones() & 1;
+
+    if pal {
+        frame.set_binary_group_flag_bit2(parity);
+    } else {
+        frame.set_biphase_mark_phase_correction(parity);
+    }
+}
+
+pub(crate) fn frame_parse_bcg_flags(frame: &raw::LTCFrame, standard: LTCTVStandard) -> i32 {
+    let (bgf0, bgf2) = if standard == LTCTVStandard::LTCTV_625_50 {
+        (
+            frame.biphase_mark_phase_correction(),
+            frame.binary_group_flag_bit0(),
+        )
+    } else {
+        (
+            frame.binary_group_flag_bit0(),
+            frame.binary_group_flag_bit2(),
+        )
+    };
+    ((bgf2 != 0) as i32) << 2 | ((frame.binary_group_flag_bit1() != 0) as i32) << 1 | bgf0 as i32
+}
+
+pub(crate) fn frame_get_user_bits(frame: &raw::LTCFrame) -> u32 {
+    [
+        frame.user8(),
+        frame.user7(),
+        frame.user6(),
+        frame.user5(),
+        frame.user4(),
+        frame.user3(),
+        frame.user2(),
+        frame.user1(),
+    ]
+    .iter()
+    .fold(0, |data, &nibble| (data << 4) + nibble)
+}
+
+pub(crate) fn frame_alignment(samples_per_frame: f64, standard: LTCTVStandard) -> i64 {
+    match standard {
+        LTCTVStandard::LTCTV_525_60 => (samples_per_frame * 4.0 / 525.0).round_ties_even() as i64,
+        LTCTVStandard::LTCTV_625_50 => (samples_per_frame * 1.0 / 625.0).round_ties_even() as i64,
+        _ => 0,
+    }
+}
+
+#[cfg(all(test, libltc))]
+mod tests {
+    use super::*;
+    use crate::api::frame::LTCFrame;
+    use crate::api::{SMPTETimecode, Timezone};
+
+    fn bytes(frame: &raw::LTCFrame) -> [u8; 10] {
+        LTCFrame::from(*frame).to_bytes()
+    }
+
+    fn raw_timecode(timecode: &SMPTETimecode) -> raw::SMPTETimecode {
+        raw::SMPTETimecode {
+            timezone: timecode.timezone().to_raw(),
+            years: timecode.years(),
+            months: timecode.months(),
+            days: timecode.days(),
+            hours: timecode.hours(),
+            mins: timecode.minutes(),
+            secs: timecode.seconds(),
+            frame: timecode.frame(),
+        }
+    }
+
+    const STANDARDS: [(LTCTVStandard, i32); 4] = [
+        (LTCTVStandard::LTCTV_525_60, 30),
+        (LTCTVStandard::LTCTV_625_50, 25),
+        (LTCTVStandard::LTCTV_1125_60, 30),
+        (LTCTVStandard::LTCTV_FILM_24, 24),
+    ];
+
+    #[test]
+    fn test_time_to_frame_matches_c() {
+        let timecodes = [
+            SMPTETimecode::new(Timezone::from(*b"+0200\0"), 24, 2, 29, 23, 59, 59, 24),
+            SMPTETimecode::new(Timezone::from(*b"-0530\0"), 99, 12, 31, 0, 1, 0, 0),
+            SMPTETimecode::new(Timezone::from(*b"+1245\0"), 0, 1, 1, 12, 10, 0, 1),
+            SMPTETimecode::new(Timezone::from(*b"+9999\0"), 5, 6, 7, 45, 70, 70, 45),
+        ];
+        for (standard, _) in STANDARDS {
+            for flags in 0..16u32 {
+                let flags = LtcBgFlags::from(flags);
+                for timecode in &timecodes {
+                    for dfbit in [0, 1] {
+                        let stime = raw_timecode(timecode);
+                        let mut native = raw::LTCFrame::default();
+                        frame_reset(&mut native);
+                        native.set_dfbit(dfbit);
+                        let mut c = native;
+                        time_to_frame(&mut native, &stime, standard, flags);
+                        let mut c_stime = stime;
+                        unsafe {
+                            raw::ltc_time_to_frame(
+                                &mut c,
+                                &mut c_stime,
+                                standard.to_raw(),
+                                flags.into(),
+                            )
+                        };
+                        assert_eq!(bytes(&native), bytes(&c));
+
+                        let mut native_time = raw::SMPTETimecode::default();
+                        let mut c_time = raw::SMPTETimecode::default();
+                        frame_to_time(&mut native_time, &native, flags);
+                        unsafe { raw::ltc_frame_to_time(&mut c_time, &mut c, flags.into()) };
+                        assert_eq!(native_time.timezone, c_time.timezone);
+                        assert_eq!(
+                            [native_time.years, native_time.months, native_time.days],
+                            [c_time.years, c_time.months, c_time.days]
+                        );
+                        assert_eq!(
+                            [native_time.hours, native_time.mins, native_time.secs],
+                            [c_time.hours, c_time.mins, c_time.secs]
+                        );
+                        assert_eq!(native_time.frame, c_time.frame);
+                        assert_eq!(frame_get_user_bits(&native), unsafe {
+                            raw::ltc_frame_get_user_bits(&mut c) as u32
+                        });
+                        assert_eq!(frame_parse_bcg_flags(&native, standard), unsafe {
+                            raw::ltc_frame_parse_bcg_flags(&mut c, standard.to_raw())
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_increment_decrement_match_c() {
+        let flags = [
+            LtcBgFlags::default(),
+            LtcBgFlags::from(LtcBgFlagsKind::LTC_USE_DATE as u32),
+            LtcBgFlags::from(LtcBgFlagsKind::LTC_NO_PARITY as u32),
+        ];
+        let start = SMPTETimecode::new(Timezone::default(), 23, 12, 31, 23, 58, 59, 0);
+        for (standard, fps) in STANDARDS {
+            for flags in flags {
+                for dfbit in [0, 1] {
+                    let mut native = raw::LTCFrame::default();
+                    frame_reset(&mut native);
+                    native.set_dfbit(dfbit);
+                    time_to_frame(&mut native, &raw_timecode(&start), standard, flags);
+                    let mut c = native;
+                    // Across midnight and the new year, then back
+                    for step in 0..(fps * 130) {
+                        let (a, b) = if step < fps * 65 {
+                            (frame_increment(&mut native, fps, standard, flags), unsafe {
+                                raw::ltc_frame_increment(
+                                    &mut c,
+                                    fps,
+                                    standard.to_raw(),
+                                    flags.into(),
+                                )
+                            })
+                        } else {
+                            (frame_decrement(&mut native, fps, standard, flags), unsafe {
+                                raw::ltc_frame_decrement(
+                                    &mut c,
+                                    fps,
+                                    standard.to_raw(),
+                                    flags.into(),
+                                )
+                            })
+                        };
+                        assert_eq!(a, b);
+                        assert_eq!(bytes(&native), bytes(&c), "step {step}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_alignment_matches_c() {
+        for (standard, _) in STANDARDS {
+            for spf in [1600.0, 1601.6, 1764.0, 1920.0, 2000.0, 8008.0] {
+                assert_eq!(frame_alignment(spf, standard), unsafe {
+                    raw::ltc_frame_alignment(spf, standard.to_raw())
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,434 @@
+use std::io::ErrorKind;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use super::artnet::sender_socket;
+use super::consts::{LtcBgFlags, LtcBgFlagsKind};
+use super::encoder::LTCEncoder;
+use super::export::LoggedTimecode;
+use super::frame::LTCFrameExt;
+use super::SMPTETimecode;
+use crate::error::{LTCEncoderError, NetError};
+
+/// An argument of an OSC message, only the OSC 1.0 atomic types used for timecode
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscArg {
+    Int(i32),
+    Float(f32),
+    String(String),
+}
+
+impl OscArg {
+    fn tag(&self) -> u8 {
+        match self {
+            OscArg::Int(_) => b'i',
+            OscArg::Float(_) => b'f',
+            OscArg::String(_) => b's',
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            OscArg::Int(i) => Some(*i),
+            OscArg::Float(f) => Some(*f as i32),
+            OscArg::String(_) => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OscMessage {
+    pub address: String,
+    pub args: Vec<OscArg>,
+}
+
+fn write_padded(buf: &mut Vec<u8>, bytes: &[u8]) {
+    buf.extend_from_slice(bytes);
+    buf.push(0);
+    while !buf.len().is_multiple_of(4) {
+        buf.push(0);
+    }
+}
+
+// A null-terminated, 4-byte aligned string starting at `*pos`
+fn read_padded<'a>(packet: &'a [u8], pos: &mut usize) -> Result<&'a str, NetError> {
+    let rest = packet.get(*pos..).unwrap_or_default();
+    let len = rest
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(NetError::InvalidPacket("unterminated OSC string"))?;
+    let s = std::str::from_utf8(&rest[..len])
+        .map_err(|_| NetError::InvalidPacket("OSC string is not UTF-8"))?;
+    *pos += (len + 4) & !3;
+    Ok(s)
+}
+
+fn read_word(packet: &[u8], pos: &mut usize) -> Result<[u8; 4], NetError> {
+    let word = packet
+        .get(*pos..*pos + 4)
+        .ok_or(NetError::InvalidPacket("OSC message too short"))?;
+    *pos += 4;
+    Ok([word[0], word[1], word[2], word[3]])
+}
+
+impl OscMessage {
+    pub fn new(address: impl Into<String>, args: Vec<OscArg>) -> Self {
+        OscMessage {
+            address: address.into(),
+            args,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        write_padded(&mut buf, self.address.as_bytes());
+        let tags: Vec<u8> = std::iter::once(b',')
+            .chain(self.args.iter().map(OscArg::tag))
+            .collect();
+        write_padded(&mut buf, &tags);
+        for arg in &self.args {
+            match arg {
+                OscArg::Int(i) => buf.extend_from_slice(&i.to_be_bytes()),
+                OscArg::Float(f) => buf.extend_from_slice(&f.to_be_bytes()),
+                OscArg::String(s) => write_padded(&mut buf, s.as_bytes()),
+            }
+        }
+        buf
+    }
+
+    pub fn from_bytes(packet: &[u8]) -> Result<Self, NetError> {
+        if packet.starts_with(b"#bundle") {
+            return Err(NetError::InvalidPacket("OSC bundles are not supported"));
+        }
+        if !packet.starts_with(b"/") {
+            return Err(NetError::InvalidPacket("not an OSC message"));
+        }
+        let mut pos = 0;
+        let address = read_padded(packet, &mut pos)?.to_string();
+        // A missing type tag string is an old-style message without arguments
+        if pos >= packet.len() {
+            return Ok(OscMessage::new(address, vec![]));
+        }
+        let tags = read_padded(packet, &mut pos)?;
+        let tags = tags
+            .strip_prefix(',')
+            .ok_or(NetError::InvalidPacket("missing OSC type tags"))?;
+        let mut args = Vec::with_capacity(tags.len());
+        for tag in tags.bytes() {
+            args.push(match tag {
+                b'i' => OscArg::Int(i32::from_be_bytes(read_word(packet, &mut pos)?)),
+                b'f' => OscArg::Float(f32::from_be_bytes(read_word(packet, &mut pos)?)),
+                b's' => OscArg::String(read_padded(packet, &mut pos)?.to_string()),
+                _ => return Err(NetError::InvalidPacket("unsupported OSC argument type")),
+            });
+        }
+        Ok(OscMessage { address, args })
+    }
+}
+
+/// Publishes timecode as `<address> ,iiiifi hours minutes seconds frame fps running`
+#[derive(Debug)]
+pub struct OscTimecodeSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    address: String,
+    fps: f64,
+}
+
+impl OscTimecodeSender {
+    /// `address` is the OSC address pattern, e.g. `/ltc/timecode`
+    pub fn new(target: impl ToSocketAddrs, address: &str, fps: f64) -> Result<Self, NetError> {
+        if !address.starts_with('/') {
+            return Err(NetError::InvalidPacket("OSC address must start with '/'"));
+        }
+        let (socket, target) = sender_socket(target)?;
+        Ok(OscTimecodeSender {
+            socket,
+            target,
+            address: address.to_string(),
+            fps,
+        })
+    }
+
+    pub fn message(&self, timecode: &SMPTETimecode, running: bool) -> OscMessage {
+        OscMessage::new(
+            self.address.clone(),
+            vec![
+                OscArg::Int(timecode.hours() as i32),
+                OscArg::Int(timecode.minutes() as i32),
+                OscArg::Int(timecode.seconds() as i32),
+                OscArg::Int(timecode.frame() as i32),
+                OscArg::Float(self.fps as f32),
+                OscArg::Int(running as i32),
+            ],
+        )
+    }
+
+    pub fn send(&self, timecode: &SMPTETimecode, running: bool) -> Result<(), NetError> {
+        self.socket
+            .send_to(&self.message(timecode, running).to_bytes(), self.target)?;
+        Ok(())
+    }
+
+    /// A frame read from `LTCDecoder`, which is running by definition
+    pub fn send_frame(&self, frame: &LTCFrameExt) -> Result<(), NetError> {
+        self.send(&frame.ltc().to_timecode(LtcBgFlags::default()), true)
+    }
+}
+
+/// A command for the generator, addressed relative to the generator's prefix
+#[derive(Debug, Clone, PartialEq)]
+pub enum OscCommand {
+    /// `<prefix>/set` with four ints or an `"HH:MM:SS:FF"` string
+    Set(LoggedTimecode),
+    /// `<prefix>/start`
+    Start,
+    /// `<prefix>/stop`
+    Stop,
+}
+
+impl OscCommand {
+    pub fn from_message(prefix: &str, message: &OscMessage) -> Option<Self> {
+        match message.address.strip_prefix(prefix)? {
+            "/start" => Some(OscCommand::Start),
+            "/stop" => Some(OscCommand::Stop),
+            "/set" => {
+                let fields: Vec<u8> = match &message.args[..] {
+                    [OscArg::String(s)] => s
+                        .split([':', ';', '.'])
+                        .map(|field| field.parse().ok())
+                        .collect::<Option<_>>()?,
+                    args => args
+                        .iter()
+                        .map(|arg| u8::try_from(arg.as_int()?).ok())
+                        .collect::<Option<_>>()?,
+                };
+                let [hours, minutes, seconds, frame] = fields[..] else {
+                    return None;
+                };
+                if hours > 23 || minutes > 59 || seconds > 59 {
+                    return None;
+                }
+                let drop_frame =
+                    matches!(&message.args[..], [OscArg::String(s)] if s.contains(';'));
+                Some(OscCommand::Set(LoggedTimecode {
+                    hours,
+                    minutes,
+                    seconds,
+                    frame,
+                    drop_frame,
+                }))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An `LTCEncoder` controlled by OSC commands received on a UDP socket
+#[derive(Debug)]
+pub struct OscGenerator {
+    socket: UdpSocket,
+    prefix: String,
+    encoder: LTCEncoder,
+    running: bool,
+}
+
+impl OscGenerator {
+    /// Listens on `addr` for `<prefix>/set`, `<prefix>/start` and `<prefix>/stop`.
+    /// The generator starts stopped.
+    pub fn bind(
+        addr: impl ToSocketAddrs,
+        prefix: &str,
+        encoder: LTCEncoder,
+    ) -> Result<Self, NetError> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_nonblocking(true)?;
+        Ok(OscGenerator {
+            socket,
+            prefix: prefix.trim_end_matches('/').to_string(),
+            encoder,
+            running: false,
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn encoder(&self) -> &LTCEncoder {
+        &self.encoder
+    }
+
+    pub fn encoder_mut(&mut self) -> &mut LTCEncoder {
+        &mut self.encoder
+    }
+
+    pub fn apply(&mut self, command: &OscCommand) {
+        match command {
+            OscCommand::Set(timecode) => {
+                // Keep the date and timezone the encoder was set up with
+                let current = self.encoder.get_timecode();
+                self.encoder.set_timecode(&SMPTETimecode::new(
+                    current.timezone(),
+                    current.years(),
+                    current.months(),
+                    current.days(),
+                    timecode.hours,
+                    timecode.minutes,
+                    timecode.seconds,
+                    timecode.frame,
+                ));
+                // "HH:MM:SS;FF" switches to drop frame counting, anything else back to non-drop
+                let mut frame = self.encoder.get_frame();
+                frame.set_dfbit(timecode.drop_frame);
+                if !self.encoder.flags().contains(LtcBgFlagsKind::LTC_NO_PARITY) {
+                    frame.set_parity(self.encoder.standard());
+                }
+                self.encoder.set_frame(&frame);
+            }
+            OscCommand::Start => self.running = true,
+            OscCommand::Stop => self.running = false,
+        }
+    }
+
+    /// Applies all pending commands without blocking and returns them
+    pub fn poll(&mut self) -> Result<Vec<OscCommand>, NetError> {
+        let mut buf = [0; 1024];
+        let mut commands = vec![];
+        loop {
+            let len = match self.socket.recv_from(&mut buf) {
+                Ok((len, _)) => len,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(commands),
+                Err(e) => return Err(e.into()),
+            };
+            let Ok(message) = OscMessage::from_bytes(&buf[..len]) else {
+                continue;
+            };
+            if let Some(command) = OscCommand::from_message(&self.prefix, &message) {
+                self.apply(&command);
+                commands.push(command);
+            }
+        }
+    }
+
+    /// Encodes the current frame and advances the timecode if running,
+    /// returns the samples or `None` when stopped
+    pub fn next_frame(&mut self) -> Result<Option<&[u8]>, LTCEncoderError> {
+        if !self.running {
+            return Ok(None);
+        }
+        self.encoder.encode_frame();
+        self.encoder.inc_timecode()?;
+        Ok(Some(self.encoder.get_buf_ref(true).0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::encoder::LTCEncoderConfig;
+    use crate::api::{LTCTVStandard, Timezone};
+    use std::time::{Duration, Instant};
+
+    fn timecode(hours: u8, minutes: u8, seconds: u8, frame: u8) -> SMPTETimecode {
+        SMPTETimecode::new(Timezone::default(), 0, 0, 0, hours, minutes, seconds, frame)
+    }
+
+    fn logged(hours: u8, minutes: u8, seconds: u8, frame: u8) -> LoggedTimecode {
+        LoggedTimecode::from(&timecode(hours, minutes, seconds, frame))
+    }
+
+    #[test]
+    fn test_message_roundtrip() {
+        let message = OscMessage::new(
+            "/ltc/tc",
+            vec![
+                OscArg::Int(-3),
+                OscArg::Float(29.97),
+                OscArg::String("abc".to_string()),
+            ],
+        );
+        let bytes = message.to_bytes();
+        assert_eq!(&bytes[..16], b"/ltc/tc\0,ifs\0\0\0\0");
+        assert_eq!(bytes.len() % 4, 0);
+        assert_eq!(OscMessage::from_bytes(&bytes).unwrap(), message);
+        assert!(OscMessage::from_bytes(&bytes[..18]).is_err());
+
+        let set = OscMessage::new("/gen/set", vec![OscArg::String("01:02:03:04".into())]);
+        assert_eq!(
+            OscCommand::from_message("/gen", &set),
+            Some(OscCommand::Set(logged(1, 2, 3, 4)))
+        );
+        assert_eq!(OscCommand::from_message("/other", &set), None);
+    }
+
+    #[test]
+    fn test_drop_frame() {
+        let config = LTCEncoderConfig {
+            fps: 29.97,
+            standard: LTCTVStandard::LTCTV_525_60,
+            ..LTCEncoderConfig::default()
+        };
+        let encoder = LTCEncoder::try_new(&config).unwrap();
+        let mut generator = OscGenerator::bind("127.0.0.1:0", "/gen", encoder).unwrap();
+        let set = |s: &str| OscMessage::new("/gen/set", vec![OscArg::String(s.into())]);
+
+        let command = OscCommand::from_message("/gen", &set("00:00:59;29")).unwrap();
+        generator.apply(&command);
+        let frame = generator.encoder().get_frame();
+        assert_eq!(frame.dfbit(), 1);
+        assert!(frame.parity_ok());
+        // Drop frame counting skips frames 0 and 1 of the next minute
+        generator.encoder_mut().inc_timecode().unwrap();
+        let timecode = generator.encoder().get_timecode();
+        assert_eq!((timecode.minutes(), timecode.frame()), (1, 2));
+
+        let command = OscCommand::from_message("/gen", &set("00:00:59:29")).unwrap();
+        generator.apply(&command);
+        assert_eq!(generator.encoder().get_frame().dfbit(), 0);
+        generator.encoder_mut().inc_timecode().unwrap();
+        assert_eq!(generator.encoder().get_timecode().frame(), 0);
+    }
+
+    #[test]
+    fn test_loopback() {
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        receiver
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let sender =
+            OscTimecodeSender::new(receiver.local_addr().unwrap(), "/ltc/timecode", 25.0).unwrap();
+        sender.send(&timecode(10, 0, 1, 2), true).unwrap();
+        let mut buf = [0; 256];
+        let (len, _) = receiver.recv_from(&mut buf).unwrap();
+        let message = OscMessage::from_bytes(&buf[..len]).unwrap();
+        assert_eq!(message, sender.message(&timecode(10, 0, 1, 2), true));
+
+        let encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let mut generator = OscGenerator::bind("127.0.0.1:0", "/gen/", encoder).unwrap();
+        assert!(generator.next_frame().unwrap().is_none());
+        let target = generator.local_addr().unwrap();
+        for message in [
+            OscMessage::new("/gen/set", (1..5).map(OscArg::Int).collect()),
+            OscMessage::new("/gen/start", vec![]),
+        ] {
+            receiver.send_to(&message.to_bytes(), target).unwrap();
+        }
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let mut commands = vec![];
+        while commands.len() < 2 && Instant::now() < deadline {
+            commands.extend(generator.poll().unwrap());
+        }
+        assert_eq!(
+            commands,
+            vec![OscCommand::Set(logged(1, 2, 3, 4)), OscCommand::Start]
+        );
+        assert!(generator.is_running());
+        assert!(generator.next_frame().unwrap().is_some());
+        assert_eq!(generator.encoder().get_timecode().frame(), 5);
+    }
+}
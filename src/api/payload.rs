@@ -0,0 +1,330 @@
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use super::frame::LTCFrameExt;
+use crate::error::PayloadError;
+
+// Every frame carries one 32 bit word in its user bits:
+//
+//   bits 31..28  kind (0 = idle)
+//   bits 27..24  message id (start) or sequence number (data, end)
+//   bits 23..0   kind specific
+//
+// START: payload length in bits 23..8 and the sync pattern in bits 7..0
+// DATA:  three payload bytes, the first one in bits 23..16. The last word is zero padded
+// END:   CRC-16 of the payload in bits 23..8 and the message id in bits 3..0
+//
+// Data and end words are numbered (mod 16) so the reassembler notices dropped frames.
+const KIND_IDLE: u32 = 0x0;
+const KIND_START: u32 = 0x1;
+const KIND_DATA: u32 = 0x2;
+const KIND_END: u32 = 0x3;
+const START_SYNC: u32 = 0xa5;
+const BYTES_PER_WORD: usize = 3;
+
+pub const PAYLOAD_MAX_LEN: usize = u16::MAX as usize;
+
+/// The user bits of a frame that carries no payload
+pub const PAYLOAD_IDLE_WORD: u32 = KIND_IDLE << 28;
+
+/// Splits byte payloads into a sequence of user-bit words, one per LTC frame.
+///
+/// ```ignore
+/// framer.push(b"scene 12 take 3")?;
+/// loop {
+///     encoder.set_user_bits(framer.next_word());
+///     encoder.encode_frame();
+///     ...
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct PayloadFramer {
+    words: VecDeque<u32>,
+    next_id: u8,
+}
+
+impl PayloadFramer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a payload. It takes `2 + ceil(len / 3)` frames to transmit.
+    pub fn push(&mut self, payload: &[u8]) -> Result<(), PayloadError> {
+        if payload.len() > PAYLOAD_MAX_LEN {
+            return Err(PayloadError::TooLong(payload.len()));
+        }
+        let id = self.next_id as u32;
+        self.next_id = (self.next_id + 1) & 0xf;
+
+        self.words.push_back(word(
+            KIND_START,
+            id,
+            ((payload.len() as u32) << 8) | START_SYNC,
+        ));
+
+        let mut seq = 0;
+        for chunk in payload.chunks(BYTES_PER_WORD) {
+            let mut data = [0u8; 4];
+            data[1..1 + chunk.len()].copy_from_slice(chunk);
+            self.words
+                .push_back(word(KIND_DATA, seq, u32::from_be_bytes(data)));
+            seq = (seq + 1) & 0xf;
+        }
+
+        self.words
+            .push_back(word(KIND_END, seq, ((crc16(payload) as u32) << 8) | id));
+        Ok(())
+    }
+
+    /// The user bits for the next frame, `PAYLOAD_IDLE_WORD` when nothing is queued
+    pub fn next_word(&mut self) -> u32 {
+        self.words.pop_front().unwrap_or(PAYLOAD_IDLE_WORD)
+    }
+
+    /// Number of frames needed to transmit everything queued so far
+    pub fn pending_frames(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_idle(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct PartialPayload {
+    id: u32,
+    len: usize,
+    seq: u32,
+    data: Vec<u8>,
+}
+
+/// Rebuilds payloads from the user bits of consecutive decoded frames.
+///
+/// A missing or repeated frame, or a checksum mismatch, discards the payload being received
+/// and the reassembler waits for the next start word.
+#[derive(Debug, Default)]
+pub struct PayloadReassembler {
+    current: Option<PartialPayload>,
+    completed: usize,
+    dropped: usize,
+    checksum_errors: usize,
+}
+
+impl PayloadReassembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds the user bits of the next frame. Returns a payload once its end word is received.
+    pub fn push(&mut self, user_bits: u32) -> Option<Vec<u8>> {
+        let kind = user_bits >> 28;
+        let tag = (user_bits >> 24) & 0xf;
+        let data = user_bits & 0x00ff_ffff;
+
+        match kind {
+            KIND_START if data & 0xff == START_SYNC => {
+                self.abandon();
+                let len = (data >> 8) as usize;
+                self.current = Some(PartialPayload {
+                    id: tag,
+                    len,
+                    seq: 0,
+                    data: Vec::with_capacity(len),
+                });
+                None
+            }
+            KIND_DATA => {
+                let partial = self.current.as_mut()?;
+                let remaining = partial.len - partial.data.len();
+                if tag != partial.seq || remaining == 0 {
+                    self.abandon();
+                    return None;
+                }
+                let bytes = data.to_be_bytes();
+                let n = remaining.min(BYTES_PER_WORD);
+                partial.data.extend_from_slice(&bytes[1..1 + n]);
+                partial.seq = (partial.seq + 1) & 0xf;
+                None
+            }
+            KIND_END => {
+                let partial = self.current.take()?;
+                if tag != partial.seq
+                    || data & 0xf != partial.id
+                    || partial.data.len() != partial.len
+                {
+                    self.dropped += 1;
+                    return None;
+                }
+                if crc16(&partial.data) as u32 != data >> 8 {
+                    self.checksum_errors += 1;
+                    return None;
+                }
+                self.completed += 1;
+                Some(partial.data)
+            }
+            _ => {
+                // An idle word or garbage in the middle of a payload means frames were lost
+                self.abandon();
+                None
+            }
+        }
+    }
+
+    /// Feeds a decoded frame. Frames decoded in reverse are ignored, they arrive out of order.
+    pub fn push_frame(&mut self, frame: &LTCFrameExt) -> Option<Vec<u8>> {
+        if frame.reverse() {
+            return None;
+        }
+        self.push(frame.ltc().get_user_bits())
+    }
+
+    /// Discards the payload being received, e.g. after a discontinuity in the timecode
+    pub fn reset(&mut self) {
+        self.abandon();
+    }
+
+    pub fn is_receiving(&self) -> bool {
+        self.current.is_some()
+    }
+
+    pub fn completed(&self) -> usize {
+        self.completed
+    }
+
+    /// Payloads that were discarded because frames went missing
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    pub fn checksum_errors(&self) -> usize {
+        self.checksum_errors
+    }
+
+    fn abandon(&mut self) {
+        if self.current.take().is_some() {
+            self.dropped += 1;
+        }
+    }
+}
+
+fn word(kind: u32, tag: u32, data: u32) -> u32 {
+    (kind << 28) | ((tag & 0xf) << 24) | (data & 0x00ff_ffff)
+}
+
+// CRC-16/CCITT-FALSE
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xffff, |mut crc, &byte| {
+        crc ^= (byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decoder::{LTCDecoder, LTCDecoderConfig};
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+    use alloc::vec;
+
+    fn transmit(framer: &mut PayloadFramer, reassembler: &mut PayloadReassembler) -> Vec<Vec<u8>> {
+        let mut received = vec![];
+        while !framer.is_idle() {
+            if let Some(payload) = reassembler.push(framer.next_word()) {
+                received.push(payload);
+            }
+        }
+        received
+    }
+
+    #[test]
+    fn test_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x29b1);
+    }
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let mut framer = PayloadFramer::new();
+        let mut reassembler = PayloadReassembler::new();
+        let payloads: [&[u8]; 4] = [b"", b"a", b"scene 12 take 3", &[0xff; 300]];
+        for payload in payloads {
+            framer.push(payload).unwrap();
+        }
+        assert_eq!(framer.pending_frames(), 4 * 2 + 1 + 5 + 100);
+
+        let received = transmit(&mut framer, &mut reassembler);
+        assert_eq!(received, payloads);
+        assert_eq!(reassembler.completed(), 4);
+        assert_eq!(reassembler.dropped(), 0);
+        assert_eq!(framer.next_word(), PAYLOAD_IDLE_WORD);
+        assert!(framer.push(&vec![0; PAYLOAD_MAX_LEN + 1]).is_err());
+    }
+
+    #[test]
+    fn test_payload_dropped_frame() {
+        let mut framer = PayloadFramer::new();
+        let mut reassembler = PayloadReassembler::new();
+        framer.push(b"this one loses a frame").unwrap();
+        framer.push(b"this one arrives").unwrap();
+
+        // Lose the second data word of the first payload
+        reassembler.push(framer.next_word());
+        reassembler.push(framer.next_word());
+        framer.next_word();
+
+        let received = transmit(&mut framer, &mut reassembler);
+        assert_eq!(received, vec![b"this one arrives".to_vec()]);
+        assert_eq!(reassembler.dropped(), 1);
+        assert!(!reassembler.is_receiving());
+    }
+
+    #[test]
+    fn test_payload_checksum() {
+        let mut framer = PayloadFramer::new();
+        let mut reassembler = PayloadReassembler::new();
+        framer.push(b"abc").unwrap();
+
+        reassembler.push(framer.next_word());
+        reassembler.push(framer.next_word() ^ 0x1);
+        assert_eq!(reassembler.push(framer.next_word()), None);
+        assert_eq!(reassembler.checksum_errors(), 1);
+    }
+
+    #[test]
+    fn test_payload_through_ltc() {
+        let config = LTCEncoderConfig::default();
+        let mut encoder = LTCEncoder::try_new(&config).unwrap();
+        let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        let mut framer = PayloadFramer::new();
+        let mut reassembler = PayloadReassembler::new();
+        framer.push(b"reel A001 scene 4 take 2").unwrap();
+
+        let mut received = vec![];
+        let mut pos = 0;
+        // A couple of idle frames first so the decoder can lock on
+        for i in 0..framer.pending_frames() + 4 {
+            encoder.set_user_bits(if i < 2 {
+                PAYLOAD_IDLE_WORD
+            } else {
+                framer.next_word()
+            });
+            encoder.encode_frame();
+            let (buf, len) = encoder.get_buf_ref(true);
+            decoder.write(buf, pos);
+            pos += len as i64;
+            encoder.inc_timecode().unwrap();
+
+            while let Some(frame) = decoder.read() {
+                received.extend(reassembler.push_frame(&frame));
+            }
+        }
+        assert_eq!(received, vec![b"reel A001 scene 4 take 2".to_vec()]);
+    }
+}
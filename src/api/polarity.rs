@@ -0,0 +1,286 @@
+use alloc::collections::VecDeque;
+
+use super::consts::SampleType;
+use super::frame::LTCFrameExt;
+#[cfg(not(feature = "std"))]
+use crate::math::F64Ext;
+
+/// Polarity of an LTC signal. With a correct parity bit every frame starts with the same edge,
+/// libltc (and most generators) use a rising edge at the start of bit 0.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Polarity {
+    /// Rising edge at the start of each frame
+    #[default]
+    Normal,
+    /// Falling edge at the start of each frame, e.g. after a mis-wired balanced connection
+    Inverted,
+}
+
+impl Polarity {
+    /// Inverts 8-bit samples around 128, the centre of the encoder output
+    pub fn apply(&self, buf: &mut [SampleType]) {
+        if *self == Polarity::Inverted {
+            for sample in buf.iter_mut() {
+                *sample = (256 - *sample as i16).min(255) as SampleType;
+            }
+        }
+    }
+}
+
+/// Converts 8-bit encoder samples to floats centred at zero, in the range [-1, 1]
+pub fn samples_to_f32(src: &[SampleType], dst: &mut [f32]) {
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d = (s as f32 - 128.0) / 127.0;
+    }
+}
+
+/// Converts 8-bit encoder samples to signed 16-bit samples centred at zero
+pub fn samples_to_i16(src: &[SampleType], dst: &mut [i16]) {
+    for (d, &s) in dst.iter_mut().zip(src) {
+        *d = ((s as i16 - 128) << 8).max(-i16::MAX);
+    }
+}
+
+// The same conversions libltc applies before decoding
+pub(crate) fn f64_to_sample(x: f64) -> SampleType {
+    (128.0 + x * 127.0).clamp(0.0, 255.0) as SampleType
+}
+
+pub(crate) fn i16_to_sample(x: i16) -> SampleType {
+    ((x >> 8) + 128) as SampleType
+}
+
+pub(crate) fn u16_to_sample(x: u16) -> SampleType {
+    (x >> 8) as SampleType
+}
+
+// Bits 64 to 79 of a frame, bit 64 in the least significant bit
+const SYNC_WORD: u16 = 0xbffc;
+
+/// The most recent input of a decoder, so decoded frames can be matched to the waveform.
+/// Sample positions are the `posinfo` passed to `LTCDecoder::write`.
+#[derive(Debug)]
+pub(crate) struct SampleHistory {
+    samples: VecDeque<SampleType>,
+    start: i64,
+    capacity: usize,
+}
+
+impl SampleHistory {
+    pub(crate) fn new(capacity: usize) -> Self {
+        SampleHistory {
+            samples: VecDeque::with_capacity(capacity),
+            start: 0,
+            capacity,
+        }
+    }
+
+    pub(crate) fn extend(&mut self, samples: impl ExactSizeIterator<Item = SampleType>, pos: i64) {
+        if pos != self.start + self.samples.len() as i64 {
+            // Not contiguous with what we have
+            self.samples.clear();
+            self.start = pos;
+        }
+        // Make room first, so the deque never grows past its initial capacity
+        let skip = samples.len().saturating_sub(self.capacity);
+        let excess = (self.samples.len() + samples.len() - skip).saturating_sub(self.capacity);
+        self.samples.drain(..excess);
+        self.start += (excess + skip) as i64;
+        self.samples.extend(samples.skip(skip));
+    }
+
+    fn get(&self, pos: i64) -> Option<SampleType> {
+        let index = usize::try_from(pos - self.start).ok()?;
+        self.samples.get(index).copied()
+    }
+
+    /// Compares the waveform of the sync word (bits 64 to 79) with the levels it has with
+    /// `Polarity::Normal`, whose last half bit is low. The bit boundaries are walked from the
+    /// end of the frame with `biphase_tics`, or from its start when playing in reverse, where
+    /// the sync word comes first in time. Only the middle half of every half bit is used, so
+    /// the edges don't count, and all of them are summed, so noise averages out.
+    pub(crate) fn detect_polarity(&self, frame: &LTCFrameExt) -> Option<Polarity> {
+        let reverse = frame.reverse();
+        let tics = frame.biphase_tics();
+
+        // The half bits moving away from the end of the sync word: where they start, their
+        // length and their level with `Polarity::Normal`
+        let mut half_bits = [(0.0, 0.0, 0.0); 32];
+        let mut start = 0.0;
+        let mut level = -1.0;
+        for (i, bit) in (64..80).rev().enumerate() {
+            // The tics are in the order received, with the sync word at the end. In reverse
+            // they run 16 bits past the frame, take the ones next to the sync word.
+            let tic = if reverse { tics[i] } else { tics[bit] } as f64;
+            if tic <= 0.0 {
+                return None;
+            }
+            for half_bit in 0..2 {
+                half_bits[2 * i + half_bit] = (start, tic / 2.0, level);
+                start += tic / 2.0;
+                // A one flips in the middle, every bit at its start
+                if half_bit == 1 || SYNC_WORD >> (bit - 64) & 1 == 1 {
+                    level = -level;
+                }
+            }
+        }
+
+        // A frame is about as long high as low, its mean is the midpoint between the levels
+        let (sum, count) = (frame.off_start()..=frame.off_end())
+            .filter_map(|pos| self.get(pos))
+            .fold((0.0, 0), |(sum, count), sample| {
+                (sum + sample as f64, count + 1)
+            });
+        if count == 0 {
+            return None;
+        }
+        let mean = sum / count as f64;
+
+        let (end, dir) = if reverse {
+            (frame.off_start() as f64, 1.0)
+        } else {
+            (frame.off_end() as f64 + 1.0, -1.0)
+        };
+        let correlation = |shift: i64| {
+            let mut sum = 0.0;
+            for &(start, len, level) in &half_bits {
+                let a = end + shift as f64 + dir * (start + len / 4.0);
+                let b = end + shift as f64 + dir * (start + len * 3.0 / 4.0);
+                for pos in a.min(b).ceil() as i64..a.max(b).ceil() as i64 {
+                    sum += level * (self.get(pos)? as f64 - mean);
+                }
+            }
+            Some(sum)
+        };
+        // With noise the frame offsets can be off by a bit or two. Take the best match around
+        // them, any other alignment of the sync word matches at most 26 of its 32 half bits.
+        let range = (half_bits[0].1 * 4.0).round() as i64;
+        let best = (-range..=range)
+            .filter_map(correlation)
+            .max_by(|a, b| a.abs().total_cmp(&b.abs()))?;
+        if best > 0.0 {
+            Some(Polarity::Normal)
+        } else if best < 0.0 {
+            Some(Polarity::Inverted)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::decoder::{LTCDecoder, LTCDecoderConfig};
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+    use crate::api::filter::EncoderFilter;
+    use crate::api::test_util::encode_with;
+    use crate::api::SMPTETimecode;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    fn encode(polarity: Polarity, frames: usize) -> Vec<SampleType> {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        // Sets the parity of the first frame
+        encoder.set_timecode(&SMPTETimecode::default());
+        encoder.set_polarity(polarity);
+        encode_with(&mut encoder, frames)
+    }
+
+    #[test]
+    fn test_inverted_output() {
+        let normal = encode(Polarity::Normal, 3);
+        let inverted = encode(Polarity::Inverted, 3);
+        assert_eq!(normal.len(), inverted.len());
+        for (n, i) in normal.iter().zip(&inverted) {
+            assert_eq!(*n as i16 - 128, 128 - *i as i16);
+        }
+    }
+
+    #[test]
+    fn test_centred_output() {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let mut buf = vec![0.0; encoder.get_buffersize()];
+        let mut samples = vec![];
+        for _ in 0..25 {
+            encoder.encode_frame();
+            let n = encoder.copy_buffer_f32(&mut buf);
+            samples.extend_from_slice(&buf[..n]);
+            encoder.inc_timecode().unwrap();
+        }
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 0.01);
+        assert!(samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+    }
+
+    #[test]
+    fn test_detect_polarity() {
+        for polarity in [Polarity::Normal, Polarity::Inverted] {
+            let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+            decoder.track_polarity(true);
+            let samples = encode(polarity, 10);
+            let mut frames = 0;
+            for (i, chunk) in samples.chunks(1000).enumerate() {
+                decoder.write(chunk, i as i64 * 1000);
+                while let Some(frame) = decoder.read() {
+                    assert_eq!(decoder.detect_polarity(&frame), Some(polarity));
+                    frames += 1;
+                }
+            }
+            assert!(frames >= 8);
+        }
+    }
+
+    // Adds deterministic, roughly gaussian noise: the sum of four uniform values of up to
+    // +/- `amplitude`
+    fn add_noise(samples: &mut [SampleType], amplitude: u32, seed: u32) {
+        let mut state = seed;
+        let mut uniform = || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state % (2 * amplitude + 1)) as i32 - amplitude as i32
+        };
+        for sample in samples.iter_mut() {
+            let noise = (0..4).map(|_| uniform()).sum::<i32>();
+            *sample = (*sample as i32 + noise).clamp(0, 255) as SampleType;
+        }
+    }
+
+    #[test]
+    fn test_detect_polarity_noisy() {
+        // Noise with a standard deviation of about 30% of the level, which puts single samples
+        // on the wrong side of the midpoint and makes the frame offsets unreliable
+        for seed in 1..=8 {
+            for reverse in [false, true] {
+                for polarity in [Polarity::Normal, Polarity::Inverted] {
+                    let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+                    encoder.set_timecode(&SMPTETimecode::default());
+                    encoder.set_polarity(polarity);
+                    encoder.set_filter_preset(EncoderFilter::Standard);
+                    let mut samples = encode_with(&mut encoder, 25);
+                    if reverse {
+                        // A recording played backwards
+                        samples.reverse();
+                    }
+                    add_noise(&mut samples, 22, seed);
+
+                    let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+                    decoder.track_polarity(true);
+                    decoder.write(&samples, 0);
+                    let mut frames = 0;
+                    while let Some(frame) = decoder.read() {
+                        assert_eq!(frame.reverse(), reverse);
+                        assert_eq!(
+                            decoder.detect_polarity(&frame),
+                            Some(polarity),
+                            "seed {seed}"
+                        );
+                        frames += 1;
+                    }
+                    assert!(frames >= 20, "{frames} frames");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,497 @@
+// serde support for the types that wrap raw pointers or bindgen bitfields. Text formats get
+// timecodes and timezones as strings, binary formats plain fields.
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use super::frame::{LTCFrame, LTCFrameExt};
+use super::{SMPTETimecode, Timezone};
+use crate::raw;
+
+impl Serialize for Timezone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&timezone_string(self))
+        } else {
+            self.to_bytes().serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Timezone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            parse_timezone(&s).ok_or_else(|| de::Error::custom(format!("invalid timezone '{s}'")))
+        } else {
+            let raw = <[u8; 6]>::deserialize(deserializer)?;
+            let timezone = Timezone::from(raw);
+            // The string has to be NUL terminated, anything after the NUL is ignored
+            if !raw.contains(&0) || parse_timezone(&timezone_string(&timezone)).is_none() {
+                return Err(de::Error::invalid_value(
+                    de::Unexpected::Bytes(&raw),
+                    &"a NUL terminated timezone like +0100",
+                ));
+            }
+            Ok(timezone)
+        }
+    }
+}
+
+// The timezone up to the terminating NUL, e.g. "+0100"
+fn timezone_string(timezone: &Timezone) -> String {
+    timezone
+        .to_bytes()
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as char)
+        .collect()
+}
+
+// "+HHMM" or "-HHMM", as in libltc's timezone table
+fn parse_timezone(s: &str) -> Option<Timezone> {
+    let (sign, digits) = s.split_at_checked(1)?;
+    if !matches!(sign, "+" | "-")
+        || digits.len() != 4
+        || !digits.bytes().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let (hours, minutes) = digits.split_at(2);
+    if hours.parse::<u8>().ok()? > 14 || minutes.parse::<u8>().ok()? > 59 {
+        return None;
+    }
+    let mut raw = [0u8; 6];
+    raw[..s.len()].copy_from_slice(s.as_bytes());
+    Some(Timezone::from(raw))
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "SMPTETimecode")]
+struct TimecodeFields {
+    timezone: Timezone,
+    years: u8,
+    months: u8,
+    days: u8,
+    hours: u8,
+    minutes: u8,
+    seconds: u8,
+    frame: u8,
+}
+
+/// "HH:MM:SS:FF" in text formats, "YY-MM-DD HH:MM:SS:FF +HHMM" when a date or timezone is set
+impl Serialize for SMPTETimecode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let time = format!(
+                "{:02}:{:02}:{:02}:{:02}",
+                self.hours(),
+                self.minutes(),
+                self.seconds(),
+                self.frame()
+            );
+            let has_date = (self.years(), self.months(), self.days()) != (0, 0, 0);
+            if has_date || self.timezone() != Timezone::default() {
+                serializer.serialize_str(&format!(
+                    "{:02}-{:02}-{:02} {time} {}",
+                    self.years(),
+                    self.months(),
+                    self.days(),
+                    timezone_string(&self.timezone())
+                ))
+            } else {
+                serializer.serialize_str(&time)
+            }
+        } else {
+            TimecodeFields {
+                timezone: self.timezone(),
+                years: self.years(),
+                months: self.months(),
+                days: self.days(),
+                hours: self.hours(),
+                minutes: self.minutes(),
+                seconds: self.seconds(),
+                frame: self.frame(),
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SMPTETimecode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = if deserializer.is_human_readable() {
+            let s = String::deserialize(deserializer)?;
+            parse_timecode(&s)
+                .ok_or_else(|| de::Error::custom(format!("invalid timecode '{s}'")))?
+        } else {
+            TimecodeFields::deserialize(deserializer)?
+        };
+        fields.check()?;
+        Ok(SMPTETimecode::new(
+            fields.timezone,
+            fields.years,
+            fields.months,
+            fields.days,
+            fields.hours,
+            fields.minutes,
+            fields.seconds,
+            fields.frame,
+        ))
+    }
+}
+
+impl TimecodeFields {
+    // Larger values don't fit in the BCD digits of a frame and would be truncated when encoded
+    fn check<E: de::Error>(&self) -> Result<(), E> {
+        check_field(self.years, 99, "years")?;
+        check_field(self.months, 12, "months")?;
+        check_field(self.days, 31, "days")?;
+        check_field(self.hours, 23, "hours")?;
+        check_field(self.minutes, 59, "minutes")?;
+        check_field(self.seconds, 59, "seconds")?;
+        check_field(self.frame, 29, "frame")
+    }
+}
+
+fn check_field<E: de::Error>(value: u8, max: u8, name: &str) -> Result<(), E> {
+    if value > max {
+        return Err(E::custom(format!("{name} out of range: {value} > {max}")));
+    }
+    Ok(())
+}
+
+fn parse_fields<const N: usize>(s: &str, separator: char) -> Option<[u8; N]> {
+    let fields: Vec<u8> = s
+        .split(separator)
+        .map(|field| field.parse().ok())
+        .collect::<Option<_>>()?;
+    fields.try_into().ok()
+}
+
+fn parse_timecode(s: &str) -> Option<TimecodeFields> {
+    let parts: Vec<&str> = s.split_whitespace().collect();
+    let (date, time, timezone) = match parts[..] {
+        [time] => ([0; 3], time, Timezone::default()),
+        [date, time, timezone] => (parse_fields(date, '-')?, time, parse_timezone(timezone)?),
+        _ => return None,
+    };
+    let [hours, minutes, seconds, frame] = parse_fields(time, ':')?;
+    let [years, months, days] = date;
+    Some(TimecodeFields {
+        timezone,
+        years,
+        months,
+        days,
+        hours,
+        minutes,
+        seconds,
+        frame,
+    })
+}
+
+// The fields of the 80 bit frame, in transmission order
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "LTCFrame")]
+struct FrameFields {
+    frame_units: u8,
+    user1: u8,
+    frame_tens: u8,
+    dfbit: u8,
+    col_frame: u8,
+    user2: u8,
+    secs_units: u8,
+    user3: u8,
+    secs_tens: u8,
+    biphase_mark_phase_correction: u8,
+    user4: u8,
+    mins_units: u8,
+    user5: u8,
+    mins_tens: u8,
+    binary_group_flag_bit0: u8,
+    user6: u8,
+    hours_units: u8,
+    user7: u8,
+    hours_tens: u8,
+    binary_group_flag_bit1: u8,
+    binary_group_flag_bit2: u8,
+    user8: u8,
+    sync_word: u16,
+}
+
+impl FrameFields {
+    // Values wider than their bit field would be truncated by the setters
+    fn check<E: de::Error>(&self) -> Result<(), E> {
+        let widths = [
+            (self.frame_units, 4, "frame_units"),
+            (self.user1, 4, "user1"),
+            (self.frame_tens, 2, "frame_tens"),
+            (self.dfbit, 1, "dfbit"),
+            (self.col_frame, 1, "col_frame"),
+            (self.user2, 4, "user2"),
+            (self.secs_units, 4, "secs_units"),
+            (self.user3, 4, "user3"),
+            (self.secs_tens, 3, "secs_tens"),
+            (
+                self.biphase_mark_phase_correction,
+                1,
+                "biphase_mark_phase_correction",
+            ),
+            (self.user4, 4, "user4"),
+            (self.mins_units, 4, "mins_units"),
+            (self.user5, 4, "user5"),
+            (self.mins_tens, 3, "mins_tens"),
+            (self.binary_group_flag_bit0, 1, "binary_group_flag_bit0"),
+            (self.user6, 4, "user6"),
+            (self.hours_units, 4, "hours_units"),
+            (self.user7, 4, "user7"),
+            (self.hours_tens, 2, "hours_tens"),
+            (self.binary_group_flag_bit1, 1, "binary_group_flag_bit1"),
+            (self.binary_group_flag_bit2, 1, "binary_group_flag_bit2"),
+            (self.user8, 4, "user8"),
+        ];
+        for (value, bits, name) in widths {
+            check_field(value, (1 << bits) - 1, name)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for LTCFrame {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let f = &self.inner_raw;
+        FrameFields {
+            frame_units: f.frame_units() as u8,
+            user1: f.user1() as u8,
+            frame_tens: f.frame_tens() as u8,
+            dfbit: f.dfbit() as u8,
+            col_frame: f.col_frame() as u8,
+            user2: f.user2() as u8,
+            secs_units: f.secs_units() as u8,
+            user3: f.user3() as u8,
+            secs_tens: f.secs_tens() as u8,
+            biphase_mark_phase_correction: f.biphase_mark_phase_correction() as u8,
+            user4: f.user4() as u8,
+            mins_units: f.mins_units() as u8,
+            user5: f.user5() as u8,
+            mins_tens: f.mins_tens() as u8,
+            binary_group_flag_bit0: f.binary_group_flag_bit0() as u8,
+            user6: f.user6() as u8,
+            hours_units: f.hours_units() as u8,
+            user7: f.user7() as u8,
+            hours_tens: f.hours_tens() as u8,
+            binary_group_flag_bit1: f.binary_group_flag_bit1() as u8,
+            binary_group_flag_bit2: f.binary_group_flag_bit2() as u8,
+            user8: f.user8() as u8,
+            sync_word: f.sync_word() as u16,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LTCFrame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = FrameFields::deserialize(deserializer)?;
+        fields.check()?;
+        let mut f = raw::LTCFrame::default();
+        f.set_frame_units(fields.frame_units as u32);
+        f.set_user1(fields.user1 as u32);
+        f.set_frame_tens(fields.frame_tens as u32);
+        f.set_dfbit(fields.dfbit as u32);
+        f.set_col_frame(fields.col_frame as u32);
+        f.set_user2(fields.user2 as u32);
+        f.set_secs_units(fields.secs_units as u32);
+        f.set_user3(fields.user3 as u32);
+        f.set_secs_tens(fields.secs_tens as u32);
+        f.set_biphase_mark_phase_correction(fields.biphase_mark_phase_correction as u32);
+        f.set_user4(fields.user4 as u32);
+        f.set_mins_units(fields.mins_units as u32);
+        f.set_user5(fields.user5 as u32);
+        f.set_mins_tens(fields.mins_tens as u32);
+        f.set_binary_group_flag_bit0(fields.binary_group_flag_bit0 as u32);
+        f.set_user6(fields.user6 as u32);
+        f.set_hours_units(fields.hours_units as u32);
+        f.set_user7(fields.user7 as u32);
+        f.set_hours_tens(fields.hours_tens as u32);
+        f.set_binary_group_flag_bit1(fields.binary_group_flag_bit1 as u32);
+        f.set_binary_group_flag_bit2(fields.binary_group_flag_bit2 as u32);
+        f.set_user8(fields.user8 as u32);
+        f.set_sync_word(fields.sync_word as u32);
+        Ok(f.into())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "LTCFrameExt")]
+struct FrameExtFields {
+    ltc: LTCFrame,
+    off_start: i64,
+    off_end: i64,
+    reverse: bool,
+    biphase_tics: Vec<f32>,
+    sample_min: u8,
+    sample_max: u8,
+    volume: f64,
+}
+
+impl Serialize for LTCFrameExt {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        FrameExtFields {
+            ltc: self.ltc(),
+            off_start: self.off_start(),
+            off_end: self.off_end(),
+            reverse: self.reverse(),
+            biphase_tics: self.biphase_tics().to_vec(),
+            sample_min: self.sample_min(),
+            sample_max: self.sample_max(),
+            volume: self.volume(),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for LTCFrameExt {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = FrameExtFields::deserialize(deserializer)?;
+        let len = fields.biphase_tics.len();
+        let biphase_tics: [f32; 80] = fields
+            .biphase_tics
+            .try_into()
+            .map_err(|_| de::Error::invalid_length(len, &"80 biphase tics"))?;
+
+        let mut frame = LTCFrameExt::default();
+        frame.set_ltc(fields.ltc);
+        frame.set_off_start(fields.off_start);
+        frame.set_off_end(fields.off_end);
+        frame.set_reverse(fields.reverse);
+        frame.set_biphase_tics(biphase_tics);
+        frame.set_sample_min(fields.sample_min);
+        frame.set_sample_max(fields.sample_max);
+        frame.set_volume(fields.volume);
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consts::{LtcBgFlags, LtcBgFlagsKind};
+    use crate::api::decoder::LTCDecoderConfig;
+    use crate::api::encoder::LTCEncoderConfig;
+    use crate::api::LTCTVStandard;
+
+    #[test]
+    fn test_timecode_strings() {
+        let tc = SMPTETimecode::new(Timezone::default(), 0, 0, 0, 10, 20, 30, 12);
+        let json = serde_json::to_string(&tc).unwrap();
+        assert_eq!(json, "\"10:20:30:12\"");
+        let back: SMPTETimecode = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.to_string(), tc.to_string());
+
+        let tc = SMPTETimecode::new(Timezone::from(*b"+0200\0"), 24, 5, 1, 23, 59, 59, 24);
+        let json = serde_json::to_string(&tc).unwrap();
+        assert_eq!(json, "\"24-05-01 23:59:59:24 +0200\"");
+        let back: SMPTETimecode = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.to_string(), tc.to_string());
+
+        assert!(serde_json::from_str::<SMPTETimecode>("\"10:20:30\"").is_err());
+        assert!(serde_json::from_str::<Timezone>("\"+020000\"").is_err());
+        assert!(serde_json::from_str::<Timezone>("\"abc\"").is_err());
+        assert!(serde_json::from_str::<Timezone>("\"+0260\"").is_err());
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        for json in [
+            "\"24:00:00:00\"",
+            "\"10:60:00:00\"",
+            "\"10:00:60:00\"",
+            "\"10:00:00:30\"",
+            "\"24-13-01 10:00:00:00 +0000\"",
+            "\"24-01-32 10:00:00:00 +0000\"",
+        ] {
+            assert!(
+                serde_json::from_str::<SMPTETimecode>(json).is_err(),
+                "{json}"
+            );
+        }
+
+        let tc = SMPTETimecode::new(Timezone::default(), 0, 0, 0, 1, 2, 3, 4);
+        let frame =
+            LTCFrame::from_timecode(&tc, LTCTVStandard::LTCTV_625_50, LtcBgFlags::default());
+        let mut value = serde_json::to_value(frame).unwrap();
+        value["frame_tens"] = 4.into();
+        let error = serde_json::from_value::<LTCFrame>(value).unwrap_err();
+        assert!(error.to_string().contains("frame_tens"), "{error}");
+    }
+
+    #[test]
+    fn test_binary() {
+        let tc = SMPTETimecode::new(Timezone::from(*b"-0530\0"), 24, 5, 1, 23, 59, 59, 24);
+        let bytes = bincode::serialize(&tc).unwrap();
+        // Timezone bytes, then the fields as plain integers
+        assert_eq!(bytes.len(), 6 + 7);
+        let back: SMPTETimecode = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back.to_string(), tc.to_string());
+        assert_eq!(back.timezone(), tc.timezone());
+
+        let mut hours = bytes.clone();
+        hours[6 + 3] = 24;
+        assert!(bincode::deserialize::<SMPTETimecode>(&hours).is_err());
+        let mut timezone = bytes.clone();
+        timezone[5] = b'1';
+        assert!(bincode::deserialize::<SMPTETimecode>(&timezone).is_err());
+
+        let frame =
+            LTCFrame::from_timecode(&tc, LTCTVStandard::LTCTV_625_50, LtcBgFlags::default());
+        let back: LTCFrame = bincode::deserialize(&bincode::serialize(&frame).unwrap()).unwrap();
+        assert_eq!(back, frame);
+    }
+
+    #[test]
+    fn test_frame_fields() {
+        let tc = SMPTETimecode::new(Timezone::default(), 0, 0, 0, 1, 2, 3, 4);
+        let mut frame =
+            LTCFrame::from_timecode(&tc, LTCTVStandard::LTCTV_625_50, LtcBgFlags::default());
+        frame.set_user_bits(0x8765_4321);
+        let value = serde_json::to_value(frame).unwrap();
+        assert_eq!(value["hours_units"], 1);
+        assert_eq!(value["frame_units"], 4);
+        assert_eq!(value["user1"], 1);
+        assert_eq!(value["user8"], 8);
+        assert_eq!(value["sync_word"], 0xbffc);
+        let back: LTCFrame = serde_json::from_value(value).unwrap();
+        assert_eq!(back, frame);
+
+        let mut ext = LTCFrameExt::default();
+        ext.set_off_start(100);
+        ext.set_off_end(1999);
+        ext.set_reverse(true);
+        ext.set_volume(-6.0);
+        let json = serde_json::to_string(&ext).unwrap();
+        let back: LTCFrameExt = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&back).unwrap(), json);
+        assert_eq!(back, ext);
+    }
+
+    #[test]
+    fn test_configs() {
+        let config = LTCEncoderConfig {
+            flags: *LtcBgFlags::default().set(LtcBgFlagsKind::LTC_USE_DATE),
+            ..LTCEncoderConfig::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LTCEncoderConfig>(&json).unwrap(),
+            config
+        );
+
+        let config = LTCDecoderConfig::default();
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(
+            serde_json::from_str::<LTCDecoderConfig>(&json).unwrap(),
+            config
+        );
+    }
+}
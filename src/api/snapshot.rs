@@ -0,0 +1,490 @@
+use alloc::vec::Vec;
+
+use super::consts::{LtcBgFlags, SampleType};
+use super::decoder::{LTCDecoder, LTCDecoderConfig};
+use super::encoder::{LTCEncoder, LTCEncoderConfig};
+use super::filter::EncoderFilter;
+use super::frame::LTCFrame;
+use super::polarity::Polarity;
+use super::LTCTVStandard;
+use crate::error::{LTCDecoderError, LTCEncoderError, SnapshotError};
+
+const ENCODER_MAGIC: &[u8; 4] = b"LTCE";
+const DECODER_MAGIC: &[u8; 4] = b"LTCD";
+const SNAPSHOT_VERSION: u8 = 2;
+
+/// The state of an `LTCEncoder`, restored with `LTCEncoder::restore`.
+///
+/// With the pure-Rust backend a restored encoder continues with exactly the samples the
+/// original would have produced. libltc does not expose the level of the signal and the
+/// sub-sample position of the next edge, so there the restored encoder starts them over like
+/// a freshly created one, and the signal may be shifted by a fraction of a sample and
+/// inverted. Both match the original again at frame boundaries when sample rate / fps is a whole number.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LTCEncoderSnapshot {
+    pub config: LTCEncoderConfig,
+    /// The current frame, which also holds the timecode and user bits
+    pub frame: LTCFrame,
+    pub volume: f64,
+    pub filter: EncoderFilter,
+    pub band_limit: Option<f64>,
+    /// The delay line of the band limiter, `None` without one
+    pub band_limiter_history: Option<[f64; 4]>,
+    /// `None` with libltc, see above
+    pub phase: Option<EncoderPhase>,
+    pub polarity: Polarity,
+    pub buffer_size: usize,
+    /// Samples encoded but not yet read. The restored encoder returns them ahead of what it
+    /// encodes next.
+    pub buffer: Vec<SampleType>,
+}
+
+/// Where the encoder is in the waveform
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct EncoderPhase {
+    /// The level the last edge went to, true if high
+    pub level: bool,
+    /// The fraction of a sample carried over to the next edge
+    pub sample_remainder: f64,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct LTCDecoderSnapshot {
+    pub config: LTCDecoderConfig,
+    pub track_polarity: bool,
+}
+
+impl LTCEncoder {
+    pub fn snapshot(&self) -> LTCEncoderSnapshot {
+        LTCEncoderSnapshot {
+            config: self.config(),
+            frame: self.get_frame(),
+            volume: self.get_volume(),
+            filter: self.filter_preset(),
+            band_limit: self.band_limit(),
+            band_limiter_history: self.band_limiter_history(),
+            phase: self.phase().map(|(level, sample_remainder)| EncoderPhase {
+                level,
+                sample_remainder,
+            }),
+            polarity: self.polarity(),
+            buffer_size: self.get_buffersize(),
+            buffer: self.get_buf_ref(false).0.to_vec(),
+        }
+    }
+
+    pub fn restore(snapshot: &LTCEncoderSnapshot) -> Result<Self, LTCEncoderError> {
+        let mut encoder = LTCEncoder::try_new(&snapshot.config)?;
+        if encoder.get_buffersize() != snapshot.buffer_size {
+            // libltc only sizes the buffer from a rate and fps, as 1 + ceil(sample_rate / fps).
+            // A "sample rate" of buffer_size - 1 at 1 fps gives exactly buffer_size samples.
+            encoder.set_buffersize(snapshot.buffer_size.saturating_sub(1) as f64, 1.0)?;
+        }
+        encoder.set_frame(&snapshot.frame);
+        encoder.set_volume(snapshot.volume)?;
+        encoder.set_filter_preset(snapshot.filter);
+        encoder.set_band_limit(snapshot.band_limit);
+        if let Some(history) = snapshot.band_limiter_history {
+            encoder.set_band_limiter_history(history);
+        }
+        if let Some(phase) = snapshot.phase {
+            encoder.set_phase(phase.level, phase.sample_remainder);
+        }
+        encoder.set_polarity(snapshot.polarity);
+        encoder.set_restored(&snapshot.buffer);
+        Ok(encoder)
+    }
+}
+
+impl LTCDecoder {
+    pub fn snapshot(&self) -> LTCDecoderSnapshot {
+        LTCDecoderSnapshot {
+            config: self.config(),
+            track_polarity: self.is_tracking_polarity(),
+        }
+    }
+
+    pub fn restore(snapshot: &LTCDecoderSnapshot) -> Result<Self, LTCDecoderError> {
+        let mut decoder = LTCDecoder::try_new(&snapshot.config)?;
+        decoder.track_polarity(snapshot.track_polarity);
+        Ok(decoder)
+    }
+}
+
+impl LTCEncoderSnapshot {
+    /// A compact little endian binary encoding
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::new(ENCODER_MAGIC);
+        w.f64(self.config.sample_rate);
+        w.f64(self.config.fps);
+        w.u32(self.config.standard as u32);
+        w.u32(self.config.flags.into());
+        w.bytes(&self.frame.to_bytes());
+        w.f64(self.volume);
+        match self.filter {
+            EncoderFilter::Square => w.u8(0),
+            EncoderFilter::Standard => w.u8(1),
+            EncoderFilter::RiseTime(rise_time) => {
+                w.u8(2);
+                w.f64(rise_time);
+            }
+            EncoderFilter::Libltc(filter) => {
+                w.u8(3);
+                w.f64(filter);
+            }
+        }
+        match self.band_limit {
+            None => w.u8(0),
+            Some(cutoff) => {
+                w.u8(1);
+                w.f64(cutoff);
+                for z in self.band_limiter_history.unwrap_or_default() {
+                    w.f64(z);
+                }
+            }
+        }
+        match self.phase {
+            None => w.u8(0),
+            Some(phase) => {
+                w.u8(1);
+                w.u8(phase.level as u8);
+                w.f64(phase.sample_remainder);
+            }
+        }
+        w.u8(self.polarity as u8);
+        w.u64(self.buffer_size as u64);
+        w.u64(self.buffer.len() as u64);
+        w.bytes(&self.buffer);
+        w.buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut r = Reader::new(bytes, ENCODER_MAGIC)?;
+        let config = LTCEncoderConfig {
+            sample_rate: r.f64()?,
+            fps: r.f64()?,
+            standard: standard_from_u32(r.u32()?)?,
+            flags: LtcBgFlags::new(r.u32()?),
+        };
+        let frame = LTCFrame::from_bytes(r.array()?);
+        let volume = r.f64()?;
+        let filter = match r.u8()? {
+            0 => EncoderFilter::Square,
+            1 => EncoderFilter::Standard,
+            2 => EncoderFilter::RiseTime(r.f64()?),
+            3 => EncoderFilter::Libltc(r.f64()?),
+            _ => return Err(SnapshotError::InvalidValue),
+        };
+        let (band_limit, band_limiter_history) = match r.u8()? {
+            0 => (None, None),
+            1 => (
+                Some(r.f64()?),
+                Some([r.f64()?, r.f64()?, r.f64()?, r.f64()?]),
+            ),
+            _ => return Err(SnapshotError::InvalidValue),
+        };
+        let phase = match r.u8()? {
+            0 => None,
+            1 => Some(EncoderPhase {
+                level: match r.u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(SnapshotError::InvalidValue),
+                },
+                sample_remainder: r.f64()?,
+            }),
+            _ => return Err(SnapshotError::InvalidValue),
+        };
+        let polarity = match r.u8()? {
+            0 => Polarity::Normal,
+            1 => Polarity::Inverted,
+            _ => return Err(SnapshotError::InvalidValue),
+        };
+        let buffer_size = r.u64()? as usize;
+        let len = r.u64()? as usize;
+        let buffer = r.take(len)?.to_vec();
+        r.finish()?;
+
+        Ok(LTCEncoderSnapshot {
+            config,
+            frame,
+            volume,
+            filter,
+            band_limit,
+            band_limiter_history,
+            phase,
+            polarity,
+            buffer_size,
+            buffer,
+        })
+    }
+}
+
+impl LTCDecoderSnapshot {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut w = Writer::new(DECODER_MAGIC);
+        w.u32(self.config.initial_apv as u32);
+        w.u32(self.config.queue_size as u32);
+        w.u8(self.track_polarity as u8);
+        w.buf
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        let mut r = Reader::new(bytes, DECODER_MAGIC)?;
+        let config = LTCDecoderConfig {
+            initial_apv: r.u32()? as i32,
+            queue_size: r.u32()? as i32,
+        };
+        let track_polarity = match r.u8()? {
+            0 => false,
+            1 => true,
+            _ => return Err(SnapshotError::InvalidValue),
+        };
+        r.finish()?;
+        Ok(LTCDecoderSnapshot {
+            config,
+            track_polarity,
+        })
+    }
+}
+
+fn standard_from_u32(standard: u32) -> Result<LTCTVStandard, SnapshotError> {
+    match standard {
+        0 => Ok(LTCTVStandard::LTCTV_525_60),
+        1 => Ok(LTCTVStandard::LTCTV_625_50),
+        2 => Ok(LTCTVStandard::LTCTV_1125_60),
+        3 => Ok(LTCTVStandard::LTCTV_FILM_24),
+        _ => Err(SnapshotError::InvalidValue),
+    }
+}
+
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn new(magic: &[u8; 4]) -> Self {
+        let mut buf = magic.to_vec();
+        buf.push(SNAPSHOT_VERSION);
+        Writer { buf }
+    }
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+    fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+    fn u32(&mut self, v: u32) {
+        self.bytes(&v.to_le_bytes());
+    }
+    fn u64(&mut self, v: u64) {
+        self.bytes(&v.to_le_bytes());
+    }
+    fn f64(&mut self, v: f64) {
+        self.bytes(&v.to_le_bytes());
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8], magic: &[u8; 4]) -> Result<Self, SnapshotError> {
+        let mut r = Reader { buf };
+        if r.take(4)? != magic {
+            return Err(SnapshotError::InvalidMagic);
+        }
+        match r.u8()? {
+            SNAPSHOT_VERSION => Ok(r),
+            v => Err(SnapshotError::UnsupportedVersion(v)),
+        }
+    }
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.buf.len() < n {
+            return Err(SnapshotError::Truncated);
+        }
+        let (head, tail) = self.buf.split_at(n);
+        self.buf = tail;
+        Ok(head)
+    }
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+    fn u8(&mut self) -> Result<u8, SnapshotError> {
+        Ok(self.take(1)?[0])
+    }
+    fn u32(&mut self) -> Result<u32, SnapshotError> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+    fn u64(&mut self) -> Result<u64, SnapshotError> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+    fn f64(&mut self) -> Result<f64, SnapshotError> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+    fn finish(self) -> Result<(), SnapshotError> {
+        if self.buf.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotError::InvalidValue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consts::LtcBgFlagsKind;
+    use crate::api::{SMPTETimecode, Timezone};
+    use alloc::vec;
+
+    #[test]
+    fn test_encoder_snapshot_restore() {
+        let flags = *LtcBgFlags::default().set(LtcBgFlagsKind::LTC_USE_DATE);
+        let config = LTCEncoderConfig {
+            sample_rate: 44_100.0,
+            fps: 30.0,
+            standard: LTCTVStandard::LTCTV_525_60,
+            flags,
+        };
+        let mut encoder = LTCEncoder::try_new(&config).unwrap();
+        encoder.set_buffersize(48_000.0, 24.0).unwrap();
+        encoder.set_timecode(&SMPTETimecode::new(
+            Timezone::from(*b"+0100\0"),
+            24,
+            6,
+            1,
+            10,
+            20,
+            30,
+            4,
+        ));
+        encoder.set_volume(-12.0).unwrap();
+        encoder.set_filter_preset(EncoderFilter::Square);
+        encoder.set_band_limit(Some(12_000.0));
+        encoder.set_polarity(Polarity::Inverted);
+
+        let snapshot = encoder.snapshot();
+        let bytes = snapshot.to_bytes();
+        assert_eq!(LTCEncoderSnapshot::from_bytes(&bytes).unwrap(), snapshot);
+
+        let mut restored = LTCEncoder::restore(&snapshot).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+        assert_eq!(restored.flags(), flags);
+
+        // Both continue with the same output
+        for _ in 0..3 {
+            encoder.encode_frame();
+            restored.encode_frame();
+            assert_eq!(encoder.get_buf_ref(true).0, restored.get_buf_ref(true).0);
+            encoder.inc_timecode().unwrap();
+            restored.inc_timecode().unwrap();
+        }
+
+        // Unread samples end up in the snapshot and come back first after restoring
+        encoder.encode_frame();
+        let snapshot = encoder.snapshot();
+        assert_eq!(snapshot.buffer.len(), encoder.get_buf_ref(false).1);
+        let mut restored = LTCEncoder::restore(&snapshot).unwrap();
+        assert_eq!(restored.snapshot(), snapshot);
+
+        let mut expected = encoder.get_buf_ref(true).0.to_vec();
+        encoder.inc_timecode().unwrap();
+        restored.inc_timecode().unwrap();
+        encoder.encode_frame();
+        restored.encode_frame();
+        expected.extend_from_slice(encoder.get_buf_ref(true).0);
+        assert_eq!(restored.get_buffersize(), expected.len());
+        let mut buf = vec![0; restored.get_buffersize()];
+        assert_eq!(
+            restored.copy_buffer_inplace(&mut buf),
+            expected.len() as i32
+        );
+        // 1470 samples per frame put the edges back on the same sample grid, so this holds
+        // with libltc too
+        assert_eq!(buf[..expected.len()], expected);
+
+        // Then it's back to the encoder's own buffer
+        restored.encode_frame();
+        assert_eq!(restored.get_buf_ref(true).1, 1470);
+    }
+
+    #[cfg(feature = "pure-rust")]
+    #[test]
+    fn test_encoder_snapshot_mid_stream() {
+        let config = LTCEncoderConfig {
+            sample_rate: 48_000.0,
+            fps: 29.97,
+            standard: LTCTVStandard::LTCTV_525_60,
+            flags: LtcBgFlags::default(),
+        };
+        let mut encoder = LTCEncoder::try_new(&config).unwrap();
+        encoder.set_band_limit(Some(8_000.0));
+        for _ in 0..3 {
+            encoder.encode_frame();
+            encoder.get_buf_ref(true);
+            encoder.inc_timecode().unwrap();
+        }
+        // Stop inside a frame, at an odd speed, with unread samples
+        for byte in 0..4 {
+            encoder.encode_byte(byte, 0.9).unwrap();
+        }
+        encoder.get_buf_ref(true);
+        encoder.encode_byte(4, 0.9).unwrap();
+
+        let snapshot = encoder.snapshot();
+        let phase = snapshot.phase.unwrap();
+        assert_ne!(phase.sample_remainder, 0.5);
+        let bytes = snapshot.to_bytes();
+        assert_eq!(LTCEncoderSnapshot::from_bytes(&bytes).unwrap(), snapshot);
+        let mut restored = LTCEncoder::restore(&snapshot).unwrap();
+
+        let continue_stream = |encoder: &mut LTCEncoder| {
+            let mut out = Vec::new();
+            for byte in 5..10 {
+                encoder.encode_byte(byte, 0.9).unwrap();
+            }
+            out.extend_from_slice(encoder.get_buf_ref(true).0);
+            for _ in 0..3 {
+                encoder.inc_timecode().unwrap();
+                encoder.encode_frame();
+                out.extend_from_slice(encoder.get_buf_ref(true).0);
+            }
+            out
+        };
+        let expected = continue_stream(&mut encoder);
+        assert_eq!(continue_stream(&mut restored), expected);
+    }
+
+    #[test]
+    fn test_decoder_snapshot_restore() {
+        let config = LTCDecoderConfig {
+            initial_apv: 1600,
+            queue_size: 8,
+        };
+        let mut decoder = LTCDecoder::try_new(&config).unwrap();
+        decoder.track_polarity(true);
+
+        let snapshot = decoder.snapshot();
+        let bytes = snapshot.to_bytes();
+        let restored = LTCDecoder::restore(&LTCDecoderSnapshot::from_bytes(&bytes).unwrap());
+        assert_eq!(restored.unwrap().snapshot(), snapshot);
+    }
+
+    #[test]
+    fn test_snapshot_invalid_bytes() {
+        let bytes = LTCEncoder::try_new(&LTCEncoderConfig::default())
+            .unwrap()
+            .snapshot()
+            .to_bytes();
+        assert!(matches!(
+            LTCEncoderSnapshot::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(SnapshotError::Truncated)
+        ));
+        assert!(matches!(
+            LTCDecoderSnapshot::from_bytes(&bytes),
+            Err(SnapshotError::InvalidMagic)
+        ));
+    }
+}
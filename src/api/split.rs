@@ -0,0 +1,247 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use super::consts::SampleType;
+use super::decoder::{DecoderSample, DecoderStats, LTCDecoder, LTCDecoderConfig};
+use super::frame::LTCFrameExt;
+use crate::error::LTCDecoderError;
+
+// Single producer, single consumer ring of decoded frames
+#[derive(Debug)]
+struct FrameRing {
+    slots: Box<[UnsafeCell<LTCFrameExt>]>,
+    // Frames pushed and popped so far, the slot is the count modulo the (power of two) length
+    tail: AtomicUsize,
+    head: AtomicUsize,
+    dropped: AtomicUsize,
+}
+
+// SAFETY: Only DecoderInput writes slots and tail, only DecoderOutput writes head, and a slot is
+// only accessed by the side that owns it according to head and tail
+unsafe impl Sync for FrameRing {}
+
+impl FrameRing {
+    fn new(capacity: usize) -> Self {
+        FrameRing {
+            slots: (0..capacity.max(1).next_power_of_two())
+                .map(|_| UnsafeCell::new(LTCFrameExt::default()))
+                .collect(),
+            tail: AtomicUsize::new(0),
+            head: AtomicUsize::new(0),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        self.tail.load(Ordering::Acquire).wrapping_sub(head)
+    }
+
+    fn push(&self, frame: &LTCFrameExt) -> bool {
+        let tail = self.tail.load(Ordering::Relaxed);
+        if tail.wrapping_sub(self.head.load(Ordering::Acquire)) == self.slots.len() {
+            return false;
+        }
+        // SAFETY: The slot is free, the consumer doesn't touch it until tail moves past it
+        unsafe { *self.slots[tail & (self.slots.len() - 1)].get() = *frame };
+        self.tail.store(tail.wrapping_add(1), Ordering::Release);
+        true
+    }
+
+    fn pop(&self, frame: &mut LTCFrameExt) -> bool {
+        let head = self.head.load(Ordering::Relaxed);
+        if head == self.tail.load(Ordering::Acquire) {
+            return false;
+        }
+        // SAFETY: The slot was published by the producer, which doesn't touch it until head
+        // moves past it
+        *frame = unsafe { *self.slots[head & (self.slots.len() - 1)].get() };
+        self.head.store(head.wrapping_add(1), Ordering::Release);
+        true
+    }
+}
+
+/// The writing half of a split `LTCDecoder`, for the audio thread. Never blocks or allocates.
+#[derive(Debug)]
+pub struct DecoderInput {
+    decoder: LTCDecoder,
+    ring: Arc<FrameRing>,
+}
+
+/// The reading half of a split `LTCDecoder`
+#[derive(Debug)]
+pub struct DecoderOutput {
+    ring: Arc<FrameRing>,
+}
+
+impl LTCDecoder {
+    /// Splits the decoder into an input and an output handle connected by a lock-free queue
+    /// of `capacity` frames (rounded up to a power of two).
+    ///
+    /// The input writes with `LTCDecoder::write_with`, moving the decoded frames to the queue
+    /// as it goes, so libltc's own queue never overflows. Frames that don't fit in a full
+    /// queue are dropped, counted and reported by the write functions.
+    pub fn split(self, capacity: usize) -> (DecoderInput, DecoderOutput) {
+        let ring = Arc::new(FrameRing::new(capacity));
+        let input = DecoderInput {
+            decoder: self,
+            ring: ring.clone(),
+        };
+        (input, DecoderOutput { ring })
+    }
+}
+
+impl DecoderInput {
+    /// Returns `LTCDecoderError::QueueOverflow` with the number of frames dropped by this write
+    /// if the output did not keep up
+    pub fn write(&mut self, buf: &[SampleType], posinfo: i64) -> Result<(), LTCDecoderError> {
+        self.write_samples(buf, posinfo)
+    }
+
+    pub fn write_double(&mut self, buf: &[f64], posinfo: i64) -> Result<(), LTCDecoderError> {
+        self.write_samples(buf, posinfo)
+    }
+
+    pub fn write_float(&mut self, buf: &[f32], posinfo: i64) -> Result<(), LTCDecoderError> {
+        self.write_samples(buf, posinfo)
+    }
+
+    pub fn write_i16(&mut self, buf: &[i16], posinfo: i64) -> Result<(), LTCDecoderError> {
+        self.write_samples(buf, posinfo)
+    }
+
+    pub fn write_u16(&mut self, buf: &[u16], posinfo: i64) -> Result<(), LTCDecoderError> {
+        self.write_samples(buf, posinfo)
+    }
+
+    /// Frames dropped because the queue was full, since the split
+    pub fn dropped_frames(&self) -> usize {
+        self.ring.dropped.load(Ordering::Relaxed)
+    }
+
+    pub fn config(&self) -> LTCDecoderConfig {
+        self.decoder.config()
+    }
+
+    /// Statistics of the decoder, see `LTCDecoder::stats`. Frames moved to the queue count as
+    /// read.
+    pub fn stats(&self) -> DecoderStats {
+        self.decoder.stats()
+    }
+
+    fn write_samples<S: DecoderSample>(
+        &mut self,
+        buf: &[S],
+        posinfo: i64,
+    ) -> Result<(), LTCDecoderError> {
+        let mut dropped = 0;
+        let ring = &self.ring;
+        self.decoder.write_with(buf, posinfo, |frame| {
+            if !ring.push(frame) {
+                dropped += 1;
+            }
+        });
+        if dropped == 0 {
+            return Ok(());
+        }
+        self.ring.dropped.fetch_add(dropped, Ordering::Relaxed);
+        Err(LTCDecoderError::QueueOverflow(dropped))
+    }
+}
+
+impl DecoderOutput {
+    pub fn read(&mut self) -> Option<LTCFrameExt> {
+        let mut frame = LTCFrameExt::default();
+        self.read_into(&mut frame).then_some(frame)
+    }
+
+    pub fn read_into(&mut self, frame: &mut LTCFrameExt) -> bool {
+        self.ring.pop(frame)
+    }
+
+    /// Frames waiting to be read
+    pub fn len(&self) -> usize {
+        self.ring.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.ring.slots.len()
+    }
+
+    /// Frames dropped because the queue was full, since the split
+    pub fn dropped_frames(&self) -> usize {
+        self.ring.dropped.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consts::LtcBgFlags;
+    use crate::api::test_util::encode;
+    use alloc::vec::Vec;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_threads() {
+        let decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        // Room for all frames, however the threads are scheduled
+        let (mut input, mut output) = decoder.split(128);
+        let samples = encode(100);
+
+        let producer = std::thread::spawn(move || {
+            // Audio sized blocks, each frame read as soon as possible on the other side
+            for (i, block) in samples.chunks(256).enumerate() {
+                input.write(block, i as i64 * 256).unwrap();
+                if i % 16 == 0 {
+                    std::thread::yield_now();
+                }
+            }
+            (input.dropped_frames(), input.stats().frames_decoded)
+        });
+
+        let mut frames = Vec::new();
+        while !producer.is_finished() || !output.is_empty() {
+            match output.read() {
+                Some(frame) => frames.push(frame),
+                None => std::thread::yield_now(),
+            }
+        }
+        assert_eq!(producer.join().unwrap(), (0, 99));
+
+        assert_eq!(frames.len(), 99);
+        for pair in frames.windows(2) {
+            let previous = pair[0].ltc().to_timecode(LtcBgFlags::default());
+            let timecode = pair[1].ltc().to_timecode(LtcBgFlags::default());
+            assert_eq!(timecode.frame(), (previous.frame() + 1) % 25);
+            assert!(pair[1].off_start() > pair[0].off_start());
+        }
+    }
+
+    #[test]
+    fn test_overflow() {
+        let decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        let (mut input, mut output) = decoder.split(3);
+        assert_eq!(output.capacity(), 4);
+
+        // A single write with more frames than libltc's queue (32) holds
+        let samples = encode(40);
+        let result = input.write(&samples, 0);
+        let Err(LTCDecoderError::QueueOverflow(dropped)) = result else {
+            panic!("expected an overflow, got {result:?}");
+        };
+        assert!(dropped >= 34);
+        assert_eq!(output.dropped_frames(), dropped);
+
+        // The oldest frames were kept
+        assert_eq!(output.len(), 4);
+        let first = output.read().unwrap();
+        assert_eq!(first.ltc().to_timecode(LtcBgFlags::default()).frame(), 0);
+    }
+}
@@ -0,0 +1,241 @@
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use tokio::io::{AsyncRead, ReadBuf};
+
+use super::consts::SampleType;
+use super::decoder::{DecoderSample, LTCDecoder};
+use super::frame::LTCFrameExt;
+
+const READ_BUFFER_SIZE: usize = 4096;
+
+// The decoder and the frames decoded but not yielded yet
+#[derive(Debug)]
+struct StreamDecoder {
+    decoder: LTCDecoder,
+    position: i64,
+    pending: VecDeque<LTCFrameExt>,
+}
+
+impl StreamDecoder {
+    fn new(decoder: LTCDecoder) -> Self {
+        StreamDecoder {
+            decoder,
+            position: 0,
+            pending: VecDeque::new(),
+        }
+    }
+
+    // `write_with` writes in chunks, so a large buffer can't overflow the decoder's queue
+    fn write<S: DecoderSample>(&mut self, buf: &[S]) {
+        let pending = &mut self.pending;
+        self.decoder
+            .write_with(buf, self.position, |frame| pending.push_back(*frame));
+        self.position += buf.len() as i64;
+    }
+}
+
+/// Decodes a stream of sample buffers, yielding every frame decoded from them.
+///
+/// Positions (`LTCFrameExt::off_start` and `off_end`) count samples from the start of the
+/// stream. The source is only polled when all frames decoded so far have been taken, so a slow
+/// consumer holds back the source. Sources that aren't `Unpin` can be wrapped with `Box::pin`.
+#[derive(Debug)]
+pub struct FrameStream<St> {
+    source: Option<St>,
+    inner: StreamDecoder,
+}
+
+impl<St> FrameStream<St> {
+    pub fn new(decoder: LTCDecoder, source: St) -> Self {
+        FrameStream {
+            source: Some(source),
+            inner: StreamDecoder::new(decoder),
+        }
+    }
+
+    pub fn decoder(&self) -> &LTCDecoder {
+        &self.inner.decoder
+    }
+
+    /// Samples consumed so far
+    pub fn position(&self) -> i64 {
+        self.inner.position
+    }
+
+    pub fn into_decoder(self) -> LTCDecoder {
+        self.inner.decoder
+    }
+}
+
+impl<St, S> Stream for FrameStream<St>
+where
+    St: Stream<Item = Vec<S>> + Unpin,
+    S: DecoderSample,
+{
+    type Item = LTCFrameExt;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(frame) = this.inner.pending.pop_front() {
+                return Poll::Ready(Some(frame));
+            }
+            let Some(source) = this.source.as_mut() else {
+                return Poll::Ready(None);
+            };
+            match Pin::new(source).poll_next(cx) {
+                Poll::Ready(Some(buf)) => this.inner.write(&buf),
+                Poll::Ready(None) => this.source = None,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Decodes an `AsyncRead` of unsigned 8 bit samples, yielding every frame decoded from it.
+///
+/// Like `FrameStream`, positions count samples from the start and the reader is only read
+/// when all frames decoded so far have been taken. Read errors are yielded, after which the
+/// stream ends.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    reader: Option<R>,
+    buf: Box<[SampleType]>,
+    inner: StreamDecoder,
+}
+
+impl<R> FrameReader<R> {
+    pub fn new(decoder: LTCDecoder, reader: R) -> Self {
+        FrameReader {
+            reader: Some(reader),
+            buf: vec![0; READ_BUFFER_SIZE].into_boxed_slice(),
+            inner: StreamDecoder::new(decoder),
+        }
+    }
+
+    pub fn decoder(&self) -> &LTCDecoder {
+        &self.inner.decoder
+    }
+
+    /// Samples consumed so far
+    pub fn position(&self) -> i64 {
+        self.inner.position
+    }
+
+    pub fn into_decoder(self) -> LTCDecoder {
+        self.inner.decoder
+    }
+}
+
+impl<R: AsyncRead + Unpin> Stream for FrameReader<R> {
+    type Item = io::Result<LTCFrameExt>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            if let Some(frame) = this.inner.pending.pop_front() {
+                return Poll::Ready(Some(Ok(frame)));
+            }
+            let Some(reader) = this.reader.as_mut() else {
+                return Poll::Ready(None);
+            };
+            let mut buf = ReadBuf::new(&mut this.buf);
+            match Pin::new(reader).poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(())) if buf.filled().is_empty() => this.reader = None,
+                Poll::Ready(Ok(())) => this.inner.write(buf.filled()),
+                Poll::Ready(Err(e)) if e.kind() == io::ErrorKind::Interrupted => {}
+                Poll::Ready(Err(e)) => {
+                    this.reader = None;
+                    return Poll::Ready(Some(Err(e)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::consts::LtcBgFlags;
+    use crate::api::decoder::LTCDecoderConfig;
+    use crate::api::test_util::encode;
+    use std::future::poll_fn;
+
+    // A source yielding the given buffers, pending once before each
+    struct Buffers<S> {
+        buffers: VecDeque<Vec<S>>,
+        ready: bool,
+    }
+
+    impl<S: Unpin> Stream for Buffers<S> {
+        type Item = Vec<S>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Vec<S>>> {
+            self.ready = !self.ready;
+            if self.ready {
+                Poll::Ready(self.buffers.pop_front())
+            } else {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    async fn collect<St: Stream + Unpin>(mut stream: St) -> Vec<St::Item> {
+        let mut items = Vec::new();
+        while let Some(item) = poll_fn(|cx| Pin::new(&mut stream).poll_next(cx)).await {
+            items.push(item);
+        }
+        items
+    }
+
+    fn assert_in_order(frames: &[LTCFrameExt]) {
+        for (i, frame) in frames.iter().enumerate() {
+            let timecode = frame.ltc().to_timecode(LtcBgFlags::default());
+            assert_eq!(
+                timecode.seconds() as usize * 25 + timecode.frame() as usize,
+                i
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stream() {
+        let samples = encode(50);
+        let floats: Vec<f32> = samples
+            .iter()
+            .map(|&x| (x as f32 - 128.0) / 128.0)
+            .collect();
+        // Buffers much larger than the decoder queue, to check none are lost
+        let source = Buffers {
+            buffers: floats.chunks(40_000).map(<[f32]>::to_vec).collect(),
+            ready: false,
+        };
+        let decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        let mut stream = FrameStream::new(decoder, source);
+        let frames = collect(&mut stream).await;
+
+        assert_eq!(frames.len(), 49);
+        assert_in_order(&frames);
+        assert!(frames
+            .windows(2)
+            .all(|w| w[1].off_start() > w[0].off_start()));
+        assert_eq!(stream.position(), samples.len() as i64);
+    }
+
+    #[tokio::test]
+    async fn test_reader() {
+        let samples = encode(50);
+        let decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+        let frames = collect(FrameReader::new(decoder, &samples[..])).await;
+
+        let frames: Vec<_> = frames.into_iter().map(Result::unwrap).collect();
+        assert_eq!(frames.len(), 49);
+        assert_in_order(&frames);
+    }
+}
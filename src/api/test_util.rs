@@ -0,0 +1,23 @@
+// Fixtures shared by the unit tests
+
+use alloc::vec::Vec;
+
+use super::consts::SampleType;
+use super::encoder::{LTCEncoder, LTCEncoderConfig};
+
+// `frames` consecutive frames from a default encoder
+pub(crate) fn encode(frames: usize) -> Vec<SampleType> {
+    let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+    encode_with(&mut encoder, frames)
+}
+
+// `frames` consecutive frames from `encoder`, which is left at the timecode after the last
+pub(crate) fn encode_with(encoder: &mut LTCEncoder, frames: usize) -> Vec<SampleType> {
+    let mut samples = Vec::new();
+    for _ in 0..frames {
+        encoder.encode_frame();
+        samples.extend_from_slice(encoder.get_buf_ref(true).0);
+        encoder.inc_timecode().unwrap();
+    }
+    samples
+}
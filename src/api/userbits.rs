@@ -0,0 +1,294 @@
+use super::consts::LtcBgFlags;
+use super::consts::LtcBgFlagsKind;
+use super::frame::LTCFrame;
+use super::LTCTVStandard;
+use super::SMPTETimecode;
+use super::Timezone;
+use crate::error::UserBitsError;
+
+/// The three binary group flags (SMPTE 12M). Their position in the frame depends on the
+/// standard: in 25fps mode BGF0 and BGF2 move to bits 27 and 43, and the parity takes bit 59.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct BinaryGroupFlags {
+    /// Together with BGF2, selects how the user bits are interpreted
+    pub bgf0: bool,
+    /// The timecode is wall-clock time (LTC_TC_CLOCK)
+    pub bgf1: bool,
+    /// Together with BGF0, selects how the user bits are interpreted
+    pub bgf2: bool,
+}
+
+impl BinaryGroupFlags {
+    pub fn from_frame(frame: &LTCFrame, standard: LTCTVStandard) -> Self {
+        let f = &frame.inner_raw;
+        match standard {
+            LTCTVStandard::LTCTV_625_50 => BinaryGroupFlags {
+                bgf0: f.biphase_mark_phase_correction() != 0,
+                bgf1: f.binary_group_flag_bit1() != 0,
+                bgf2: f.binary_group_flag_bit0() != 0,
+            },
+            _ => BinaryGroupFlags {
+                bgf0: f.binary_group_flag_bit0() != 0,
+                bgf1: f.binary_group_flag_bit1() != 0,
+                bgf2: f.binary_group_flag_bit2() != 0,
+            },
+        }
+    }
+
+    /// Note that this changes the frame's parity, use `LTCFrame::set_parity` afterwards.
+    pub fn apply_to(&self, frame: &mut LTCFrame, standard: LTCTVStandard) {
+        let f = &mut frame.inner_raw;
+        match standard {
+            LTCTVStandard::LTCTV_625_50 => {
+                f.set_biphase_mark_phase_correction(self.bgf0 as u32);
+                f.set_binary_group_flag_bit1(self.bgf1 as u32);
+                f.set_binary_group_flag_bit0(self.bgf2 as u32);
+            }
+            _ => {
+                f.set_binary_group_flag_bit0(self.bgf0 as u32);
+                f.set_binary_group_flag_bit1(self.bgf1 as u32);
+                f.set_binary_group_flag_bit2(self.bgf2 as u32);
+            }
+        }
+    }
+}
+
+/// Typed view of the 32 user bits of a frame. The kind is signalled by BGF0 and BGF2:
+///
+/// | BGF2 | BGF0 | Kind                              |
+/// |------|------|-----------------------------------|
+/// | 0    | 0    | `Binary` / `Bcd` (user defined)   |
+/// | 0    | 1    | `Ascii` (8-bit character set)     |
+/// | 1    | 0    | `Date` (SMPTE 309M)               |
+/// | 1    | 1    | page/line, read back as `Binary`  |
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum UserBits {
+    /// Opaque user defined data, as returned by `LTCFrame::get_user_bits`
+    Binary(u32),
+    /// Four characters, the first one is transmitted first (user groups 1 and 2)
+    Ascii([u8; 4]),
+    /// Eight BCD digits, most significant first. e.g. reel 1234, take 0056
+    /// is `[1, 2, 3, 4, 0, 0, 5, 6]`, or 0x12340056 when read as binary.
+    Bcd([u8; 8]),
+    /// SMPTE 309M date and timezone. The timezone must be one of the codes known to libltc,
+    /// `to_raw` returns `UserBitsError::UnknownTimezone` otherwise.
+    Date {
+        timezone: Timezone,
+        years: u8,
+        months: u8,
+        days: u8,
+    },
+}
+
+impl Default for UserBits {
+    fn default() -> Self {
+        UserBits::Binary(0)
+    }
+}
+
+impl UserBits {
+    pub fn to_raw(&self) -> Result<u32, UserBitsError> {
+        match *self {
+            UserBits::Binary(data) => Ok(data),
+            UserBits::Ascii(chars) => {
+                if let Some(c) = chars.iter().find(|c| !c.is_ascii()) {
+                    return Err(UserBitsError::NonAsciiCharacter(*c));
+                }
+                Ok(u32::from_le_bytes(chars))
+            }
+            UserBits::Bcd(digits) => digits.iter().try_fold(0u32, |acc, &d| {
+                if d > 9 {
+                    Err(UserBitsError::InvalidBcdDigit(d))
+                } else {
+                    Ok((acc << 4) | d as u32)
+                }
+            }),
+            UserBits::Date {
+                timezone,
+                years,
+                months,
+                days,
+            } => {
+                if years > 99 || !(1..=12).contains(&months) || !(1..=31).contains(&days) {
+                    return Err(UserBitsError::InvalidDate);
+                }
+                // libltc would encode an unknown timezone as +0000
+                if timezone.smpte_code().is_none() {
+                    return Err(UserBitsError::UnknownTimezone(timezone));
+                }
+                // Let libltc pack the date and the timezone code
+                let timecode = SMPTETimecode::new(timezone, years, months, days, 0, 0, 0, 0);
+                let frame = LTCFrame::from_timecode(
+                    &timecode,
+                    LTCTVStandard::default(),
+                    Self::date_flags(),
+                );
+                Ok(frame.get_user_bits())
+            }
+        }
+    }
+
+    /// The BGF0 and BGF2 values announcing this kind of user bits
+    pub fn flags(&self) -> (bool, bool) {
+        match self {
+            UserBits::Binary(_) | UserBits::Bcd(_) => (false, false),
+            UserBits::Ascii(_) => (true, false),
+            UserBits::Date { .. } => (false, true),
+        }
+    }
+
+    pub fn from_raw(data: u32, flags: BinaryGroupFlags) -> Self {
+        match (flags.bgf2, flags.bgf0) {
+            (false, true) => UserBits::Ascii(data.to_le_bytes()),
+            (true, false) => {
+                let mut frame = LTCFrame::new();
+                frame.set_user_bits(data);
+                let timecode = frame.to_timecode(Self::date_flags());
+                UserBits::Date {
+                    timezone: timecode.timezone(),
+                    years: timecode.years(),
+                    months: timecode.months(),
+                    days: timecode.days(),
+                }
+            }
+            _ => UserBits::Binary(data),
+        }
+    }
+
+    /// Reads `Binary` or `Bcd` user bits as BCD digits. Returns `None` if any nibble is not a
+    /// decimal digit.
+    pub fn as_bcd(&self) -> Option<[u8; 8]> {
+        let data = match *self {
+            UserBits::Binary(data) => data,
+            UserBits::Bcd(digits) => return Some(digits),
+            _ => return None,
+        };
+        let mut digits = [0; 8];
+        for (i, digit) in digits.iter_mut().enumerate() {
+            *digit = ((data >> (28 - 4 * i)) & 0xf) as u8;
+            if *digit > 9 {
+                return None;
+            }
+        }
+        Some(digits)
+    }
+
+    fn date_flags() -> LtcBgFlags {
+        *LtcBgFlags::default().set(LtcBgFlagsKind::LTC_USE_DATE)
+    }
+}
+
+impl LTCFrame {
+    pub fn get_typed_user_bits(&self, standard: LTCTVStandard) -> UserBits {
+        UserBits::from_raw(
+            self.get_user_bits(),
+            BinaryGroupFlags::from_frame(self, standard),
+        )
+    }
+
+    /// Writes the user bits and sets BGF0/BGF2 accordingly. BGF1 (clock) is left untouched.
+    /// Since the flags take part in the parity, call `set_parity` afterwards.
+    pub fn set_typed_user_bits(
+        &mut self,
+        bits: &UserBits,
+        standard: LTCTVStandard,
+    ) -> Result<(), UserBitsError> {
+        let data = bits.to_raw()?;
+        let (bgf0, bgf2) = bits.flags();
+        let mut flags = BinaryGroupFlags::from_frame(self, standard);
+        flags.bgf0 = bgf0;
+        flags.bgf2 = bgf2;
+
+        self.set_user_bits(data);
+        flags.apply_to(self, standard);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_bits_roundtrip() {
+        let cases = [
+            UserBits::Binary(0xdeadbeef),
+            UserBits::Ascii(*b"Tk12"),
+            UserBits::Date {
+                timezone: Timezone::from(*b"+0100\0"),
+                years: 24,
+                months: 12,
+                days: 31,
+            },
+        ];
+        for standard in [
+            LTCTVStandard::LTCTV_525_60,
+            LTCTVStandard::LTCTV_625_50,
+            LTCTVStandard::LTCTV_1125_60,
+            LTCTVStandard::LTCTV_FILM_24,
+        ] {
+            for bits in cases {
+                let mut frame = LTCFrame::new();
+                frame.set_typed_user_bits(&bits, standard).unwrap();
+                frame.set_parity(standard);
+                assert_eq!(frame.get_typed_user_bits(standard), bits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_user_bits_bcd() {
+        let bits = UserBits::Bcd([1, 2, 3, 4, 0, 0, 5, 6]);
+        assert_eq!(bits.to_raw().unwrap(), 0x12340056);
+
+        let mut frame = LTCFrame::new();
+        frame
+            .set_typed_user_bits(&bits, LTCTVStandard::LTCTV_625_50)
+            .unwrap();
+        let read = frame.get_typed_user_bits(LTCTVStandard::LTCTV_625_50);
+        assert_eq!(read, UserBits::Binary(0x12340056));
+        assert_eq!(read.as_bcd(), Some([1, 2, 3, 4, 0, 0, 5, 6]));
+        assert_eq!(UserBits::Binary(0x1234005a).as_bcd(), None);
+
+        assert!(UserBits::Bcd([1, 2, 3, 4, 0, 0, 5, 10]).to_raw().is_err());
+        assert!(UserBits::Ascii([b'a', 0xff, b'c', b'd']).to_raw().is_err());
+    }
+
+    #[test]
+    fn test_user_bits_timezone() {
+        let date = |timezone: &[u8; 6]| UserBits::Date {
+            timezone: timezone.into(),
+            years: 24,
+            months: 1,
+            days: 2,
+        };
+        assert!(date(b"-0530\0").to_raw().is_ok());
+        assert!(date(b"TP-01\0").to_raw().is_ok());
+        assert!(matches!(
+            date(b"+0115\0").to_raw(),
+            Err(UserBitsError::UnknownTimezone(_))
+        ));
+        assert!(date(b"\0\0\0\0\0\0").to_raw().is_err());
+    }
+
+    #[test]
+    fn test_user_bits_keep_clock_flag() {
+        for standard in [LTCTVStandard::LTCTV_525_60, LTCTVStandard::LTCTV_625_50] {
+            let mut frame = LTCFrame::new();
+            let clock = BinaryGroupFlags {
+                bgf0: false,
+                bgf1: true,
+                bgf2: false,
+            };
+            clock.apply_to(&mut frame, standard);
+
+            frame
+                .set_typed_user_bits(&UserBits::Ascii(*b"ABCD"), standard)
+                .unwrap();
+            let flags = BinaryGroupFlags::from_frame(&frame, standard);
+            assert!(flags.bgf0);
+            assert!(flags.bgf1);
+            assert!(!flags.bgf2);
+        }
+    }
+}
@@ -0,0 +1,927 @@
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use super::bwf::{self, BwfTimecode};
+use super::consts::SampleType;
+use super::decoder::{LTCDecoder, LTCDecoderConfig};
+use super::encoder::LTCEncoder;
+use super::frame::LTCFrameExt;
+use crate::error::WavError;
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+// Sample frames read from the file at once
+const BLOCK_FRAMES: usize = 1024;
+
+// Room reserved after the RIFF header for a ds64 chunk, should the file outgrow 4GB
+const JUNK_SIZE: u32 = 28;
+// Offset of the RIFF chunk size in the files written by `WavWriter`
+const RIFF_SIZE_OFFSET: u64 = 4;
+
+// KSDATAFORMAT_SUBTYPE_PCM / _IEEE_FLOAT, without the format tag in the first two bytes
+const SUBFORMAT_GUID_TAIL: [u8; 14] = [
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WavSampleFormat {
+    /// Unsigned for 8 bits, signed otherwise
+    Int,
+    Float,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct WavFormat {
+    pub sample_format: WavSampleFormat,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub bits_per_sample: u16,
+}
+
+impl WavFormat {
+    pub fn bytes_per_sample(&self) -> usize {
+        self.bits_per_sample as usize / 8
+    }
+
+    pub fn block_align(&self) -> usize {
+        self.bytes_per_sample() * self.channels as usize
+    }
+}
+
+// Samples of one channel, in the closest format `LTCDecoder` accepts
+#[derive(Debug)]
+enum ChannelSamples {
+    U8(Vec<SampleType>),
+    I16(Vec<i16>),
+    F32(Vec<f32>),
+    F64(Vec<f64>),
+}
+
+/// Streaming reader for RIFF and RF64 (BWF) WAV files, 8/16/24/32-bit integer and 32/64-bit
+/// float, any number of channels. Only needs `Read`, so it also works on pipes.
+#[derive(Debug)]
+pub struct WavReader<R: Read> {
+    reader: R,
+    format: WavFormat,
+    /// Bytes left in the data chunk, `None` when the length is unknown (streamed files)
+    remaining: Option<u64>,
+    position: u64,
+    buf: Vec<u8>,
+    time_reference: Option<u64>,
+    ixml: Option<String>,
+}
+
+impl<R: Read> WavReader<R> {
+    /// Parses the header up to the start of the sample data
+    pub fn new(mut reader: R) -> Result<Self, WavError> {
+        let mut header = [0u8; 12];
+        reader.read_exact(&mut header)?;
+        let rf64 = match &header[0..4] {
+            b"RIFF" => false,
+            b"RF64" => true,
+            _ => return Err(WavError::InvalidHeader("not a RIFF or RF64 file")),
+        };
+        if &header[8..12] != b"WAVE" {
+            return Err(WavError::InvalidHeader("not a WAVE file"));
+        }
+
+        let mut format = None;
+        let mut ds64_data_size = None;
+        let mut time_reference = None;
+        let mut ixml = None;
+        loop {
+            let mut chunk = [0u8; 8];
+            reader.read_exact(&mut chunk)?;
+            let size = u32::from_le_bytes(chunk[4..8].try_into().unwrap());
+
+            match &chunk[0..4] {
+                b"ds64" if rf64 => {
+                    let body = read_chunk(&mut reader, size)?;
+                    if body.len() < 16 {
+                        return Err(WavError::InvalidHeader("ds64 chunk too short"));
+                    }
+                    ds64_data_size = Some(u64::from_le_bytes(body[8..16].try_into().unwrap()));
+                }
+                b"fmt " => {
+                    format = Some(parse_fmt(&read_chunk(&mut reader, size)?)?);
+                }
+                b"bext" => {
+                    time_reference = bwf::parse_bext(&read_chunk(&mut reader, size)?);
+                }
+                b"iXML" => {
+                    let body = read_chunk(&mut reader, size)?;
+                    ixml = Some(String::from_utf8_lossy(&body).into_owned());
+                }
+                b"data" => {
+                    let format = format.ok_or(WavError::InvalidHeader("data before fmt chunk"))?;
+                    let remaining = match size {
+                        0xffff_ffff if rf64 => ds64_data_size,
+                        // Written by a stream that did not know the length in advance
+                        0 | 0xffff_ffff => None,
+                        size => Some(size as u64),
+                    };
+                    return Ok(WavReader {
+                        reader,
+                        format,
+                        remaining,
+                        position: 0,
+                        buf: vec![],
+                        time_reference,
+                        ixml,
+                    });
+                }
+                _ => {
+                    read_chunk(&mut reader, size)?;
+                }
+            }
+        }
+    }
+
+    pub fn format(&self) -> WavFormat {
+        self.format
+    }
+
+    /// Sample frames read so far, i.e. the position of the next sample
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Total number of sample frames, if the header states it
+    pub fn len(&self) -> Option<u64> {
+        self.remaining
+            .map(|remaining| self.position + remaining / self.format.block_align() as u64)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
+    }
+
+    /// The `bext` TimeReference: samples since midnight at the first sample of the file
+    pub fn time_reference(&self) -> Option<u64> {
+        self.time_reference
+    }
+
+    /// The iXML chunk, if the file has one before the sample data
+    pub fn ixml(&self) -> Option<&str> {
+        self.ixml.as_deref()
+    }
+
+    /// The TimeReference with the timecode rate from iXML. Files without iXML need the rate from
+    /// elsewhere, see `BwfTimecode`.
+    pub fn bwf_timecode(&self) -> Option<BwfTimecode> {
+        let (fps, drop_frame) = bwf::parse_ixml_rate(self.ixml.as_deref()?)?;
+        Some(BwfTimecode {
+            time_reference: self.time_reference?,
+            fps,
+            drop_frame,
+        })
+    }
+
+    /// A decoder configuration with the right audio frames per video frame for this file
+    pub fn decoder_config(&self, fps: f64) -> LTCDecoderConfig {
+        LTCDecoderConfig {
+            initial_apv: (self.format.sample_rate as f64 / fps).round() as i32,
+            ..LTCDecoderConfig::default()
+        }
+    }
+
+    // Reads up to `frames` sample frames into self.buf. Returns the number of frames read.
+    fn read_block(&mut self, frames: usize) -> Result<usize, WavError> {
+        let block_align = self.format.block_align();
+        let mut len = frames * block_align;
+        if let Some(remaining) = self.remaining {
+            len = len.min(remaining as usize - remaining as usize % block_align);
+        }
+        self.buf.resize(len, 0);
+
+        let mut filled = 0;
+        while filled < len {
+            match self.reader.read(&mut self.buf[filled..]) {
+                Ok(0) => break,
+                Ok(n) => filled += n,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        // A trailing partial frame is dropped
+        let frames = filled / block_align;
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= filled as u64;
+        }
+        self.position += frames as u64;
+        Ok(frames)
+    }
+
+    fn check_channel(&self, channel: u16) -> Result<(), WavError> {
+        if channel < self.format.channels {
+            Ok(())
+        } else {
+            Err(WavError::InvalidChannel(channel))
+        }
+    }
+
+    // The bytes of `channel` in each of the first `frames` frames of self.buf
+    fn channel_bytes(&self, channel: u16, frames: usize) -> impl Iterator<Item = &[u8]> {
+        let width = self.format.bytes_per_sample();
+        let offset = channel as usize * width;
+        self.buf
+            .chunks_exact(self.format.block_align())
+            .take(frames)
+            .map(move |frame| &frame[offset..offset + width])
+    }
+
+    fn read_channel(
+        &mut self,
+        channel: u16,
+        samples: &mut ChannelSamples,
+    ) -> Result<usize, WavError> {
+        let frames = self.read_block(BLOCK_FRAMES)?;
+        let format = self.format;
+        let bytes = self.channel_bytes(channel, frames);
+        match (format.sample_format, format.bits_per_sample, samples) {
+            (WavSampleFormat::Int, 8, ChannelSamples::U8(out)) => {
+                out.clear();
+                out.extend(bytes.map(|b| b[0]));
+            }
+            (WavSampleFormat::Int, 16, ChannelSamples::I16(out)) => {
+                out.clear();
+                out.extend(bytes.map(|b| i16::from_le_bytes([b[0], b[1]])));
+            }
+            (WavSampleFormat::Int, 24, ChannelSamples::F32(out)) => {
+                out.clear();
+                out.extend(bytes.map(|b| {
+                    (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0
+                }));
+            }
+            (WavSampleFormat::Int, 32, ChannelSamples::F32(out)) => {
+                out.clear();
+                out.extend(
+                    bytes.map(|b| {
+                        i32::from_le_bytes(b.try_into().unwrap()) as f32 / 2_147_483_648.0
+                    }),
+                );
+            }
+            (WavSampleFormat::Float, 32, ChannelSamples::F32(out)) => {
+                out.clear();
+                out.extend(bytes.map(|b| f32::from_le_bytes(b.try_into().unwrap())));
+            }
+            (WavSampleFormat::Float, 64, ChannelSamples::F64(out)) => {
+                out.clear();
+                out.extend(bytes.map(|b| f64::from_le_bytes(b.try_into().unwrap())));
+            }
+            _ => unreachable!("the sample buffer matches the format"),
+        }
+        Ok(frames)
+    }
+
+    fn channel_samples(&self) -> ChannelSamples {
+        match (self.format.sample_format, self.format.bits_per_sample) {
+            (WavSampleFormat::Int, 8) => ChannelSamples::U8(vec![]),
+            (WavSampleFormat::Int, 16) => ChannelSamples::I16(vec![]),
+            (WavSampleFormat::Float, 64) => ChannelSamples::F64(vec![]),
+            _ => ChannelSamples::F32(vec![]),
+        }
+    }
+
+    /// Reads one channel as floats in [-1, 1]. Returns the number of samples read, 0 at the
+    /// end of the data.
+    pub fn read_f32(&mut self, channel: u16, buf: &mut [f32]) -> Result<usize, WavError> {
+        self.check_channel(channel)?;
+        let frames = self.read_block(buf.len())?;
+        let format = self.format;
+        let samples = self.channel_bytes(channel, frames).map(|b| match format {
+            WavFormat {
+                sample_format: WavSampleFormat::Float,
+                bits_per_sample: 64,
+                ..
+            } => f64::from_le_bytes(b.try_into().unwrap()) as f32,
+            WavFormat {
+                sample_format: WavSampleFormat::Float,
+                ..
+            } => f32::from_le_bytes(b.try_into().unwrap()),
+            WavFormat {
+                bits_per_sample: 8, ..
+            } => (b[0] as f32 - 128.0) / 128.0,
+            WavFormat {
+                bits_per_sample: 16,
+                ..
+            } => i16::from_le_bytes([b[0], b[1]]) as f32 / 32_768.0,
+            WavFormat {
+                bits_per_sample: 24,
+                ..
+            } => (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+            _ => i32::from_le_bytes(b.try_into().unwrap()) as f32 / 2_147_483_648.0,
+        });
+        for (out, sample) in buf.iter_mut().zip(samples) {
+            *out = sample;
+        }
+        Ok(frames)
+    }
+
+    /// Streams the rest of `channel` through the decoder, with the sample position in the file
+    /// as `posinfo`, and calls `on_frame` for every decoded frame.
+    pub fn decode(
+        &mut self,
+        decoder: &mut LTCDecoder,
+        channel: u16,
+        mut on_frame: impl FnMut(LTCFrameExt),
+    ) -> Result<(), WavError> {
+        self.check_channel(channel)?;
+        let mut samples = self.channel_samples();
+        loop {
+            let pos = self.position as i64;
+            if self.read_channel(channel, &mut samples)? == 0 {
+                return Ok(());
+            }
+            match &samples {
+                ChannelSamples::U8(buf) => decoder.write(buf, pos),
+                ChannelSamples::I16(buf) => decoder.write_i16(buf, pos),
+                ChannelSamples::F32(buf) => decoder.write_float(buf, pos),
+                ChannelSamples::F64(buf) => decoder.write_double(buf, pos),
+            }
+            while let Some(frame) = decoder.read() {
+                on_frame(frame);
+            }
+        }
+    }
+}
+
+/// Writes encoder output as a WAV file: LTC on one channel, silence on the others.
+///
+/// Files larger than 4GB are written as RF64 when finalized. On outputs that cannot seek (e.g.
+/// stdout) the header states an unknown length, which most tools, including `WavReader`,
+/// accept as "until the end of the file".
+#[derive(Debug)]
+pub struct WavWriter<W: Write> {
+    writer: W,
+    format: WavFormat,
+    ltc_channel: u16,
+    data_len: u64,
+    data_size_offset: u64,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> WavWriter<W> {
+    pub fn new(writer: W, format: WavFormat, ltc_channel: u16) -> Result<Self, WavError> {
+        Self::with_header(writer, format, ltc_channel, None)
+    }
+
+    /// A Broadcast WAV writer, with `bext` and iXML chunks stating where the file starts
+    pub fn with_timecode(
+        writer: W,
+        format: WavFormat,
+        ltc_channel: u16,
+        timecode: &BwfTimecode,
+    ) -> Result<Self, WavError> {
+        Self::with_header(writer, format, ltc_channel, Some(timecode))
+    }
+
+    fn with_header(
+        mut writer: W,
+        format: WavFormat,
+        ltc_channel: u16,
+        timecode: Option<&BwfTimecode>,
+    ) -> Result<Self, WavError> {
+        let supported = match format.sample_format {
+            WavSampleFormat::Int => matches!(format.bits_per_sample, 8 | 16 | 24 | 32),
+            WavSampleFormat::Float => matches!(format.bits_per_sample, 32 | 64),
+        };
+        if !supported || format.channels == 0 {
+            return Err(WavError::UnsupportedFormat);
+        }
+        if ltc_channel >= format.channels {
+            return Err(WavError::InvalidChannel(ltc_channel));
+        }
+
+        let header = wav_header(&format, timecode);
+        writer.write_all(&header)?;
+        Ok(WavWriter {
+            writer,
+            format,
+            ltc_channel,
+            data_len: 0,
+            data_size_offset: header.len() as u64 - 4,
+            buf: vec![],
+        })
+    }
+
+    /// A Broadcast WAV writer at the encoder's sample rate, starting at the encoder's current
+    /// timecode
+    pub fn for_encoder(
+        writer: W,
+        encoder: &LTCEncoder,
+        sample_format: WavSampleFormat,
+        bits_per_sample: u16,
+        channels: u16,
+        ltc_channel: u16,
+    ) -> Result<Self, WavError> {
+        let format = WavFormat {
+            sample_format,
+            channels,
+            sample_rate: encoder.sample_rate().round() as u32,
+            bits_per_sample,
+        };
+        Self::with_timecode(
+            writer,
+            format,
+            ltc_channel,
+            &BwfTimecode::from_encoder(encoder),
+        )
+    }
+
+    pub fn format(&self) -> WavFormat {
+        self.format
+    }
+
+    /// Sample frames written so far
+    pub fn len(&self) -> u64 {
+        self.data_len / self.format.block_align() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data_len == 0
+    }
+
+    /// Appends 8-bit encoder samples
+    pub fn write_samples(&mut self, samples: &[SampleType]) -> Result<(), WavError> {
+        let width = self.format.bytes_per_sample();
+        let block_align = self.format.block_align();
+        self.buf.clear();
+        self.buf.resize(samples.len() * block_align, 0);
+        if self.format.sample_format == WavSampleFormat::Int && width == 1 {
+            // 8-bit WAV is unsigned, silence is 128
+            self.buf.fill(128);
+        }
+
+        let offset = self.ltc_channel as usize * width;
+        for (frame, &sample) in self.buf.chunks_exact_mut(block_align).zip(samples) {
+            let centred = sample as i32 - 128;
+            let out = &mut frame[offset..offset + width];
+            match (self.format.sample_format, width) {
+                (WavSampleFormat::Int, 1) => out[0] = sample,
+                (WavSampleFormat::Int, 2) => {
+                    out.copy_from_slice(&((centred << 8) as i16).to_le_bytes())
+                }
+                (WavSampleFormat::Int, 3) => {
+                    out.copy_from_slice(&(centred << 16).to_le_bytes()[..3])
+                }
+                (WavSampleFormat::Int, _) => out.copy_from_slice(&(centred << 24).to_le_bytes()),
+                (WavSampleFormat::Float, 4) => {
+                    out.copy_from_slice(&(centred as f32 / 127.0).to_le_bytes())
+                }
+                (WavSampleFormat::Float, _) => {
+                    out.copy_from_slice(&(centred as f64 / 127.0).to_le_bytes())
+                }
+            }
+        }
+        self.writer.write_all(&self.buf)?;
+        self.data_len += self.buf.len() as u64;
+        Ok(())
+    }
+
+    /// Moves everything in the encoder's buffer to the file
+    pub fn write_encoder(&mut self, encoder: &mut LTCEncoder) -> Result<(), WavError> {
+        let (samples, _) = encoder.get_buf_ref(true);
+        self.write_samples(samples)
+    }
+
+    /// Flushes and returns the writer without touching the header
+    pub fn into_inner(mut self) -> Result<W, WavError> {
+        self.pad()?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn pad(&mut self) -> Result<(), WavError> {
+        if self.data_len % 2 == 1 {
+            self.writer.write_all(&[0])?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Writes the final sizes into the header and returns the writer
+    pub fn finalize(self) -> Result<W, WavError> {
+        let rf64 = self.data_len + self.data_size_offset + 4 > u32::MAX as u64;
+        self.finalize_as(rf64)
+    }
+
+    fn finalize_as(mut self, rf64: bool) -> Result<W, WavError> {
+        self.pad()?;
+        let riff_len = self.data_size_offset + 4 + self.data_len + (self.data_len & 1) - 8;
+        let end = self.writer.stream_position()?;
+
+        if rf64 {
+            self.writer.seek(SeekFrom::Start(0))?;
+            self.writer.write_all(b"RF64")?;
+            self.writer.write_all(&u32::MAX.to_le_bytes())?;
+            self.writer.seek(SeekFrom::Start(12))?;
+            self.writer.write_all(b"ds64")?;
+            self.writer.write_all(&JUNK_SIZE.to_le_bytes())?;
+            self.writer.write_all(&riff_len.to_le_bytes())?;
+            self.writer.write_all(&self.data_len.to_le_bytes())?;
+            self.writer.write_all(&self.len().to_le_bytes())?;
+            self.writer.write_all(&0u32.to_le_bytes())?;
+            self.writer.seek(SeekFrom::Start(self.data_size_offset))?;
+            self.writer.write_all(&u32::MAX.to_le_bytes())?;
+        } else {
+            self.writer.seek(SeekFrom::Start(RIFF_SIZE_OFFSET))?;
+            self.writer.write_all(&(riff_len as u32).to_le_bytes())?;
+            self.writer.seek(SeekFrom::Start(self.data_size_offset))?;
+            self.writer
+                .write_all(&(self.data_len as u32).to_le_bytes())?;
+        }
+
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+// RIFF header, JUNK chunk (room for ds64), fmt chunk, the BWF chunks and the data chunk header.
+// The sizes state an unknown length until the writer is finalized.
+fn wav_header(format: &WavFormat, timecode: Option<&BwfTimecode>) -> Vec<u8> {
+    let tag = match format.sample_format {
+        WavSampleFormat::Int => WAVE_FORMAT_PCM,
+        WavSampleFormat::Float => WAVE_FORMAT_IEEE_FLOAT,
+    };
+    // WAVE_FORMAT_EXTENSIBLE is required for more than 2 channels or 16 bits
+    let extensible = format.channels > 2 || format.bits_per_sample > 16;
+
+    let mut header = vec![];
+    header.extend_from_slice(b"RIFF");
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header.extend_from_slice(b"WAVE");
+    header.extend_from_slice(b"JUNK");
+    header.extend_from_slice(&JUNK_SIZE.to_le_bytes());
+    header.extend_from_slice(&[0; JUNK_SIZE as usize]);
+
+    header.extend_from_slice(b"fmt ");
+    header.extend_from_slice(&(if extensible { 40u32 } else { 16 }).to_le_bytes());
+    header.extend_from_slice(
+        &(if extensible {
+            WAVE_FORMAT_EXTENSIBLE
+        } else {
+            tag
+        })
+        .to_le_bytes(),
+    );
+    header.extend_from_slice(&format.channels.to_le_bytes());
+    header.extend_from_slice(&format.sample_rate.to_le_bytes());
+    header.extend_from_slice(&(format.sample_rate * format.block_align() as u32).to_le_bytes());
+    header.extend_from_slice(&(format.block_align() as u16).to_le_bytes());
+    header.extend_from_slice(&format.bits_per_sample.to_le_bytes());
+    if extensible {
+        header.extend_from_slice(&22u16.to_le_bytes());
+        header.extend_from_slice(&format.bits_per_sample.to_le_bytes());
+        // No speaker positions
+        header.extend_from_slice(&0u32.to_le_bytes());
+        header.extend_from_slice(&tag.to_le_bytes());
+        header.extend_from_slice(&SUBFORMAT_GUID_TAIL);
+    }
+
+    if let Some(timecode) = timecode {
+        for (id, mut body) in [
+            (b"bext", timecode.bext_chunk()),
+            (b"iXML", timecode.ixml_chunk(format.sample_rate)),
+        ] {
+            header.extend_from_slice(id);
+            header.extend_from_slice(&(body.len() as u32).to_le_bytes());
+            if body.len() % 2 == 1 {
+                body.push(0);
+            }
+            header.extend_from_slice(&body);
+        }
+    }
+
+    header.extend_from_slice(b"data");
+    header.extend_from_slice(&u32::MAX.to_le_bytes());
+    header
+}
+
+fn read_chunk<R: Read>(reader: &mut R, size: u32) -> Result<Vec<u8>, WavError> {
+    // Chunks are padded to an even size
+    let padded = size as u64 + (size as u64 & 1);
+    let mut body = vec![];
+    reader.take(padded).read_to_end(&mut body)?;
+    if (body.len() as u64) < padded {
+        return Err(WavError::InvalidHeader("truncated chunk"));
+    }
+    body.truncate(size as usize);
+    Ok(body)
+}
+
+fn parse_fmt(body: &[u8]) -> Result<WavFormat, WavError> {
+    if body.len() < 16 {
+        return Err(WavError::InvalidHeader("fmt chunk too short"));
+    }
+    let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+    let mut tag = u16_at(0);
+    if tag == WAVE_FORMAT_EXTENSIBLE {
+        if body.len() < 26 {
+            return Err(WavError::InvalidHeader("fmt chunk too short"));
+        }
+        // The first two bytes of the sub format GUID are the format tag
+        tag = u16_at(24);
+    }
+    let format = WavFormat {
+        sample_format: match tag {
+            WAVE_FORMAT_PCM => WavSampleFormat::Int,
+            WAVE_FORMAT_IEEE_FLOAT => WavSampleFormat::Float,
+            _ => return Err(WavError::UnsupportedFormat),
+        },
+        channels: u16_at(2),
+        sample_rate: u32::from_le_bytes(body[4..8].try_into().unwrap()),
+        bits_per_sample: u16_at(14),
+    };
+    let supported = match format.sample_format {
+        WavSampleFormat::Int => matches!(format.bits_per_sample, 8 | 16 | 24 | 32),
+        WavSampleFormat::Float => matches!(format.bits_per_sample, 32 | 64),
+    };
+    if !supported || format.channels == 0 {
+        return Err(WavError::UnsupportedFormat);
+    }
+    if u16_at(12) as usize != format.block_align() {
+        return Err(WavError::InvalidHeader("unexpected block align"));
+    }
+    Ok(format)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::encoder::{LTCEncoder, LTCEncoderConfig};
+    use crate::api::polarity::samples_to_f32;
+    use crate::api::test_util::encode;
+    use crate::api::{SMPTETimecode, Timezone};
+
+    // LTC on channel 1 of a stereo file, silence on channel 0
+    fn encode_stereo(frames: usize) -> Vec<f32> {
+        let samples = encode(frames);
+        let mut buf = vec![0.0; samples.len()];
+        samples_to_f32(&samples, &mut buf);
+        buf.into_iter().flat_map(|sample| [0.0, sample]).collect()
+    }
+
+    fn wav_bytes(tag: u16, bits: u16, samples: &[f32], rf64: bool) -> Vec<u8> {
+        let data: Vec<u8> = samples
+            .iter()
+            .flat_map(|&s| match (tag, bits) {
+                (WAVE_FORMAT_IEEE_FLOAT, 32) => s.to_le_bytes().to_vec(),
+                (WAVE_FORMAT_IEEE_FLOAT, _) => (s as f64).to_le_bytes().to_vec(),
+                (_, 8) => vec![(s * 127.0 + 128.0) as u8],
+                (_, 16) => ((s * 32767.0) as i16).to_le_bytes().to_vec(),
+                (_, 24) => ((s * 8_388_607.0) as i32).to_le_bytes()[..3].to_vec(),
+                _ => ((s as f64 * 2_147_483_647.0) as i32).to_le_bytes().to_vec(),
+            })
+            .collect();
+        let block_align = 2 * bits / 8;
+        let mut out = vec![];
+        out.extend_from_slice(if rf64 { b"RF64" } else { b"RIFF" });
+        out.extend_from_slice(&0xffff_ffffu32.to_le_bytes());
+        out.extend_from_slice(b"WAVE");
+        if rf64 {
+            out.extend_from_slice(b"ds64");
+            out.extend_from_slice(&28u32.to_le_bytes());
+            out.extend_from_slice(&0u64.to_le_bytes());
+            out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+            out.extend_from_slice(&0u64.to_le_bytes());
+            out.extend_from_slice(&0u32.to_le_bytes());
+        }
+        // An unknown chunk with odd size to exercise the padding
+        out.extend_from_slice(b"junk");
+        out.extend_from_slice(&3u32.to_le_bytes());
+        out.extend_from_slice(&[1, 2, 3, 0]);
+        out.extend_from_slice(b"fmt ");
+        out.extend_from_slice(&16u32.to_le_bytes());
+        out.extend_from_slice(&tag.to_le_bytes());
+        out.extend_from_slice(&2u16.to_le_bytes());
+        out.extend_from_slice(&48_000u32.to_le_bytes());
+        out.extend_from_slice(&(48_000 * block_align as u32).to_le_bytes());
+        out.extend_from_slice(&block_align.to_le_bytes());
+        out.extend_from_slice(&bits.to_le_bytes());
+        out.extend_from_slice(b"data");
+        let size = if rf64 { 0xffff_ffff } else { data.len() as u32 };
+        out.extend_from_slice(&size.to_le_bytes());
+        out.extend_from_slice(&data);
+        out
+    }
+
+    #[test]
+    fn test_wav_decode_formats() {
+        let samples = encode_stereo(10);
+        for (tag, bits) in [
+            (WAVE_FORMAT_PCM, 8),
+            (WAVE_FORMAT_PCM, 16),
+            (WAVE_FORMAT_PCM, 24),
+            (WAVE_FORMAT_PCM, 32),
+            (WAVE_FORMAT_IEEE_FLOAT, 32),
+            (WAVE_FORMAT_IEEE_FLOAT, 64),
+        ] {
+            for rf64 in [false, true] {
+                let bytes = wav_bytes(tag, bits, &samples, rf64);
+                let mut reader = WavReader::new(bytes.as_slice()).unwrap();
+                assert_eq!(reader.format().bits_per_sample, bits);
+                assert_eq!(reader.len(), Some(samples.len() as u64 / 2));
+
+                let mut decoder = LTCDecoder::try_new(&reader.decoder_config(25.0)).unwrap();
+                let mut frames = vec![];
+                reader
+                    .decode(&mut decoder, 1, |frame| frames.push(frame))
+                    .unwrap();
+                assert_eq!(reader.position(), samples.len() as u64 / 2);
+                assert!(frames.len() >= 8, "{bits} bit: {} frames", frames.len());
+                for frame in &frames {
+                    // 48kHz at 25fps
+                    assert!((frame.off_end() - frame.off_start() - 1920).abs() < 10);
+                    assert!(frame.off_end() < reader.position() as i64);
+                }
+
+                let mut silent = WavReader::new(bytes.as_slice()).unwrap();
+                let mut decoder = LTCDecoder::try_new(&silent.decoder_config(25.0)).unwrap();
+                let mut count = 0;
+                silent.decode(&mut decoder, 0, |_| count += 1).unwrap();
+                assert_eq!(count, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_wav_read_f32() {
+        let samples = encode_stereo(2);
+        let bytes = wav_bytes(WAVE_FORMAT_PCM, 16, &samples, false);
+        let mut reader = WavReader::new(bytes.as_slice()).unwrap();
+        let mut buf = vec![0.0; 100];
+        assert_eq!(reader.read_f32(1, &mut buf).unwrap(), 100);
+        for (read, expected) in buf.iter().zip(samples.iter().skip(1).step_by(2)) {
+            assert!((read - expected).abs() < 0.001);
+        }
+        assert!(matches!(
+            reader.read_f32(2, &mut buf),
+            Err(WavError::InvalidChannel(2))
+        ));
+    }
+
+    fn write_wav(format: WavFormat, ltc_channel: u16, frames: usize) -> Vec<u8> {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let mut writer = WavWriter::new(std::io::Cursor::new(vec![]), format, ltc_channel).unwrap();
+        for _ in 0..frames {
+            encoder.encode_frame();
+            writer.write_encoder(&mut encoder).unwrap();
+            encoder.inc_timecode().unwrap();
+        }
+        assert_eq!(writer.len(), 1920 * frames as u64);
+        writer.finalize().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_wav_writer_roundtrip() {
+        for (sample_format, bits) in [
+            (WavSampleFormat::Int, 8),
+            (WavSampleFormat::Int, 16),
+            (WavSampleFormat::Int, 24),
+            (WavSampleFormat::Int, 32),
+            (WavSampleFormat::Float, 32),
+            (WavSampleFormat::Float, 64),
+        ] {
+            for channels in [1, 2, 4] {
+                let format = WavFormat {
+                    sample_format,
+                    channels,
+                    sample_rate: 48_000,
+                    bits_per_sample: bits,
+                };
+                let ltc_channel = channels - 1;
+                let bytes = write_wav(format, ltc_channel, 10);
+                assert_eq!(&bytes[0..4], b"RIFF");
+                assert_eq!(
+                    u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize,
+                    bytes.len() - 8
+                );
+
+                let mut reader = WavReader::new(bytes.as_slice()).unwrap();
+                assert_eq!(reader.format(), format);
+                assert_eq!(reader.len(), Some(19_200));
+
+                let mut decoder = LTCDecoder::try_new(&reader.decoder_config(25.0)).unwrap();
+                let mut frames = 0;
+                reader
+                    .decode(&mut decoder, ltc_channel, |_| frames += 1)
+                    .unwrap();
+                assert!(frames >= 8);
+
+                if channels > 1 {
+                    let mut silent = WavReader::new(bytes.as_slice()).unwrap();
+                    let mut buf = vec![1.0; 19_200];
+                    silent.read_f32(0, &mut buf).unwrap();
+                    assert!(buf.iter().all(|&s| s == 0.0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_wav_writer_rf64_and_streaming() {
+        let format = WavFormat {
+            sample_format: WavSampleFormat::Int,
+            channels: 1,
+            sample_rate: 48_000,
+            bits_per_sample: 16,
+        };
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        encoder.encode_frame();
+        let (samples, _) = encoder.get_buf_ref(true);
+
+        let mut writer = WavWriter::new(std::io::Cursor::new(vec![]), format, 0).unwrap();
+        writer.write_samples(samples).unwrap();
+        let bytes = writer.finalize_as(true).unwrap().into_inner();
+        assert_eq!(&bytes[0..4], b"RF64");
+        let reader = WavReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.len(), Some(samples.len() as u64));
+
+        // Not seekable, the length stays unknown
+        let mut writer = WavWriter::new(vec![], format, 0).unwrap();
+        writer.write_samples(samples).unwrap();
+        let bytes = writer.into_inner().unwrap();
+        let mut reader = WavReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.len(), None);
+        let mut buf = vec![0.0; 4000];
+        assert_eq!(reader.read_f32(0, &mut buf).unwrap(), samples.len());
+
+        assert!(matches!(
+            WavWriter::new(vec![], format, 1),
+            Err(WavError::InvalidChannel(1))
+        ));
+    }
+
+    #[test]
+    fn test_bwf_time_reference() {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        let start = SMPTETimecode::new(Timezone::default(), 0, 0, 0, 10, 0, 0, 0);
+        encoder.set_timecode(&start);
+        let mut writer = WavWriter::for_encoder(
+            std::io::Cursor::new(vec![]),
+            &encoder,
+            WavSampleFormat::Int,
+            16,
+            2,
+            1,
+        )
+        .unwrap();
+        for _ in 0..10 {
+            encoder.encode_frame();
+            writer.write_encoder(&mut encoder).unwrap();
+            encoder.inc_timecode().unwrap();
+        }
+        let bytes = writer.finalize().unwrap().into_inner();
+
+        let mut reader = WavReader::new(bytes.as_slice()).unwrap();
+        assert_eq!(reader.time_reference(), Some(10 * 3600 * 48_000));
+        let bwf = reader.bwf_timecode().unwrap();
+        assert_eq!(bwf.fps, 25.0);
+        assert!(!bwf.drop_frame);
+
+        // The LTC agrees with the TimeReference, within the precision of the decoder
+        let sample_rate = reader.format().sample_rate;
+        let mut decoder = LTCDecoder::try_new(&reader.decoder_config(25.0)).unwrap();
+        let mut offsets = vec![];
+        reader
+            .decode(&mut decoder, 1, |frame| {
+                offsets.extend(bwf.ltc_offset(&frame, sample_rate))
+            })
+            .unwrap();
+        assert!(offsets.len() >= 8);
+        assert!(
+            offsets.iter().all(|offset| offset.abs() < 24),
+            "{offsets:?}"
+        );
+
+        // A file that starts a second later than its LTC says
+        let late = BwfTimecode {
+            time_reference: bwf.time_reference + 48_000,
+            ..bwf
+        };
+        let mut reader = WavReader::new(bytes.as_slice()).unwrap();
+        let mut decoder = LTCDecoder::try_new(&reader.decoder_config(25.0)).unwrap();
+        reader
+            .decode(&mut decoder, 1, |frame| {
+                let offset = late.ltc_offset(&frame, sample_rate).unwrap();
+                assert!((offset + 48_000).abs() < 24);
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn test_wav_invalid() {
+        assert!(WavReader::new(&b"RIFX\0\0\0\0WAVE"[..]).is_err());
+        let mut bytes = wav_bytes(WAVE_FORMAT_PCM, 16, &[0.0; 4], false);
+        // Unsupported bit depth
+        bytes[46] = 12;
+        assert!(matches!(
+            WavReader::new(bytes.as_slice()),
+            Err(WavError::UnsupportedFormat)
+        ));
+    }
+}
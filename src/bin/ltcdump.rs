@@ -0,0 +1,416 @@
+//! Decodes LTC from a WAV file, raw samples or stdin and prints one line per frame
+
+use std::fs::File;
+use std::io::{self, BufWriter, Cursor, Read, Write};
+use std::process::exit;
+
+use clap::{Parser, ValueEnum};
+use libltc_rs::prelude::*;
+
+const BUFFER_SIZE: usize = 4096;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Csv,
+    /// One JSON object per line
+    Json,
+}
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "ltcdump",
+    version,
+    about = "Decode LTC from a WAV or raw audio file"
+)]
+struct Args {
+    /// WAV (RIFF/RF64) or raw unsigned 8-bit mono file, `-` for stdin
+    #[arg(default_value = "-")]
+    input: String,
+
+    /// Channel to decode, WAV input only. Raw input must be mono
+    #[arg(short, long, default_value_t = 0)]
+    channel: u16,
+
+    /// Expected frame rate
+    #[arg(short, long, default_value_t = 25.0)]
+    fps: f64,
+
+    /// Sample rate of raw (unsigned 8-bit mono) input
+    #[arg(short = 'r', long, default_value_t = 48_000)]
+    sample_rate: u32,
+
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Read the user bits as a date (SMPTE 309M)
+    #[arg(short, long)]
+    date: bool,
+
+    /// Only print the summary
+    #[arg(short, long)]
+    summary: bool,
+}
+
+#[derive(Debug, Default)]
+struct Summary {
+    frames: usize,
+    reverse: usize,
+    discontinuities: usize,
+    first: Option<String>,
+    last: Option<String>,
+    min_volume: f64,
+    max_volume: f64,
+}
+
+struct Dump<W: Write> {
+    out: W,
+    format: OutputFormat,
+    flags: LtcBgFlags,
+    standard: LTCTVStandard,
+    fps: i32,
+    print_frames: bool,
+    previous: Option<LTCFrame>,
+    summary: Summary,
+}
+
+impl<W: Write> Dump<W> {
+    fn new(out: W, args: &Args) -> Self {
+        let mut flags = LtcBgFlags::default();
+        if args.date {
+            flags.set(LtcBgFlagsKind::LTC_USE_DATE);
+        }
+        let fps = args.fps.round() as i32;
+        let standard = match fps {
+            25 => LTCTVStandard::LTCTV_625_50,
+            24 => LTCTVStandard::LTCTV_FILM_24,
+            _ => LTCTVStandard::LTCTV_525_60,
+        };
+        Dump {
+            out,
+            format: args.output,
+            flags,
+            standard,
+            fps,
+            print_frames: !args.summary,
+            previous: None,
+            summary: Summary::default(),
+        }
+    }
+
+    fn header(&mut self) -> io::Result<()> {
+        if self.print_frames && self.format == OutputFormat::Csv {
+            writeln!(
+                self.out,
+                "off_start,off_end,timecode,user_bits,direction,volume,flags"
+            )?;
+        }
+        Ok(())
+    }
+
+    fn frame(&mut self, frame: &LTCFrameExt) -> io::Result<()> {
+        let ltc = frame.ltc();
+        let timecode = self.format_timecode(&ltc);
+        let volume = frame.volume();
+        let direction = if frame.reverse() { "R" } else { "F" };
+
+        let bgf = BinaryGroupFlags::from_frame(&ltc, self.standard);
+        let flags: Vec<&str> = [
+            (ltc.dfbit() != 0, "df"),
+            (bgf.bgf0, "bgf0"),
+            (bgf.bgf1, "bgf1"),
+            (bgf.bgf2, "bgf2"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+
+        self.track(&ltc, frame.reverse(), &timecode, volume);
+        if !self.print_frames {
+            return Ok(());
+        }
+
+        let user_bits = ltc.get_user_bits();
+        match self.format {
+            OutputFormat::Text => writeln!(
+                self.out,
+                "{:>10} {:>10} {timecode} {user_bits:08x} {direction} {volume:6.1} {}",
+                frame.off_start(),
+                frame.off_end(),
+                flags.join(",")
+            ),
+            OutputFormat::Csv => writeln!(
+                self.out,
+                "{},{},{timecode},{user_bits:08x},{direction},{volume:.1},{}",
+                frame.off_start(),
+                frame.off_end(),
+                flags.join(" ")
+            ),
+            OutputFormat::Json => writeln!(
+                self.out,
+                "{{\"off_start\":{},\"off_end\":{},\"timecode\":\"{timecode}\",\
+                 \"user_bits\":\"{user_bits:08x}\",\"reverse\":{},\"volume\":{volume:.1},\
+                 \"flags\":[{}]}}",
+                frame.off_start(),
+                frame.off_end(),
+                frame.reverse(),
+                flags
+                    .iter()
+                    .map(|flag| format!("\"{flag}\""))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+        }
+    }
+
+    // HH:MM:SS:FF, with `.` before the frames for drop frame and the date in front with --date
+    fn format_timecode(&self, ltc: &LTCFrame) -> String {
+        let tc = ltc.to_timecode(self.flags);
+        let separator = if ltc.dfbit() != 0 { '.' } else { ':' };
+        let time = format!(
+            "{:02}:{:02}:{:02}{separator}{:02}",
+            tc.hours(),
+            tc.minutes(),
+            tc.seconds(),
+            tc.frame()
+        );
+        if self.flags.contains(LtcBgFlagsKind::LTC_USE_DATE) {
+            let year = if tc.years() < 67 { 2000 } else { 1900 } + tc.years() as i32;
+            format!(
+                "{year:04}-{:02}-{:02} {} {time}",
+                tc.months(),
+                tc.days(),
+                tc.timezone()
+            )
+        } else {
+            time
+        }
+    }
+
+    fn track(&mut self, ltc: &LTCFrame, reverse: bool, timecode: &str, volume: f64) {
+        let summary = &mut self.summary;
+        if let Some(mut expected) = self.previous {
+            // Step the previous frame in the direction of play and compare the time
+            let stepped = if reverse {
+                expected.decrement(self.fps, self.standard, self.flags)
+            } else {
+                expected.increment(self.fps, self.standard, self.flags)
+            };
+            let expected = expected.to_timecode(self.flags);
+            let actual = ltc.to_timecode(self.flags);
+            let same = (expected.hours(), expected.minutes(), expected.seconds())
+                == (actual.hours(), actual.minutes(), actual.seconds())
+                && expected.frame() == actual.frame();
+            if stepped.is_err() || !same {
+                summary.discontinuities += 1;
+            }
+        }
+        self.previous = Some(*ltc);
+
+        if summary.frames == 0 {
+            summary.min_volume = volume;
+            summary.max_volume = volume;
+        }
+        summary.frames += 1;
+        summary.reverse += reverse as usize;
+        summary.min_volume = summary.min_volume.min(volume);
+        summary.max_volume = summary.max_volume.max(volume);
+        if summary.first.is_none() {
+            summary.first = Some(timecode.to_string());
+        }
+        summary.last = Some(timecode.to_string());
+    }
+
+    // To `out` with --summary, to `err` after the frames otherwise
+    fn write_summary(&mut self, samples: i64, err: &mut impl Write) -> io::Result<()> {
+        let summary = &self.summary;
+        let summary = format!(
+            "frames: {} ({} reverse)\n\
+             first: {}\n\
+             last: {}\n\
+             discontinuities: {}\n\
+             volume: {:.1} to {:.1} dBFS\n\
+             samples: {samples}\n",
+            summary.frames,
+            summary.reverse,
+            summary.first.as_deref().unwrap_or("-"),
+            summary.last.as_deref().unwrap_or("-"),
+            summary.discontinuities,
+            summary.min_volume,
+            summary.max_volume,
+        );
+        if self.print_frames {
+            self.out.flush()?;
+            err.write_all(summary.as_bytes())
+        } else {
+            self.out.write_all(summary.as_bytes())?;
+            self.out.flush()
+        }
+    }
+}
+
+type Error = Box<dyn std::error::Error>;
+
+fn run(args: &Args) -> Result<(), Error> {
+    let input: Box<dyn Read> = if args.input == "-" {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(File::open(&args.input).map_err(|e| format!("{}: {e}", args.input))?)
+    };
+    dump(
+        args,
+        input,
+        BufWriter::new(io::stdout().lock()),
+        &mut io::stderr(),
+    )
+}
+
+// Decodes `input`, printing the frames and/or the summary to `out` and `err`
+fn dump(
+    args: &Args,
+    mut input: impl Read,
+    out: impl Write,
+    err: &mut impl Write,
+) -> Result<(), Error> {
+    // Look at the first bytes to tell WAV from raw input, then put them back
+    let mut magic = Vec::with_capacity(4);
+    input.by_ref().take(4).read_to_end(&mut magic)?;
+    let is_wav = magic == b"RIFF" || magic == b"RF64";
+    let input = Cursor::new(magic).chain(input);
+
+    let mut dump = Dump::new(out, args);
+    dump.header()?;
+
+    let samples = if is_wav {
+        let mut reader = WavReader::new(input)?;
+        let mut decoder = LTCDecoder::try_new(&reader.decoder_config(args.fps))?;
+        let mut result = Ok(());
+        reader.decode(&mut decoder, args.channel, |frame| {
+            if result.is_ok() {
+                result = dump.frame(&frame);
+            }
+        })?;
+        result?;
+        reader.position() as i64
+    } else {
+        let config = LTCDecoderConfig {
+            initial_apv: (args.sample_rate as f64 / args.fps).round() as i32,
+            ..LTCDecoderConfig::default()
+        };
+        let mut decoder = LTCDecoder::try_new(&config)?;
+        let mut input = input;
+        let mut buf = vec![0; BUFFER_SIZE];
+        let mut total = 0;
+        loop {
+            let n = match input.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            decoder.write(&buf[..n], total);
+            total += n as i64;
+            while let Some(frame) = decoder.read() {
+                dump.frame(&frame)?;
+            }
+        }
+        total
+    };
+
+    dump.write_summary(samples, err)?;
+    Ok(())
+}
+
+fn main() {
+    let args = Args::parse();
+    if let Err(e) = run(&args) {
+        // Stop quietly when the output is closed, e.g. piped into `head`
+        if e.downcast_ref::<io::Error>()
+            .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe)
+        {
+            return;
+        }
+        eprintln!("ltcdump: {e}");
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 3 frames from 10:00:00:00 at 25 fps, 48 kHz, LTC on the second of two channels
+    fn wav() -> Vec<u8> {
+        let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+        encoder.set_timecode(&SMPTETimecode::new(
+            Timezone::default(),
+            0,
+            0,
+            0,
+            10,
+            0,
+            0,
+            0,
+        ));
+        let mut writer = WavWriter::for_encoder(
+            Cursor::new(Vec::new()),
+            &encoder,
+            WavSampleFormat::Int,
+            16,
+            2,
+            1,
+        )
+        .unwrap();
+        for _ in 0..4 {
+            encoder.encode_frame();
+            writer.write_encoder(&mut encoder).unwrap();
+            encoder.inc_timecode().unwrap();
+        }
+        writer.finalize().unwrap().into_inner()
+    }
+
+    fn run_dump(argv: &[&str], input: &[u8]) -> (String, String) {
+        let args = Args::try_parse_from(["ltcdump"].iter().chain(argv)).unwrap();
+        let (mut out, mut err) = (Vec::new(), Vec::new());
+        dump(&args, input, &mut out, &mut err).unwrap();
+        (
+            String::from_utf8(out).unwrap(),
+            String::from_utf8(err).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_text() {
+        let (out, err) = run_dump(&["-c", "1"], &wav());
+        let timecodes: Vec<&str> = out
+            .lines()
+            .map(|line| line.split_whitespace().nth(2).unwrap())
+            .collect();
+        assert_eq!(timecodes, ["10:00:00:00", "10:00:00:01", "10:00:00:02"]);
+        assert!(err.starts_with("frames: 3 (0 reverse)\nfirst: 10:00:00:00\n"));
+        assert!(err.contains("discontinuities: 0\n"));
+    }
+
+    #[test]
+    fn test_json() {
+        let (out, err) = run_dump(&["-c", "1", "-o", "json"], &wav());
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with("{\"off_start\":"));
+        assert!(lines[1].contains("\"timecode\":\"10:00:00:01\",\"user_bits\":\"00000000\""));
+        assert!(lines[1].contains("\"reverse\":false"));
+        assert!(!err.is_empty());
+
+        // The summary alone goes to stdout
+        let (out, err) = run_dump(&["-c", "1", "-s"], &wav());
+        assert!(out.starts_with("frames: 3"));
+        assert!(err.is_empty());
+    }
+
+    #[test]
+    fn test_silent_channel() {
+        let (out, err) = run_dump(&[], &wav());
+        assert!(out.is_empty());
+        assert!(err.starts_with("frames: 0"));
+    }
+}
@@ -0,0 +1,430 @@
+//! Generates an LTC signal as a WAV file, raw samples or on stdout
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::process::exit;
+
+use clap::error::ErrorKind;
+use clap::{ArgGroup, CommandFactory, Parser, ValueEnum};
+use libltc_rs::prelude::*;
+
+// Upper bound when generating up to an end timecode that is never reached
+const MAX_FRAMES: u64 = 24 * 3600 * 60;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Wav,
+    /// Unsigned 8-bit mono, as read by `ltcdump`
+    Raw,
+}
+
+#[derive(Debug, Parser)]
+#[command(name = "ltcgen", version, about = "Generate an LTC audio file")]
+#[command(group(ArgGroup::new("length").args(["duration", "frames", "end"])))]
+struct Args {
+    /// Output file, `-` for stdout
+    output: String,
+
+    /// Output format, by default from the file extension (raw for stdout)
+    #[arg(long, value_enum)]
+    format: Option<OutputFormat>,
+
+    /// Start timecode, HH:MM:SS:FF (HH:MM:SS;FF for drop frame)
+    #[arg(short, long, default_value = "00:00:00:00")]
+    start: String,
+
+    /// Length in seconds
+    #[arg(short, long)]
+    duration: Option<f64>,
+
+    /// Length in frames
+    #[arg(long)]
+    frames: Option<u64>,
+
+    /// End timecode (exclusive), HH:MM:SS:FF
+    #[arg(short, long)]
+    end: Option<String>,
+
+    /// Frame rate up to 30, e.g. 24, 25, 29.97 or 30
+    #[arg(short, long, default_value_t = 25.0)]
+    fps: f64,
+
+    /// Drop frame timecode (29.97 only)
+    #[arg(long)]
+    drop_frame: bool,
+
+    #[arg(short = 'r', long, default_value_t = 48_000)]
+    sample_rate: u32,
+
+    /// Bits per sample of WAV output: 8, 16, 24 or 32 (32 or 64 with --float) [default: 16,
+    /// 32 with --float]
+    #[arg(short, long)]
+    bits: Option<u16>,
+
+    /// Write WAV samples as floats
+    #[arg(long)]
+    float: bool,
+
+    /// Number of WAV channels, the LTC goes to --ltc-channel and the others are silent
+    #[arg(long, default_value_t = 1)]
+    channels: u16,
+
+    #[arg(long, default_value_t = 0)]
+    ltc_channel: u16,
+
+    /// Signal level in dBFS
+    #[arg(short, long, allow_negative_numbers = true)]
+    volume: Option<f64>,
+
+    /// User bits as 8 hex digits
+    #[arg(short, long, conflicts_with = "date")]
+    user_bits: Option<String>,
+
+    /// Date in the user bits (SMPTE 309M), YYYY-MM-DD
+    #[arg(long)]
+    date: Option<String>,
+
+    /// Timezone for --date, e.g. +0100
+    #[arg(long, default_value = "+0000", allow_hyphen_values = true)]
+    timezone: String,
+}
+
+type Error = Box<dyn std::error::Error>;
+
+impl Args {
+    fn bits(&self) -> u16 {
+        self.bits.unwrap_or(if self.float { 32 } else { 16 })
+    }
+}
+
+fn parse_args<I, T>(args: I) -> Result<Args, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let args = Args::try_parse_from(args)?;
+    let valid_bits: &[u16] = if args.float {
+        &[32, 64]
+    } else {
+        &[8, 16, 24, 32]
+    };
+    if !valid_bits.contains(&args.bits()) {
+        let kind = if args.float { "float" } else { "integer" };
+        return Err(Args::command().error(
+            ErrorKind::ArgumentConflict,
+            format!("{} bit {kind} samples are not supported", args.bits()),
+        ));
+    }
+    Ok(args)
+}
+
+// Hours, minutes, seconds, frames and whether `;` (drop frame) separated the frames
+fn parse_timecode(s: &str) -> Result<([u8; 4], bool), Error> {
+    let drop_frame = s.contains(';');
+    let fields: Vec<u8> = s
+        .split([':', ';', '.'])
+        .map(|field| field.parse::<u8>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("invalid timecode '{s}'"))?;
+    let [hours, minutes, seconds, frame] = fields[..] else {
+        return Err(format!("invalid timecode '{s}', expected HH:MM:SS:FF").into());
+    };
+    if hours > 23 || minutes > 59 || seconds > 59 {
+        return Err(format!("invalid timecode '{s}'").into());
+    }
+    Ok(([hours, minutes, seconds, frame], drop_frame))
+}
+
+// Fails for frame numbers past the frame rate and for the frames drop frame timecode skips:
+// the first two of every minute except every tenth
+fn check_timecode(s: &str, timecode: [u8; 4], fps: f64, drop_frame: bool) -> Result<(), Error> {
+    let [_, minutes, seconds, frame] = timecode;
+    if frame as f64 >= fps.ceil() {
+        return Err(
+            format!("invalid timecode '{s}', frame {frame} does not exist at {fps} fps").into(),
+        );
+    }
+    if drop_frame && seconds == 0 && minutes % 10 != 0 && frame < 2 {
+        return Err(format!("invalid timecode '{s}', it is skipped in drop frame timecode").into());
+    }
+    Ok(())
+}
+
+fn parse_date(s: &str) -> Result<(u8, u8, u8), Error> {
+    let invalid = || format!("invalid date '{s}', expected YYYY-MM-DD");
+    let fields: Vec<u32> = s
+        .split('-')
+        .map(|field| field.parse::<u32>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| invalid())?;
+    match fields[..] {
+        [year @ 1967..=2066, month @ 1..=12, day @ 1..=31] => {
+            Ok(((year % 100) as u8, month as u8, day as u8))
+        }
+        _ => Err(invalid().into()),
+    }
+}
+
+fn parse_timezone(s: &str) -> Result<Timezone, Error> {
+    let valid = s.len() == 5
+        && (s.starts_with('+') || s.starts_with('-'))
+        && s[1..].bytes().all(|b| b.is_ascii_digit());
+    if !valid {
+        return Err(format!("invalid timezone '{s}', expected +HHMM").into());
+    }
+    let mut raw = [0u8; 6];
+    raw[..5].copy_from_slice(s.as_bytes());
+    Ok(Timezone::from(raw))
+}
+
+fn standard(fps: f64) -> LTCTVStandard {
+    match fps.round() as i32 {
+        25 => LTCTVStandard::LTCTV_625_50,
+        24 => LTCTVStandard::LTCTV_FILM_24,
+        _ => LTCTVStandard::LTCTV_525_60,
+    }
+}
+
+fn setup_encoder(args: &Args) -> Result<LTCEncoder, Error> {
+    // An LTC frame counts at most 39 frames and drop frame only skips two, higher rates need
+    // a frame pair per LTC frame, which libltc doesn't do
+    if !(args.fps > 0.0 && args.fps <= 30.0) {
+        return Err(format!("invalid frame rate {}, LTC carries up to 30 fps", args.fps).into());
+    }
+    let mut flags = LtcBgFlags::default();
+    let (years, months, days) = match &args.date {
+        Some(date) => {
+            flags.set(LtcBgFlagsKind::LTC_USE_DATE);
+            parse_date(date)?
+        }
+        None => (0, 0, 0),
+    };
+    let config = LTCEncoderConfig {
+        sample_rate: args.sample_rate as f64,
+        fps: args.fps,
+        standard: standard(args.fps),
+        flags,
+    };
+    let mut encoder = LTCEncoder::try_new(&config)?;
+    if let Some(volume) = args.volume {
+        encoder.set_volume(volume)?;
+    }
+
+    let ([hours, minutes, seconds, frame], df_separator) = parse_timecode(&args.start)?;
+    let timezone = parse_timezone(&args.timezone)?;
+    encoder.set_timecode(&SMPTETimecode::new(
+        timezone, years, months, days, hours, minutes, seconds, frame,
+    ));
+    if let Some(user_bits) = &args.user_bits {
+        let user_bits = u32::from_str_radix(user_bits, 16)
+            .map_err(|_| format!("invalid user bits '{user_bits}', expected 8 hex digits"))?;
+        encoder.set_user_bits(user_bits);
+    }
+
+    let drop_frame = args.drop_frame || df_separator;
+    if drop_frame && (args.fps - 30000.0 / 1001.0).abs() >= 0.01 {
+        return Err("drop frame is only defined for 29.97 fps".into());
+    }
+    check_timecode(
+        &args.start,
+        [hours, minutes, seconds, frame],
+        args.fps,
+        drop_frame,
+    )?;
+    if let Some(end) = &args.end {
+        check_timecode(end, parse_timecode(end)?.0, args.fps, drop_frame)?;
+    }
+    let mut ltc = encoder.get_frame();
+    ltc.set_dfbit(drop_frame);
+    ltc.set_parity(config.standard);
+    encoder.set_frame(&ltc);
+    Ok(encoder)
+}
+
+// Encodes frame after frame and hands the samples to `sink`
+fn generate(
+    args: &Args,
+    encoder: &mut LTCEncoder,
+    mut sink: impl FnMut(&mut LTCEncoder) -> Result<(), Error>,
+) -> Result<u64, Error> {
+    let end = args.end.as_deref().map(parse_timecode).transpose()?;
+    let frames = match (args.duration, args.frames) {
+        (Some(duration), _) => (duration * args.fps).round() as u64,
+        (_, Some(frames)) => frames,
+        // One second unless an end timecode is given
+        _ if end.is_none() => args.fps.round() as u64,
+        _ => MAX_FRAMES,
+    };
+
+    let mut count = 0;
+    while count < frames {
+        if let Some(([hours, minutes, seconds, frame], _)) = end {
+            let tc = encoder.get_timecode();
+            if [tc.hours(), tc.minutes(), tc.seconds(), tc.frame()]
+                == [hours, minutes, seconds, frame]
+            {
+                break;
+            }
+        }
+        encoder.encode_frame();
+        sink(encoder)?;
+        encoder.inc_timecode()?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+fn run(args: &Args) -> Result<(), Error> {
+    let format = args.format.unwrap_or(if args.output.ends_with(".wav") {
+        OutputFormat::Wav
+    } else {
+        OutputFormat::Raw
+    });
+    let sample_format = if args.float {
+        WavSampleFormat::Float
+    } else {
+        WavSampleFormat::Int
+    };
+    let mut encoder = setup_encoder(args)?;
+
+    let frames = match (format, args.output.as_str()) {
+        (OutputFormat::Raw, "-") => {
+            let mut out = BufWriter::new(io::stdout().lock());
+            let frames = generate(args, &mut encoder, |encoder| {
+                Ok(out.write_all(encoder.get_buf_ref(true).0)?)
+            })?;
+            out.flush()?;
+            frames
+        }
+        (OutputFormat::Raw, path) => {
+            let mut out = BufWriter::new(File::create(path)?);
+            let frames = generate(args, &mut encoder, |encoder| {
+                Ok(out.write_all(encoder.get_buf_ref(true).0)?)
+            })?;
+            out.flush()?;
+            frames
+        }
+        (OutputFormat::Wav, "-") => {
+            // stdout cannot seek, the header states an unknown length
+            let mut writer = WavWriter::for_encoder(
+                BufWriter::new(io::stdout().lock()),
+                &encoder,
+                sample_format,
+                args.bits(),
+                args.channels,
+                args.ltc_channel,
+            )?;
+            let frames = generate(args, &mut encoder, |encoder| {
+                Ok(writer.write_encoder(encoder)?)
+            })?;
+            writer.into_inner()?.flush()?;
+            frames
+        }
+        (OutputFormat::Wav, path) => {
+            let mut writer = WavWriter::for_encoder(
+                BufWriter::new(File::create(path)?),
+                &encoder,
+                sample_format,
+                args.bits(),
+                args.channels,
+                args.ltc_channel,
+            )?;
+            let frames = generate(args, &mut encoder, |encoder| {
+                Ok(writer.write_encoder(encoder)?)
+            })?;
+            writer.finalize()?.flush()?;
+            frames
+        }
+    };
+
+    if args.output != "-" {
+        eprintln!("Wrote {frames} frames to '{}'", args.output);
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = parse_args(std::env::args_os()).unwrap_or_else(|e| e.exit());
+    if let Err(e) = run(&args) {
+        eprintln!("ltcgen: {e}");
+        exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(argv: &[&str]) -> Result<Args, clap::Error> {
+        parse_args(["ltcgen"].iter().chain(argv))
+    }
+
+    #[test]
+    fn test_bits() {
+        assert_eq!(args(&["out.wav"]).unwrap().bits(), 16);
+        assert_eq!(args(&["out.wav", "--float"]).unwrap().bits(), 32);
+        assert_eq!(
+            args(&["out.wav", "--float", "-b", "64"]).unwrap().bits(),
+            64
+        );
+        let e = args(&["out.wav", "--float", "-b", "16"]).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::ArgumentConflict);
+        assert!(args(&["out.wav", "-b", "12"]).is_err());
+        assert!(args(&["out.wav", "-d", "1", "--frames", "2"]).is_err());
+    }
+
+    #[test]
+    fn test_timecodes() {
+        let setup = |argv: &[&str]| setup_encoder(&args(argv).unwrap()).map(|_| ());
+        assert!(setup(&["-", "-s", "01:02:03:24"]).is_ok());
+        assert!(setup(&["-", "-s", "01:02:03:25"]).is_err());
+        assert!(setup(&["-", "-s", "00:00:00:00", "-e", "00:00:01:25"]).is_err());
+
+        let df = ["-", "-f", "29.97", "-s"];
+        assert!(setup(&[&df[..], &["00:01:00;02"]].concat()).is_ok());
+        assert!(setup(&[&df[..], &["00:10:00;00"]].concat()).is_ok());
+        assert!(setup(&[&df[..], &["00:01:00;00"]].concat()).is_err());
+        assert!(setup(&[&df[..], &["00:01:00:01", "--drop-frame"]].concat()).is_err());
+        assert!(setup(&[&df[..], &["00:00:50;00", "-e", "00:02:00;01"]].concat()).is_err());
+
+        assert!(setup(&["-", "-f", "30"]).is_ok());
+        assert!(setup(&["-", "-f", "50"]).is_err());
+        assert!(setup(&["-", "-f", "59.94", "--drop-frame"]).is_err());
+        assert!(setup(&["-", "-f", "0"]).is_err());
+        assert!(setup(&["-", "-f", "25", "--drop-frame"]).is_err());
+    }
+
+    #[test]
+    fn test_generate_decode() {
+        let args = args(&["-", "-s", "10:00:59:20", "--frames", "10"]).unwrap();
+        let mut encoder = setup_encoder(&args).unwrap();
+        let mut samples = Vec::new();
+        let frames = generate(&args, &mut encoder, |encoder| {
+            samples.extend_from_slice(encoder.get_buf_ref(true).0);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(frames, 10);
+
+        let config = LTCDecoderConfig {
+            initial_apv: 48_000 / 25,
+            ..LTCDecoderConfig::default()
+        };
+        let mut decoder = LTCDecoder::try_new(&config).unwrap();
+        decoder.write(&samples, 0);
+        let mut decoded = Vec::new();
+        while let Some(frame) = decoder.read() {
+            let tc = frame.ltc().to_timecode(LtcBgFlags::default());
+            decoded.push(format!(
+                "{:02}:{:02}:{:02}",
+                tc.minutes(),
+                tc.seconds(),
+                tc.frame()
+            ));
+        }
+        // The last frame is only complete with the next sync word
+        assert_eq!(decoded.len(), 9);
+        assert_eq!(decoded[0], "00:59:20");
+        assert_eq!(decoded[5], "01:00:00");
+    }
+}
@@ -1,4 +1,6 @@
-use std::error::Error;
+use core::error::Error;
+
+use crate::api::Timezone;
 
 // error.rs
 #[derive(Debug)]
@@ -9,12 +11,14 @@ pub enum LTCEncoderError {
     VolumeError,
     EncodeError,
     TimecodeError(TimecodeError),
+    UserBitsError(UserBitsError),
 }
 
 #[derive(Debug)]
 pub enum LTCDecoderError {
     CreateError,
     TImecodeError(TimecodeError),
+    QueueOverflow(usize),
 }
 
 #[derive(Debug)]
@@ -22,9 +26,67 @@ pub enum TimecodeError {
     InvalidReturn,
 }
 
+#[derive(Debug)]
+pub enum UserBitsError {
+    InvalidBcdDigit(u8),
+    NonAsciiCharacter(u8),
+    InvalidDate,
+    UnknownTimezone(Timezone),
+}
+
+#[derive(Debug)]
+pub enum PayloadError {
+    TooLong(usize),
+}
+
+#[cfg(feature = "wav")]
+#[derive(Debug)]
+pub enum WavError {
+    Io(std::io::Error),
+    InvalidHeader(&'static str),
+    UnsupportedFormat,
+    InvalidChannel(u16),
+}
+
+#[cfg(feature = "net")]
+#[derive(Debug)]
+pub enum NetError {
+    Io(std::io::Error),
+    InvalidPacket(&'static str),
+}
+
+#[derive(Debug)]
+pub enum SnapshotError {
+    InvalidMagic,
+    UnsupportedVersion(u8),
+    Truncated,
+    InvalidValue,
+}
+
 impl Error for LTCEncoderError {}
 impl Error for LTCDecoderError {}
 impl Error for TimecodeError {}
+impl Error for UserBitsError {}
+impl Error for PayloadError {}
+impl Error for SnapshotError {}
+#[cfg(feature = "wav")]
+impl Error for WavError {}
+#[cfg(feature = "net")]
+impl Error for NetError {}
+
+#[cfg(feature = "net")]
+impl From<std::io::Error> for NetError {
+    fn from(e: std::io::Error) -> Self {
+        NetError::Io(e)
+    }
+}
+
+#[cfg(feature = "wav")]
+impl From<std::io::Error> for WavError {
+    fn from(e: std::io::Error) -> Self {
+        WavError::Io(e)
+    }
+}
 
 impl From<TimecodeError> for LTCEncoderError {
     fn from(e: TimecodeError) -> Self {
@@ -32,14 +94,20 @@ impl From<TimecodeError> for LTCEncoderError {
     }
 }
 
+impl From<UserBitsError> for LTCEncoderError {
+    fn from(e: UserBitsError) -> Self {
+        LTCEncoderError::UserBitsError(e)
+    }
+}
+
 impl From<TimecodeError> for LTCDecoderError {
     fn from(e: TimecodeError) -> Self {
         LTCDecoderError::TImecodeError(e)
     }
 }
 
-impl std::fmt::Display for LTCEncoderError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for LTCEncoderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             LTCEncoderError::CreateError => write!(f, "Error creating LTC encoder"),
             LTCEncoderError::ReinitError => write!(f, "Error reinitializing LTC encoder"),
@@ -47,23 +115,77 @@ impl std::fmt::Display for LTCEncoderError {
             LTCEncoderError::VolumeError => write!(f, "Error setting volume"),
             LTCEncoderError::EncodeError => write!(f, "Error during encoding"),
             LTCEncoderError::TimecodeError(e) => write!(f, "Timecode error: {e}"),
+            LTCEncoderError::UserBitsError(e) => write!(f, "User bits error: {e}"),
         }
     }
 }
 
-impl std::fmt::Display for LTCDecoderError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for LTCDecoderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             LTCDecoderError::CreateError => write!(f, "Error creating LTC decoder"),
             LTCDecoderError::TImecodeError(e) => write!(f, "Timecode error: {e}"),
+            LTCDecoderError::QueueOverflow(n) => write!(f, "Queue full, {n} frames dropped"),
         }
     }
 }
 
-impl std::fmt::Display for TimecodeError {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl core::fmt::Display for TimecodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         match self {
             TimecodeError::InvalidReturn => write!(f, "Invalid return value from C function"),
         }
     }
 }
+
+impl core::fmt::Display for UserBitsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            UserBitsError::InvalidBcdDigit(d) => write!(f, "Invalid BCD digit: {d}"),
+            UserBitsError::NonAsciiCharacter(c) => write!(f, "Non ASCII character: {c:#04x}"),
+            UserBitsError::InvalidDate => write!(f, "Invalid date"),
+            UserBitsError::UnknownTimezone(tz) => write!(f, "Unknown timezone: {tz}"),
+        }
+    }
+}
+
+impl core::fmt::Display for PayloadError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            PayloadError::TooLong(len) => write!(f, "Payload too long: {len} bytes"),
+        }
+    }
+}
+
+impl core::fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            SnapshotError::InvalidMagic => write!(f, "Not a snapshot of this kind"),
+            SnapshotError::UnsupportedVersion(v) => write!(f, "Unsupported snapshot version: {v}"),
+            SnapshotError::Truncated => write!(f, "Snapshot is truncated"),
+            SnapshotError::InvalidValue => write!(f, "Invalid value in snapshot"),
+        }
+    }
+}
+
+#[cfg(feature = "wav")]
+impl core::fmt::Display for WavError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            WavError::Io(e) => write!(f, "IO error: {e}"),
+            WavError::InvalidHeader(reason) => write!(f, "Invalid WAV header: {reason}"),
+            WavError::UnsupportedFormat => write!(f, "Unsupported WAV sample format"),
+            WavError::InvalidChannel(c) => write!(f, "Channel {c} does not exist"),
+        }
+    }
+}
+
+#[cfg(feature = "net")]
+impl core::fmt::Display for NetError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            NetError::Io(e) => write!(f, "IO error: {e}"),
+            NetError::InvalidPacket(reason) => write!(f, "Invalid packet: {reason}"),
+        }
+    }
+}
@@ -1,18 +1,44 @@
 // lib.rs
+#![cfg_attr(not(feature = "std"), no_std)]
+// The math::F64Ext imports are unused in tests, see math.rs
+#![cfg_attr(all(test, not(feature = "std")), allow(unused_imports))]
+
+extern crate alloc;
 
 pub mod api;
 mod error;
+#[cfg(not(feature = "std"))]
+mod math;
 mod raw;
 
 pub mod prelude {
+    #[cfg(feature = "net")]
+    pub use super::api::artnet::*;
+    #[cfg(feature = "wav")]
+    pub use super::api::bwf::*;
     pub use super::api::consts::*;
     pub use super::api::decoder::*;
     pub use super::api::encoder::*;
+    pub use super::api::export::*;
+    pub use super::api::filter::*;
     pub use super::api::frame::*;
+    pub use super::api::mtc::*;
+    #[cfg(feature = "net")]
+    pub use super::api::osc::*;
+    pub use super::api::payload::*;
+    pub use super::api::polarity::*;
+    pub use super::api::snapshot::*;
+    pub use super::api::split::*;
+    #[cfg(feature = "async")]
+    pub use super::api::stream::*;
+    pub use super::api::userbits::*;
+    #[cfg(feature = "wav")]
+    pub use super::api::wav::*;
     pub use super::api::*;
 }
 
-// Assert that the library version is correct
+// With the bindgen feature, the version comes from the installed ltc.h, and the build fails
+// against anything but libltc 1.3.2. The committed bindings only restate it.
 const _: () = assert!(api::consts::LIBLTC_VERSION_MAJOR == 1);
 const _: () = assert!(api::consts::LIBLTC_VERSION_MINOR == 3);
 const _: () = assert!(api::consts::LIBLTC_VERSION_MICRO == 2);
@@ -0,0 +1,46 @@
+//! The f64 functions that need std, taken from libm in no_std builds
+
+// Most of them are only used by the pure-Rust codec. The test harness links std, whose
+// inherent methods then take precedence.
+#[cfg_attr(any(test, not(feature = "pure-rust")), allow(dead_code))]
+pub(crate) trait F64Ext {
+    fn round(self) -> f64;
+    fn round_ties_even(self) -> f64;
+    fn ceil(self) -> f64;
+    fn exp(self) -> f64;
+    fn ln(self) -> f64;
+    fn log10(self) -> f64;
+    fn powf(self, n: f64) -> f64;
+    fn sin(self) -> f64;
+    fn cos(self) -> f64;
+}
+
+impl F64Ext for f64 {
+    fn round(self) -> f64 {
+        libm::round(self)
+    }
+    fn round_ties_even(self) -> f64 {
+        libm::rint(self)
+    }
+    fn ceil(self) -> f64 {
+        libm::ceil(self)
+    }
+    fn exp(self) -> f64 {
+        libm::exp(self)
+    }
+    fn ln(self) -> f64 {
+        libm::log(self)
+    }
+    fn log10(self) -> f64 {
+        libm::log10(self)
+    }
+    fn powf(self, n: f64) -> f64 {
+        libm::pow(self, n)
+    }
+    fn sin(self) -> f64 {
+        libm::sin(self)
+    }
+    fn cos(self) -> f64 {
+        libm::cos(self)
+    }
+}
@@ -6,8 +6,15 @@ pub(crate) use autogen::*;
 
 // Several functions in this library are deprecated. We allow dead code to avoid warnings.
 #[allow(dead_code)]
+#[allow(clippy::useless_transmute, clippy::too_many_arguments)] // bindgen's bitfield accessors
 mod autogen {
+    // Generated from ltc.h at build time, see build.rs
+    #[cfg(feature = "bindgen")]
     include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+    // The bindings for libltc 1.3.2, compared with bindgen's output by the bindings CI job
+    #[cfg(not(feature = "bindgen"))]
+    include!("raw/bindings.rs");
 }
 
 /* For some reason, the generated code does not have default implementations for the structs.
@@ -0,0 +1,724 @@
+/* Written to match rust-bindgen 0.70's output for the libltc 1.3.2 ltc.h, not generated by it.
+ * The bindings CI job generates them from ltc.h and fails while they differ; commit its
+ * `bindings` artifact over this file. */
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct __BindgenBitfieldUnit<Storage> {
+    storage: Storage,
+}
+impl<Storage> __BindgenBitfieldUnit<Storage> {
+    #[inline]
+    pub const fn new(storage: Storage) -> Self {
+        Self { storage }
+    }
+}
+impl<Storage> __BindgenBitfieldUnit<Storage>
+where
+    Storage: AsRef<[u8]> + AsMut<[u8]>,
+{
+    #[inline]
+    pub fn get_bit(&self, index: usize) -> bool {
+        debug_assert!(index / 8 < self.storage.as_ref().len());
+        let byte_index = index / 8;
+        let byte = self.storage.as_ref()[byte_index];
+        let bit_index = if cfg!(target_endian = "big") {
+            7 - (index % 8)
+        } else {
+            index % 8
+        };
+        let mask = 1 << bit_index;
+        byte & mask == mask
+    }
+    #[inline]
+    pub fn set_bit(&mut self, index: usize, val: bool) {
+        debug_assert!(index / 8 < self.storage.as_ref().len());
+        let byte_index = index / 8;
+        let byte = &mut self.storage.as_mut()[byte_index];
+        let bit_index = if cfg!(target_endian = "big") {
+            7 - (index % 8)
+        } else {
+            index % 8
+        };
+        let mask = 1 << bit_index;
+        if val {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+    }
+    #[inline]
+    pub fn get(&self, bit_offset: usize, bit_width: u8) -> u64 {
+        debug_assert!(bit_width <= 64);
+        debug_assert!(bit_offset / 8 < self.storage.as_ref().len());
+        debug_assert!((bit_offset + (bit_width as usize)) / 8 <= self.storage.as_ref().len());
+        let mut val = 0;
+        for i in 0..(bit_width as usize) {
+            if self.get_bit(i + bit_offset) {
+                let index = if cfg!(target_endian = "big") {
+                    bit_width as usize - 1 - i
+                } else {
+                    i
+                };
+                val |= 1 << index;
+            }
+        }
+        val
+    }
+    #[inline]
+    pub fn set(&mut self, bit_offset: usize, bit_width: u8, val: u64) {
+        debug_assert!(bit_width <= 64);
+        debug_assert!(bit_offset / 8 < self.storage.as_ref().len());
+        debug_assert!((bit_offset + (bit_width as usize)) / 8 <= self.storage.as_ref().len());
+        for i in 0..(bit_width as usize) {
+            let mask = 1 << i;
+            let val_bit_is_set = val & mask == mask;
+            let index = if cfg!(target_endian = "big") {
+                bit_width as usize - 1 - i
+            } else {
+                i
+            };
+            self.set_bit(index + bit_offset, val_bit_is_set);
+        }
+    }
+}
+pub const LTC_H: u32 = 1;
+pub const LIBLTC_VERSION: &[u8; 6] = b"1.3.2\0";
+pub const LIBLTC_VERSION_MAJOR: u32 = 1;
+pub const LIBLTC_VERSION_MINOR: u32 = 3;
+pub const LIBLTC_VERSION_MICRO: u32 = 2;
+pub const LIBLTC_CUR: u32 = 13;
+pub const LIBLTC_REV: u32 = 1;
+pub const LIBLTC_AGE: u32 = 2;
+pub const LTC_FRAME_BIT_COUNT: u32 = 80;
+pub type wchar_t = ::core::ffi::c_int;
+#[repr(C)]
+#[repr(align(16))]
+#[derive(Debug, Copy, Clone)]
+pub struct max_align_t {
+    pub __clang_max_align_nonce1: ::core::ffi::c_longlong,
+    pub __bindgen_padding_0: u64,
+    pub __clang_max_align_nonce2: u128,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of max_align_t"][::core::mem::size_of::<max_align_t>() - 32usize];
+    ["Alignment of max_align_t"][::core::mem::align_of::<max_align_t>() - 16usize];
+    ["Offset of field: max_align_t::__clang_max_align_nonce1"]
+        [::core::mem::offset_of!(max_align_t, __clang_max_align_nonce1) - 0usize];
+    ["Offset of field: max_align_t::__clang_max_align_nonce2"]
+        [::core::mem::offset_of!(max_align_t, __clang_max_align_nonce2) - 16usize];
+};
+pub type ltcsnd_sample_t = ::core::ffi::c_uchar;
+pub type ltc_off_t = ::core::ffi::c_longlong;
+#[repr(C)]
+#[repr(align(4))]
+#[derive(Debug, Copy, Clone)]
+pub struct LTCFrame {
+    pub _bitfield_align_1: [u16; 0],
+    pub _bitfield_1: __BindgenBitfieldUnit<[u8; 10usize]>,
+    pub __bindgen_padding_0: u16,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of LTCFrame"][::core::mem::size_of::<LTCFrame>() - 12usize];
+    ["Alignment of LTCFrame"][::core::mem::align_of::<LTCFrame>() - 4usize];
+};
+impl LTCFrame {
+    #[inline]
+    pub fn frame_units(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(0usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_frame_units(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(0usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user1(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(4usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user1(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(4usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn frame_tens(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(8usize, 2u8) as u32) }
+    }
+    #[inline]
+    pub fn set_frame_tens(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(8usize, 2u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn dfbit(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(10usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_dfbit(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(10usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn col_frame(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(11usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_col_frame(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(11usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user2(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(12usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user2(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(12usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn secs_units(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(16usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_secs_units(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(16usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user3(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(20usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user3(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(20usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn secs_tens(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(24usize, 3u8) as u32) }
+    }
+    #[inline]
+    pub fn set_secs_tens(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(24usize, 3u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn biphase_mark_phase_correction(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(27usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_biphase_mark_phase_correction(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(27usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user4(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(28usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user4(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(28usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn mins_units(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(32usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_mins_units(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(32usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user5(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(36usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user5(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(36usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn mins_tens(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(40usize, 3u8) as u32) }
+    }
+    #[inline]
+    pub fn set_mins_tens(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(40usize, 3u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn binary_group_flag_bit0(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(43usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_binary_group_flag_bit0(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(43usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user6(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(44usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user6(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(44usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn hours_units(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(48usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_hours_units(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(48usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user7(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(52usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user7(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(52usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn hours_tens(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(56usize, 2u8) as u32) }
+    }
+    #[inline]
+    pub fn set_hours_tens(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(56usize, 2u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn binary_group_flag_bit1(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(58usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_binary_group_flag_bit1(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(58usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn binary_group_flag_bit2(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(59usize, 1u8) as u32) }
+    }
+    #[inline]
+    pub fn set_binary_group_flag_bit2(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(59usize, 1u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn user8(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(60usize, 4u8) as u32) }
+    }
+    #[inline]
+    pub fn set_user8(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(60usize, 4u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn sync_word(&self) -> ::core::ffi::c_uint {
+        unsafe { ::core::mem::transmute(self._bitfield_1.get(64usize, 16u8) as u32) }
+    }
+    #[inline]
+    pub fn set_sync_word(&mut self, val: ::core::ffi::c_uint) {
+        unsafe {
+            let val: u32 = ::core::mem::transmute(val);
+            self._bitfield_1.set(64usize, 16u8, val as u64)
+        }
+    }
+    #[inline]
+    pub fn new_bitfield_1(
+        frame_units: ::core::ffi::c_uint,
+        user1: ::core::ffi::c_uint,
+        frame_tens: ::core::ffi::c_uint,
+        dfbit: ::core::ffi::c_uint,
+        col_frame: ::core::ffi::c_uint,
+        user2: ::core::ffi::c_uint,
+        secs_units: ::core::ffi::c_uint,
+        user3: ::core::ffi::c_uint,
+        secs_tens: ::core::ffi::c_uint,
+        biphase_mark_phase_correction: ::core::ffi::c_uint,
+        user4: ::core::ffi::c_uint,
+        mins_units: ::core::ffi::c_uint,
+        user5: ::core::ffi::c_uint,
+        mins_tens: ::core::ffi::c_uint,
+        binary_group_flag_bit0: ::core::ffi::c_uint,
+        user6: ::core::ffi::c_uint,
+        hours_units: ::core::ffi::c_uint,
+        user7: ::core::ffi::c_uint,
+        hours_tens: ::core::ffi::c_uint,
+        binary_group_flag_bit1: ::core::ffi::c_uint,
+        binary_group_flag_bit2: ::core::ffi::c_uint,
+        user8: ::core::ffi::c_uint,
+        sync_word: ::core::ffi::c_uint,
+    ) -> __BindgenBitfieldUnit<[u8; 10usize]> {
+        let mut __bindgen_bitfield_unit: __BindgenBitfieldUnit<[u8; 10usize]> = Default::default();
+        __bindgen_bitfield_unit.set(0usize, 4u8, {
+            let frame_units: u32 = unsafe { ::core::mem::transmute(frame_units) };
+            frame_units as u64
+        });
+        __bindgen_bitfield_unit.set(4usize, 4u8, {
+            let user1: u32 = unsafe { ::core::mem::transmute(user1) };
+            user1 as u64
+        });
+        __bindgen_bitfield_unit.set(8usize, 2u8, {
+            let frame_tens: u32 = unsafe { ::core::mem::transmute(frame_tens) };
+            frame_tens as u64
+        });
+        __bindgen_bitfield_unit.set(10usize, 1u8, {
+            let dfbit: u32 = unsafe { ::core::mem::transmute(dfbit) };
+            dfbit as u64
+        });
+        __bindgen_bitfield_unit.set(11usize, 1u8, {
+            let col_frame: u32 = unsafe { ::core::mem::transmute(col_frame) };
+            col_frame as u64
+        });
+        __bindgen_bitfield_unit.set(12usize, 4u8, {
+            let user2: u32 = unsafe { ::core::mem::transmute(user2) };
+            user2 as u64
+        });
+        __bindgen_bitfield_unit.set(16usize, 4u8, {
+            let secs_units: u32 = unsafe { ::core::mem::transmute(secs_units) };
+            secs_units as u64
+        });
+        __bindgen_bitfield_unit.set(20usize, 4u8, {
+            let user3: u32 = unsafe { ::core::mem::transmute(user3) };
+            user3 as u64
+        });
+        __bindgen_bitfield_unit.set(24usize, 3u8, {
+            let secs_tens: u32 = unsafe { ::core::mem::transmute(secs_tens) };
+            secs_tens as u64
+        });
+        __bindgen_bitfield_unit.set(27usize, 1u8, {
+            let biphase_mark_phase_correction: u32 =
+                unsafe { ::core::mem::transmute(biphase_mark_phase_correction) };
+            biphase_mark_phase_correction as u64
+        });
+        __bindgen_bitfield_unit.set(28usize, 4u8, {
+            let user4: u32 = unsafe { ::core::mem::transmute(user4) };
+            user4 as u64
+        });
+        __bindgen_bitfield_unit.set(32usize, 4u8, {
+            let mins_units: u32 = unsafe { ::core::mem::transmute(mins_units) };
+            mins_units as u64
+        });
+        __bindgen_bitfield_unit.set(36usize, 4u8, {
+            let user5: u32 = unsafe { ::core::mem::transmute(user5) };
+            user5 as u64
+        });
+        __bindgen_bitfield_unit.set(40usize, 3u8, {
+            let mins_tens: u32 = unsafe { ::core::mem::transmute(mins_tens) };
+            mins_tens as u64
+        });
+        __bindgen_bitfield_unit.set(43usize, 1u8, {
+            let binary_group_flag_bit0: u32 =
+                unsafe { ::core::mem::transmute(binary_group_flag_bit0) };
+            binary_group_flag_bit0 as u64
+        });
+        __bindgen_bitfield_unit.set(44usize, 4u8, {
+            let user6: u32 = unsafe { ::core::mem::transmute(user6) };
+            user6 as u64
+        });
+        __bindgen_bitfield_unit.set(48usize, 4u8, {
+            let hours_units: u32 = unsafe { ::core::mem::transmute(hours_units) };
+            hours_units as u64
+        });
+        __bindgen_bitfield_unit.set(52usize, 4u8, {
+            let user7: u32 = unsafe { ::core::mem::transmute(user7) };
+            user7 as u64
+        });
+        __bindgen_bitfield_unit.set(56usize, 2u8, {
+            let hours_tens: u32 = unsafe { ::core::mem::transmute(hours_tens) };
+            hours_tens as u64
+        });
+        __bindgen_bitfield_unit.set(58usize, 1u8, {
+            let binary_group_flag_bit1: u32 =
+                unsafe { ::core::mem::transmute(binary_group_flag_bit1) };
+            binary_group_flag_bit1 as u64
+        });
+        __bindgen_bitfield_unit.set(59usize, 1u8, {
+            let binary_group_flag_bit2: u32 =
+                unsafe { ::core::mem::transmute(binary_group_flag_bit2) };
+            binary_group_flag_bit2 as u64
+        });
+        __bindgen_bitfield_unit.set(60usize, 4u8, {
+            let user8: u32 = unsafe { ::core::mem::transmute(user8) };
+            user8 as u64
+        });
+        __bindgen_bitfield_unit.set(64usize, 16u8, {
+            let sync_word: u32 = unsafe { ::core::mem::transmute(sync_word) };
+            sync_word as u64
+        });
+        __bindgen_bitfield_unit
+    }
+}
+pub const LTC_TV_STANDARD_LTC_TV_525_60: LTC_TV_STANDARD = 0;
+pub const LTC_TV_STANDARD_LTC_TV_625_50: LTC_TV_STANDARD = 1;
+pub const LTC_TV_STANDARD_LTC_TV_1125_60: LTC_TV_STANDARD = 2;
+pub const LTC_TV_STANDARD_LTC_TV_FILM_24: LTC_TV_STANDARD = 3;
+pub type LTC_TV_STANDARD = ::core::ffi::c_uint;
+pub const LTC_BG_FLAGS_LTC_USE_DATE: LTC_BG_FLAGS = 1;
+pub const LTC_BG_FLAGS_LTC_TC_CLOCK: LTC_BG_FLAGS = 2;
+pub const LTC_BG_FLAGS_LTC_BGF_DONT_TOUCH: LTC_BG_FLAGS = 4;
+pub const LTC_BG_FLAGS_LTC_NO_PARITY: LTC_BG_FLAGS = 8;
+pub type LTC_BG_FLAGS = ::core::ffi::c_uint;
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct LTCFrameExt {
+    pub ltc: LTCFrame,
+    pub off_start: ltc_off_t,
+    pub off_end: ltc_off_t,
+    pub reverse: ::core::ffi::c_int,
+    pub biphase_tics: [f32; 80usize],
+    pub sample_min: ltcsnd_sample_t,
+    pub sample_max: ltcsnd_sample_t,
+    pub volume: f64,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of LTCFrameExt"][::core::mem::size_of::<LTCFrameExt>() - 368usize];
+    ["Alignment of LTCFrameExt"][::core::mem::align_of::<LTCFrameExt>() - 8usize];
+    ["Offset of field: LTCFrameExt::ltc"][::core::mem::offset_of!(LTCFrameExt, ltc) - 0usize];
+    ["Offset of field: LTCFrameExt::off_start"]
+        [::core::mem::offset_of!(LTCFrameExt, off_start) - 16usize];
+    ["Offset of field: LTCFrameExt::off_end"]
+        [::core::mem::offset_of!(LTCFrameExt, off_end) - 24usize];
+    ["Offset of field: LTCFrameExt::reverse"]
+        [::core::mem::offset_of!(LTCFrameExt, reverse) - 32usize];
+    ["Offset of field: LTCFrameExt::biphase_tics"]
+        [::core::mem::offset_of!(LTCFrameExt, biphase_tics) - 36usize];
+    ["Offset of field: LTCFrameExt::sample_min"]
+        [::core::mem::offset_of!(LTCFrameExt, sample_min) - 356usize];
+    ["Offset of field: LTCFrameExt::sample_max"]
+        [::core::mem::offset_of!(LTCFrameExt, sample_max) - 357usize];
+    ["Offset of field: LTCFrameExt::volume"]
+        [::core::mem::offset_of!(LTCFrameExt, volume) - 360usize];
+};
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct SMPTETimecode {
+    pub timezone: [::core::ffi::c_char; 6usize],
+    pub years: ::core::ffi::c_uchar,
+    pub months: ::core::ffi::c_uchar,
+    pub days: ::core::ffi::c_uchar,
+    pub hours: ::core::ffi::c_uchar,
+    pub mins: ::core::ffi::c_uchar,
+    pub secs: ::core::ffi::c_uchar,
+    pub frame: ::core::ffi::c_uchar,
+}
+#[allow(clippy::unnecessary_operation, clippy::identity_op)]
+const _: () = {
+    ["Size of SMPTETimecode"][::core::mem::size_of::<SMPTETimecode>() - 13usize];
+    ["Alignment of SMPTETimecode"][::core::mem::align_of::<SMPTETimecode>() - 1usize];
+    ["Offset of field: SMPTETimecode::timezone"]
+        [::core::mem::offset_of!(SMPTETimecode, timezone) - 0usize];
+    ["Offset of field: SMPTETimecode::years"]
+        [::core::mem::offset_of!(SMPTETimecode, years) - 6usize];
+    ["Offset of field: SMPTETimecode::months"]
+        [::core::mem::offset_of!(SMPTETimecode, months) - 7usize];
+    ["Offset of field: SMPTETimecode::days"][::core::mem::offset_of!(SMPTETimecode, days) - 8usize];
+    ["Offset of field: SMPTETimecode::hours"]
+        [::core::mem::offset_of!(SMPTETimecode, hours) - 9usize];
+    ["Offset of field: SMPTETimecode::mins"][::core::mem::offset_of!(SMPTETimecode, mins) - 10usize];
+    ["Offset of field: SMPTETimecode::secs"][::core::mem::offset_of!(SMPTETimecode, secs) - 11usize];
+    ["Offset of field: SMPTETimecode::frame"]
+        [::core::mem::offset_of!(SMPTETimecode, frame) - 12usize];
+};
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct LTCDecoder {
+    _unused: [u8; 0],
+}
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct LTCEncoder {
+    _unused: [u8; 0],
+}
+extern "C" {
+    pub fn ltc_frame_to_time(
+        stime: *mut SMPTETimecode,
+        frame: *mut LTCFrame,
+        flags: ::core::ffi::c_int,
+    );
+    pub fn ltc_time_to_frame(
+        frame: *mut LTCFrame,
+        stime: *mut SMPTETimecode,
+        standard: LTC_TV_STANDARD,
+        flags: ::core::ffi::c_int,
+    );
+    pub fn ltc_frame_reset(frame: *mut LTCFrame);
+    pub fn ltc_frame_increment(
+        frame: *mut LTCFrame,
+        fps: ::core::ffi::c_int,
+        standard: LTC_TV_STANDARD,
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_frame_decrement(
+        frame: *mut LTCFrame,
+        fps: ::core::ffi::c_int,
+        standard: LTC_TV_STANDARD,
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_decoder_create(
+        apv: ::core::ffi::c_int,
+        queue_size: ::core::ffi::c_int,
+    ) -> *mut LTCDecoder;
+    pub fn ltc_decoder_free(d: *mut LTCDecoder) -> ::core::ffi::c_int;
+    pub fn ltc_decoder_write(
+        d: *mut LTCDecoder,
+        buf: *mut ltcsnd_sample_t,
+        size: usize,
+        posinfo: ltc_off_t,
+    );
+    pub fn ltc_decoder_write_double(
+        d: *mut LTCDecoder,
+        buf: *mut f64,
+        size: usize,
+        posinfo: ltc_off_t,
+    );
+    pub fn ltc_decoder_write_float(
+        d: *mut LTCDecoder,
+        buf: *mut f32,
+        size: usize,
+        posinfo: ltc_off_t,
+    );
+    pub fn ltc_decoder_write_s16(
+        d: *mut LTCDecoder,
+        buf: *mut ::core::ffi::c_short,
+        size: usize,
+        posinfo: ltc_off_t,
+    );
+    pub fn ltc_decoder_write_u16(
+        d: *mut LTCDecoder,
+        buf: *mut ::core::ffi::c_ushort,
+        size: usize,
+        posinfo: ltc_off_t,
+    );
+    pub fn ltc_decoder_read(d: *mut LTCDecoder, frame: *mut LTCFrameExt) -> ::core::ffi::c_int;
+    pub fn ltc_decoder_queue_flush(d: *mut LTCDecoder);
+    pub fn ltc_decoder_queue_length(d: *mut LTCDecoder) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_create(
+        sample_rate: f64,
+        fps: f64,
+        standard: LTC_TV_STANDARD,
+        flags: ::core::ffi::c_int,
+    ) -> *mut LTCEncoder;
+    pub fn ltc_encoder_free(e: *mut LTCEncoder);
+    pub fn ltc_encoder_set_timecode(e: *mut LTCEncoder, t: *mut SMPTETimecode);
+    pub fn ltc_encoder_get_timecode(e: *mut LTCEncoder, t: *mut SMPTETimecode);
+    pub fn ltc_encoder_set_user_bits(e: *mut LTCEncoder, data: ::core::ffi::c_ulong);
+    pub fn ltc_encoder_inc_timecode(e: *mut LTCEncoder) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_dec_timecode(e: *mut LTCEncoder) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_set_frame(e: *mut LTCEncoder, f: *mut LTCFrame);
+    pub fn ltc_encoder_get_frame(e: *mut LTCEncoder, f: *mut LTCFrame);
+    pub fn ltc_encoder_get_buffer(
+        e: *mut LTCEncoder,
+        buf: *mut ltcsnd_sample_t,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_copy_buffer(
+        e: *mut LTCEncoder,
+        buf: *mut ltcsnd_sample_t,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_get_bufptr(
+        e: *mut LTCEncoder,
+        buf: *mut *mut ltcsnd_sample_t,
+        flush: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_get_bufferptr(
+        e: *mut LTCEncoder,
+        buf: *mut *mut ltcsnd_sample_t,
+        flush: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_buffer_flush(e: *mut LTCEncoder);
+    pub fn ltc_encoder_get_buffersize(e: *mut LTCEncoder) -> usize;
+    pub fn ltc_encoder_reinit(
+        e: *mut LTCEncoder,
+        sample_rate: f64,
+        fps: f64,
+        standard: LTC_TV_STANDARD,
+        flags: ::core::ffi::c_int,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_reset(e: *mut LTCEncoder);
+    pub fn ltc_encoder_set_bufsize(
+        e: *mut LTCEncoder,
+        sample_rate: f64,
+        fps: f64,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_set_buffersize(
+        e: *mut LTCEncoder,
+        sample_rate: f64,
+        fps: f64,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_get_volume(e: *mut LTCEncoder) -> f64;
+    pub fn ltc_encoder_set_volume(e: *mut LTCEncoder, dBFS: f64) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_get_filter(e: *mut LTCEncoder) -> f64;
+    pub fn ltc_encoder_set_filter(e: *mut LTCEncoder, rise_time: f64);
+    pub fn ltc_encoder_encode_byte(
+        e: *mut LTCEncoder,
+        byte: ::core::ffi::c_int,
+        speed: f64,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_end_encode(e: *mut LTCEncoder) -> ::core::ffi::c_int;
+    pub fn ltc_encoder_encode_frame(e: *mut LTCEncoder);
+    pub fn ltc_encoder_encode_reversed_frame(e: *mut LTCEncoder);
+    pub fn ltc_frame_set_parity(frame: *mut LTCFrame, standard: LTC_TV_STANDARD);
+    pub fn parse_bcg_flags(f: *mut LTCFrame, standard: LTC_TV_STANDARD) -> ::core::ffi::c_int;
+    pub fn ltc_frame_parse_bcg_flags(
+        f: *mut LTCFrame,
+        standard: LTC_TV_STANDARD,
+    ) -> ::core::ffi::c_int;
+    pub fn ltc_frame_alignment(samples_per_frame: f64, standard: LTC_TV_STANDARD) -> ltc_off_t;
+    pub fn ltc_frame_get_user_bits(f: *mut LTCFrame) -> ::core::ffi::c_ulong;
+}
@@ -0,0 +1,94 @@
+//! The encode and decode hot paths must not allocate once everything is constructed. This is
+//! its own test binary so the counting allocator doesn't see the other tests.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use libltc_rs::prelude::*;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    // Fails during thread teardown, nothing to count then
+    let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+// Allocations made by this thread while running `f`
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn test_hot_paths_do_not_allocate() {
+    let mut encoder = LTCEncoder::try_new(&LTCEncoderConfig::default()).unwrap();
+    encoder.set_band_limit(Some(BAND_LIMIT_CUTOFF_HZ));
+    encoder.set_polarity(Polarity::Inverted);
+    let mut decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+    decoder.track_polarity(true);
+    let mut float_decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+    let mut i16_decoder = LTCDecoder::try_new(&LTCDecoderConfig::default()).unwrap();
+
+    let mut frame = LTCFrameExt::default();
+    let mut timecode = SMPTETimecode::default();
+    let mut floats = vec![0.0; encoder.get_buffersize()];
+    let mut ints = vec![0; encoder.get_buffersize()];
+    let mut decoded = Vec::with_capacity(100);
+
+    let mut pos = 0;
+    let count = allocations(|| {
+        for _ in 0..50 {
+            encoder.encode_frame();
+            let (buf, len) = encoder.get_buf_ref(true);
+            samples_to_f32(buf, &mut floats[..len]);
+            samples_to_i16(buf, &mut ints[..len]);
+            decoder.write(buf, pos);
+            float_decoder.write_float(&floats[..len], pos);
+            i16_decoder.write_i16(&ints[..len], pos);
+            pos += len as i64;
+            encoder.inc_timecode().unwrap();
+
+            while decoder.read_into(&mut frame) {
+                frame
+                    .ltc()
+                    .to_timecode_inplace(&mut timecode, LtcBgFlags::default());
+                decoded.push((timecode.seconds(), timecode.frame()));
+                assert_eq!(decoder.detect_polarity(&frame), Some(Polarity::Inverted));
+            }
+            while float_decoder.read_into(&mut frame) || i16_decoder.read_into(&mut frame) {}
+        }
+    });
+
+    assert_eq!(count, 0);
+    assert!(decoded.len() > 45);
+    assert_eq!(decoded[1], (0, 1));
+}